@@ -16,7 +16,7 @@ pub struct Args {
     #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
-    /// Suppress non-error output
+    /// Suppress warnings and other non-error output
     #[arg(short, long, global = true)]
     pub quiet: bool,
 }
@@ -36,7 +36,7 @@ pub enum Command {
         #[arg(long = "schema-dir", value_name = "DIR")]
         schema_dir: Option<PathBuf>,
 
-        /// Path to configuration file (default: sqlsift.toml in current or parent directory)
+        /// Configuration file to use instead of searching for sqlsift.toml in the current or parent directories
         #[arg(short, long = "config", value_name = "FILE")]
         config: Option<PathBuf>,
 
@@ -44,19 +44,46 @@ pub enum Command {
         #[arg(long = "disable", value_name = "RULE")]
         disable: Vec<String>,
 
-        /// SQL dialect
-        #[arg(short, long, default_value = "postgresql")]
-        dialect: String,
+        /// Enable opt-in rules (e.g., W1002)
+        #[arg(long = "enable", value_name = "RULE")]
+        enable: Vec<String>,
+
+        /// SQL dialect: postgresql, mysql or sqlite (default: postgresql)
+        #[arg(short, long)]
+        dialect: Option<String>,
 
         /// Output format
         #[arg(short, long, value_enum)]
         format: Option<OutputFormat>,
 
+        /// Cache the built schema catalog in this file, reusing it while the schema is unchanged
+        #[arg(long = "cache", value_name = "FILE")]
+        cache: Option<PathBuf>,
+
         /// Maximum number of errors before stopping
         #[arg(long, default_value = "100")]
         max_errors: usize,
     },
 
+    /// Compare two versions of a schema and warn about destructive changes
+    Diff {
+        /// Schema definition files before the change
+        #[arg(long = "from", value_name = "FILE", required = true, num_args = 1..)]
+        from: Vec<PathBuf>,
+
+        /// Schema definition files after the change
+        #[arg(long = "to", value_name = "FILE", required = true, num_args = 1..)]
+        to: Vec<PathBuf>,
+
+        /// SQL dialect: postgresql, mysql or sqlite (default: postgresql)
+        #[arg(short, long)]
+        dialect: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
     /// Display schema information
     Schema {
         /// Schema definition files
@@ -80,4 +107,6 @@ pub enum OutputFormat {
     Json,
     /// SARIF output (for GitHub Code Scanning)
     Sarif,
+    /// GitHub Actions workflow commands (inline PR annotations)
+    Github,
 }
@@ -2,6 +2,8 @@
 
 use miette::{IntoDiagnostic, Result};
 use serde::{Deserialize, Serialize};
+use sqlsift_core::RuleLevel;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Configuration for sqlsift
@@ -15,7 +17,7 @@ pub struct Config {
     #[serde(default)]
     pub files: Vec<String>,
 
-    /// SQL dialect (currently only "postgresql" is supported)
+    /// SQL dialect ("postgresql", "mysql" or "sqlite")
     #[serde(default)]
     pub dialect: Option<String>,
 
@@ -27,19 +29,64 @@ pub struct Config {
     #[serde(default)]
     pub disable: Vec<String>,
 
+    /// Opt-in rules to enable (e.g., ["W1002"])
+    #[serde(default)]
+    pub enable: Vec<String>,
+
+    /// Severity overrides by rule code or name (e.g., E0003 = "warning")
+    #[serde(default)]
+    pub severity: HashMap<String, RuleLevel>,
+
+    /// Report type-derived diagnostics such as E0003 (default: true)
+    #[serde(default)]
+    pub type_checking: Option<bool>,
+
+    /// Analyze the SQL inside PL/pgSQL `DO` blocks (default: false)
+    #[serde(default)]
+    pub plpgsql: Option<bool>,
+
+    /// Schemas searched in order for unqualified table names (PostgreSQL search_path)
+    #[serde(default)]
+    pub search_path: Vec<String>,
+
     /// Schema directory
     pub schema_dir: Option<String>,
+
+    /// File caching the built schema catalog between runs
+    #[serde(default)]
+    pub cache: Option<String>,
 }
 
 impl Config {
     /// Load configuration from a TOML file
+    ///
+    /// A file with a `[tool.sqlsift]` table (e.g. `pyproject.toml`) is read
+    /// from that table; any other file is read as a standalone config.
     pub fn from_file(path: &PathBuf) -> Result<Self> {
         let contents = std::fs::read_to_string(path).into_diagnostic()?;
+        if let Some(config) = Self::from_tool_table(&contents)? {
+            return Ok(config);
+        }
         let config: Config = toml::from_str(&contents).into_diagnostic()?;
         Ok(config)
     }
 
-    /// Try to find and load sqlsift.toml in current directory or parent directories
+    /// Parse the `[tool.sqlsift]` table of a shared TOML file, if present
+    fn from_tool_table(contents: &str) -> Result<Option<Self>> {
+        let mut document: toml::Table = toml::from_str(contents).into_diagnostic()?;
+        let Some(table) = document
+            .remove("tool")
+            .and_then(|tool| tool.get("sqlsift").cloned())
+        else {
+            return Ok(None);
+        };
+        table.try_into().map(Some).into_diagnostic()
+    }
+
+    /// Try to find and load configuration in current directory or parent directories
+    ///
+    /// In each directory `sqlsift.toml` wins; otherwise a `[tool.sqlsift]`
+    /// table in `pyproject.toml` is used.
     pub fn find_and_load() -> Result<Option<Self>> {
         let mut current_dir = std::env::current_dir().into_diagnostic()?;
 
@@ -49,6 +96,14 @@ impl Config {
                 return Ok(Some(Self::from_file(&config_path)?));
             }
 
+            let shared_path = current_dir.join("pyproject.toml");
+            if shared_path.exists() {
+                let contents = std::fs::read_to_string(&shared_path).into_diagnostic()?;
+                if let Some(config) = Self::from_tool_table(&contents)? {
+                    return Ok(Some(config));
+                }
+            }
+
             // Try parent directory
             if !current_dir.pop() {
                 break;
@@ -67,6 +122,7 @@ impl Config {
         files: &[PathBuf],
         format: &Option<crate::args::OutputFormat>,
         disable: &[String],
+        enable: &[String],
     ) -> Self {
         // CLI args override config file
         if !schema.is_empty() {
@@ -89,6 +145,10 @@ impl Config {
             self.disable = disable.to_vec();
         }
 
+        if !enable.is_empty() {
+            self.enable = enable.to_vec();
+        }
+
         self
     }
 }
@@ -5,16 +5,20 @@ mod config;
 mod output;
 
 use std::fs;
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 use clap::Parser;
 use miette::{IntoDiagnostic, Result};
+use rayon::prelude::*;
 use sqlsift_core::schema::SchemaBuilder;
-use sqlsift_core::{Analyzer, SqlDialect};
+use sqlsift_core::{
+    apply_rule_levels, Analyzer, Catalog, Diagnostic, DiagnosticKind, DiagnosticSummary, SqlDialect,
+};
 
 use crate::args::{Args, Command, OutputFormat};
 use crate::config::Config;
-use crate::output::OutputFormatter;
+use crate::output::{print_json_array, print_sarif_log, OutputFormatter};
 
 fn main() -> ExitCode {
     let args = Args::parse();
@@ -59,13 +63,12 @@ fn run(args: Args) -> Result<bool> {
             schema_dir,
             config: config_path,
             disable,
+            enable,
             dialect,
             format,
+            cache,
             max_errors,
         } => {
-            // Parse and validate dialect
-            let dialect: SqlDialect = dialect.parse().map_err(|e: String| miette::miette!(e))?;
-
             // Load configuration
             let config = if let Some(path) = config_path {
                 // Load from specified path
@@ -76,13 +79,22 @@ fn run(args: Args) -> Result<bool> {
             };
 
             // Merge CLI args with config (CLI takes precedence)
-            let config = config.merge_with_args(&schema, &schema_dir, &files, &format, &disable);
+            let config =
+                config.merge_with_args(&schema, &schema_dir, &files, &format, &disable, &enable);
             tracing::info!(
                 schema_count = config.schema.len(),
                 query_pattern_count = config.files.len(),
                 "Loaded sqlsift configuration"
             );
 
+            // Parse and validate dialect
+            let dialect: SqlDialect = dialect
+                .as_deref()
+                .or(config.dialect.as_deref())
+                .unwrap_or("postgresql")
+                .parse()
+                .map_err(|e: String| miette::miette!(e))?;
+
             // Get schema files from config or CLI
             let mut schema_files: Vec<std::path::PathBuf> =
                 config.schema.iter().map(std::path::PathBuf::from).collect();
@@ -103,31 +115,65 @@ fn run(args: Args) -> Result<bool> {
                 match fmt_str.as_str() {
                     "json" => OutputFormat::Json,
                     "sarif" => OutputFormat::Sarif,
+                    "github" => OutputFormat::Github,
                     _ => OutputFormat::Human,
                 }
             } else {
                 OutputFormat::Human
             };
 
-            // Build schema catalog
-            let mut builder = SchemaBuilder::with_dialect(dialect);
+            // Read schema files up front; their contents key the catalog cache
+            let mut schema_sources = Vec::new();
             for schema_file in &schema_files {
                 let content = fs::read_to_string(schema_file).into_diagnostic()?;
-                if let Err(diags) = builder.parse(&content) {
-                    let formatter =
-                        OutputFormatter::new(output_format, schema_file.display().to_string());
-                    formatter.print_diagnostics(&diags, &content);
-                    return Ok(true);
-                }
+                schema_sources.push((schema_file.as_path(), content));
             }
-            let (catalog, schema_diags) = builder.build();
+            let cache_path = cache.or_else(|| config.cache.as_ref().map(PathBuf::from));
+            let cache_key = Catalog::cache_key(
+                dialect,
+                &schema_sources
+                    .iter()
+                    .map(|(path, content)| (*path, content.as_str()))
+                    .collect::<Vec<_>>(),
+            );
 
+            let cached = cache_path
+                .as_deref()
+                .and_then(|path| Catalog::load_cached(path, cache_key));
+            let (mut catalog, schema_diags) = if let Some(cached) = cached {
+                tracing::debug!("Using cached schema catalog");
+                cached
+            } else {
+                // Build schema catalog
+                let mut builder = SchemaBuilder::with_dialect(dialect);
+                for (schema_file, content) in &schema_sources {
+                    let result = if schema_file.extension().is_some_and(|ext| ext == "json") {
+                        builder.load_json(content)
+                    } else {
+                        builder.parse_file(schema_file, content)
+                    };
+                    if let Err(diags) = result {
+                        let formatter =
+                            OutputFormatter::new(output_format, schema_file.display().to_string());
+                        formatter.print_diagnostics(&diags, content);
+                        return Ok(true);
+                    }
+                }
+                let (catalog, schema_diags) = builder.build();
+                if let Some(path) = &cache_path {
+                    if let Err(e) = catalog.save_cache(path, cache_key, &schema_diags) {
+                        tracing::warn!(path = %path.display(), "Failed to write catalog cache: {e}");
+                    }
+                }
+                (catalog, schema_diags)
+            };
             if !schema_diags.is_empty() {
                 eprintln!(
                     "Warning: Schema parsing produced {} warnings",
                     schema_diags.len()
                 );
             }
+            catalog.set_search_path(config.search_path.clone());
 
             // Collect query files from config or CLI
             let mut query_files = Vec::new();
@@ -151,11 +197,45 @@ fn run(args: Args) -> Result<bool> {
             if query_files.is_empty() {
                 miette::bail!("No query files specified. Use positional arguments or configure in sqlsift.toml");
             }
+            // Output order doesn't depend on glob order or thread scheduling
+            query_files.sort();
+
+            let mut enabled_rules = Vec::new();
+            for rule in &config.enable {
+                match DiagnosticKind::from_code(rule) {
+                    Some(kind) => enabled_rules.push(kind),
+                    None => miette::bail!("Unknown rule '{}' in enable list", rule),
+                }
+            }
+            let mut rule_levels = std::collections::HashMap::new();
+            for (rule, level) in &config.severity {
+                match DiagnosticKind::from_code(rule) {
+                    Some(kind) => rule_levels.insert(kind, *level),
+                    None => miette::bail!("Unknown rule '{}' in severity table", rule),
+                };
+            }
+            let new_analyzer = || {
+                let mut analyzer = Analyzer::with_dialect(&catalog, dialect);
+                analyzer.set_type_checking(config.type_checking.unwrap_or(true));
+                analyzer.set_plpgsql_analysis(config.plpgsql.unwrap_or(false));
+                for kind in &enabled_rules {
+                    analyzer.enable_rule(*kind);
+                }
+                analyzer
+            };
 
-            // Analyze each query file
-            let mut total_errors = 0;
-            let mut total_warnings = 0;
-            let mut analyzer = Analyzer::with_dialect(&catalog, dialect);
+            // Analyze the query files in parallel; the catalog is shared read-only
+            let analyzed: Vec<(String, Vec<Diagnostic>)> = query_files
+                .par_iter()
+                .map_init(new_analyzer, |analyzer, query_file| {
+                    tracing::debug!(file = %query_file.display(), "Analyzing SQL file");
+                    let content = fs::read_to_string(query_file).into_diagnostic()?;
+                    let diagnostics = apply_rule_levels(analyzer.analyze(&content), &rule_levels);
+                    Ok((content, diagnostics))
+                })
+                .collect::<Result<_>>()?;
+
+            let mut summary = DiagnosticSummary::default();
             let max_errors = if max_errors == 0 {
                 usize::MAX
             } else {
@@ -167,16 +247,16 @@ fn run(args: Args) -> Result<bool> {
             let disabled_rules: std::collections::HashSet<String> =
                 config.disable.iter().cloned().collect();
 
-            for query_file in &query_files {
-                if total_errors >= max_errors {
+            // JSON and SARIF output is a single document covering every file
+            let mut json_diagnostics = Vec::new();
+            let mut sarif_results = Vec::new();
+
+            for (query_file, (content, diagnostics)) in query_files.iter().zip(analyzed) {
+                if summary.errors >= max_errors {
                     limit_reached = true;
                     break;
                 }
 
-                tracing::debug!(file = %query_file.display(), "Analyzing SQL file");
-                let content = fs::read_to_string(query_file).into_diagnostic()?;
-                let diagnostics = analyzer.analyze(&content);
-
                 // Filter out disabled rules
                 let filtered_diagnostics: Vec<_> = diagnostics
                     .into_iter()
@@ -185,25 +265,31 @@ fn run(args: Args) -> Result<bool> {
 
                 let mut diagnostics_to_print = Vec::new();
                 for diag in filtered_diagnostics {
-                    if matches!(diag.severity, sqlsift_core::Severity::Error)
-                        && total_errors >= max_errors
-                    {
+                    if quiet && !diag.is_error() {
+                        continue;
+                    }
+                    if diag.is_error() && summary.errors >= max_errors {
                         limit_reached = true;
                         break;
                     }
 
-                    match diag.severity {
-                        sqlsift_core::Severity::Error => total_errors += 1,
-                        sqlsift_core::Severity::Warning => total_warnings += 1,
-                        _ => {}
-                    }
+                    summary.add(&diag);
                     diagnostics_to_print.push(diag);
                 }
 
-                if !diagnostics_to_print.is_empty() {
-                    let formatter =
-                        OutputFormatter::new(output_format, query_file.display().to_string());
-                    formatter.print_diagnostics(&diagnostics_to_print, &content);
+                let formatter =
+                    OutputFormatter::new(output_format, query_file.display().to_string());
+                match output_format {
+                    OutputFormat::Json => {
+                        json_diagnostics.extend(formatter.to_json(&diagnostics_to_print, &content))
+                    }
+                    OutputFormat::Sarif => sarif_results
+                        .extend(formatter.to_sarif_results(&diagnostics_to_print, &content)),
+                    OutputFormat::Human | OutputFormat::Github => {
+                        if !diagnostics_to_print.is_empty() {
+                            formatter.print_diagnostics(&diagnostics_to_print, &content);
+                        }
+                    }
                 }
 
                 if limit_reached {
@@ -211,18 +297,24 @@ fn run(args: Args) -> Result<bool> {
                 }
             }
 
+            match output_format {
+                OutputFormat::Json => print_json_array(&json_diagnostics),
+                OutputFormat::Sarif => print_sarif_log(sarif_results),
+                OutputFormat::Human | OutputFormat::Github => {}
+            }
+
             // Print summary
             if !quiet {
                 if limit_reached && max_errors != usize::MAX {
                     eprintln!("Reached maximum error limit ({max_errors}). Stopped early.");
                 }
 
-                if total_errors > 0 || total_warnings > 0 {
+                if summary.errors > 0 || summary.warnings > 0 {
                     eprintln!();
                     eprintln!(
                         "Found {} error(s), {} warning(s) in {} file(s)",
-                        total_errors,
-                        total_warnings,
+                        summary.errors,
+                        summary.warnings,
                         query_files.len()
                     );
                 } else {
@@ -230,7 +322,61 @@ fn run(args: Args) -> Result<bool> {
                 }
             }
 
-            Ok(total_errors > 0)
+            Ok(summary.has_errors())
+        }
+
+        Command::Diff {
+            from,
+            to,
+            dialect,
+            format,
+        } => {
+            let dialect: SqlDialect = dialect
+                .as_deref()
+                .unwrap_or("postgresql")
+                .parse()
+                .map_err(|e: String| miette::miette!(e))?;
+            let output_format = format.unwrap_or_default();
+
+            let Some(before) = load_schema(&from, dialect, output_format)? else {
+                return Ok(true);
+            };
+            let Some(after) = load_schema(&to, dialect, output_format)? else {
+                return Ok(true);
+            };
+
+            let mut summary = DiagnosticSummary::default();
+            let diagnostics: Vec<Diagnostic> = before
+                .destructive_changes(&after)
+                .into_iter()
+                .filter(|diag| !quiet || diag.is_error())
+                .inspect(|diag| summary.add(diag))
+                .collect();
+
+            // Changes span two schemas, so they carry no file location
+            let formatter = OutputFormatter::new(output_format, String::new());
+            match output_format {
+                OutputFormat::Json => print_json_array(&formatter.to_json(&diagnostics, "")),
+                OutputFormat::Sarif => {
+                    print_sarif_log(formatter.to_sarif_results(&diagnostics, ""))
+                }
+                OutputFormat::Human | OutputFormat::Github => {
+                    formatter.print_diagnostics(&diagnostics, "")
+                }
+            }
+
+            if !quiet {
+                if summary.errors > 0 || summary.warnings > 0 {
+                    eprintln!(
+                        "Found {} error(s), {} warning(s) in the schema change",
+                        summary.errors, summary.warnings
+                    );
+                } else {
+                    eprintln!("No destructive schema changes");
+                }
+            }
+
+            Ok(summary.has_errors())
         }
 
         Command::Schema { files } => {
@@ -288,3 +434,27 @@ fn run(args: Args) -> Result<bool> {
         }
     }
 }
+
+/// Build a catalog from schema files, printing parse errors and returning
+/// `None` if any file fails to parse
+fn load_schema(
+    files: &[PathBuf],
+    dialect: SqlDialect,
+    output_format: OutputFormat,
+) -> Result<Option<Catalog>> {
+    let mut builder = SchemaBuilder::with_dialect(dialect);
+    for file in files {
+        let content = fs::read_to_string(file).into_diagnostic()?;
+        let result = if file.extension().is_some_and(|ext| ext == "json") {
+            builder.load_json(&content)
+        } else {
+            builder.parse_file(file, &content)
+        };
+        if let Err(diags) = result {
+            OutputFormatter::new(output_format, file.display().to_string())
+                .print_diagnostics(&diags, &content);
+            return Ok(None);
+        }
+    }
+    Ok(Some(builder.build().0))
+}
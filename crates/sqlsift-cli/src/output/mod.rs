@@ -1,9 +1,25 @@
 //! Output formatting
 
-use sqlsift_core::{Diagnostic, Severity};
+use serde::Serialize;
+use sqlsift_core::{Diagnostic, DiagnosticKind, Severity};
 
 use crate::args::OutputFormat;
 
+/// A diagnostic as emitted by `--format json`
+#[derive(Debug, Serialize)]
+pub struct DiagnosticJson {
+    pub file: String,
+    /// 1-indexed line, if the diagnostic has a location
+    pub line: Option<usize>,
+    /// 1-indexed column, if the diagnostic has a location
+    pub column: Option<usize>,
+    pub length: Option<usize>,
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub help: Option<String>,
+}
+
 /// Output formatter for diagnostics
 pub struct OutputFormatter {
     format: OutputFormat,
@@ -19,8 +35,9 @@ impl OutputFormatter {
     pub fn print_diagnostics(&self, diagnostics: &[Diagnostic], source: &str) {
         match self.format {
             OutputFormat::Human => self.print_human(diagnostics, source),
-            OutputFormat::Json => self.print_json(diagnostics),
-            OutputFormat::Sarif => self.print_sarif(diagnostics),
+            OutputFormat::Json => self.print_json(diagnostics, source),
+            OutputFormat::Sarif => self.print_sarif(diagnostics, source),
+            OutputFormat::Github => self.print_github(diagnostics, source),
         }
     }
 
@@ -70,16 +87,48 @@ impl OutputFormatter {
         }
     }
 
-    fn print_json(&self, diagnostics: &[Diagnostic]) {
-        let output = serde_json::json!({
-            "file": self.file_name,
-            "diagnostics": diagnostics
-        });
-        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    fn print_json(&self, diagnostics: &[Diagnostic], source: &str) {
+        print_json_array(&self.to_json(diagnostics, source));
+    }
+
+    /// Convert diagnostics to their JSON form, resolving line and column
+    pub fn to_json(&self, diagnostics: &[Diagnostic], source: &str) -> Vec<DiagnosticJson> {
+        diagnostics
+            .iter()
+            .map(|d| {
+                let position = d.span.map(|span| {
+                    let (line, col) = if span.line > 0 {
+                        (span.line, span.column)
+                    } else {
+                        offset_to_line_col(source, span.offset)
+                    };
+                    (line, col, span.length)
+                });
+                DiagnosticJson {
+                    file: self.file_name.clone(),
+                    line: position.map(|p| p.0),
+                    column: position.map(|p| p.1),
+                    length: position.map(|p| p.2),
+                    code: d.code(),
+                    severity: d.severity,
+                    message: d.message.clone(),
+                    help: d.help.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn print_sarif(&self, diagnostics: &[Diagnostic], source: &str) {
+        print_sarif_log(self.to_sarif_results(diagnostics, source));
     }
 
-    fn print_sarif(&self, diagnostics: &[Diagnostic]) {
-        let results: Vec<serde_json::Value> = diagnostics
+    /// Convert diagnostics to SARIF `result` objects
+    pub fn to_sarif_results(
+        &self,
+        diagnostics: &[Diagnostic],
+        source: &str,
+    ) -> Vec<serde_json::Value> {
+        diagnostics
             .iter()
             .map(|d| {
                 let mut location = serde_json::json!({
@@ -90,16 +139,19 @@ impl OutputFormatter {
 
                 // Add region if we have span information
                 if let Some(span) = &d.span {
-                    if span.line > 0 {
-                        location["region"] = serde_json::json!({
-                            "startLine": span.line,
-                            "startColumn": span.column,
-                            "endColumn": span.column + span.length
-                        });
-                    }
+                    let (line, col) = if span.line > 0 {
+                        (span.line, span.column)
+                    } else {
+                        offset_to_line_col(source, span.offset)
+                    };
+                    location["region"] = serde_json::json!({
+                        "startLine": line,
+                        "startColumn": col,
+                        "endColumn": col + span.length
+                    });
                 }
 
-                serde_json::json!({
+                let mut result = serde_json::json!({
                     "ruleId": d.code(),
                     "level": match d.severity {
                         Severity::Error => "error",
@@ -112,28 +164,120 @@ impl OutputFormatter {
                     "locations": [{
                         "physicalLocation": location
                     }]
-                })
+                });
+                if let Some(index) = DiagnosticKind::ALL.iter().position(|k| *k == d.kind) {
+                    result["ruleIndex"] = index.into();
+                }
+                result
             })
-            .collect();
-
-        let sarif = serde_json::json!({
-            "$schema": "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
-            "version": "2.1.0",
-            "runs": [{
-                "tool": {
-                    "driver": {
-                        "name": "sqlsift",
-                        "version": env!("CARGO_PKG_VERSION")
+            .collect()
+    }
+
+    fn print_github(&self, diagnostics: &[Diagnostic], source: &str) {
+        for command in self.to_github_commands(diagnostics, source) {
+            println!("{}", command);
+        }
+    }
+
+    /// Convert diagnostics to GitHub Actions workflow commands
+    ///
+    /// Each command is a `::error`, `::warning` or `::notice` line that GitHub
+    /// shows as an annotation on the matching line of the pull request.
+    pub fn to_github_commands(&self, diagnostics: &[Diagnostic], source: &str) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|d| {
+                let command = match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "notice",
+                };
+                let mut properties = vec![format!("file={}", escape_property(&self.file_name))];
+                if let Some(span) = &d.span {
+                    let (line, col) = if span.line > 0 {
+                        (span.line, span.column)
+                    } else {
+                        offset_to_line_col(source, span.offset)
+                    };
+                    properties.push(format!("line={}", line));
+                    properties.push(format!("col={}", col));
+                    if span.length > 0 {
+                        properties.push(format!("endColumn={}", col + span.length));
                     }
-                },
-                "results": results
-            }]
-        });
+                }
+                properties.push(format!("title={}", escape_property(d.code())));
 
-        println!("{}", serde_json::to_string_pretty(&sarif).unwrap());
+                let mut message = d.message.clone();
+                if let Some(help) = &d.help {
+                    message.push_str("\nhelp: ");
+                    message.push_str(help);
+                }
+                format!(
+                    "::{} {}::{}",
+                    command,
+                    properties.join(","),
+                    escape_data(&message)
+                )
+            })
+            .collect()
     }
 }
 
+/// Escape a workflow command message
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value, which also can't contain `:` or `,`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Print diagnostics from all files as a single JSON array
+pub fn print_json_array(diagnostics: &[DiagnosticJson]) {
+    println!("{}", serde_json::to_string_pretty(diagnostics).unwrap());
+}
+
+/// Print a SARIF 2.1.0 log with a single run holding `results` from all files
+pub fn print_sarif_log(results: Vec<serde_json::Value>) {
+    let rules: Vec<serde_json::Value> = DiagnosticKind::ALL
+        .iter()
+        .map(|kind| {
+            serde_json::json!({
+                "id": kind.code(),
+                "name": kind.name(),
+                "shortDescription": {
+                    "text": kind.description()
+                },
+                "defaultConfiguration": {
+                    "enabled": kind.is_enabled_by_default()
+                }
+            })
+        })
+        .collect();
+
+    let sarif = serde_json::json!({
+        "$schema": "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "sqlsift",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules
+                }
+            },
+            "results": results
+        }]
+    });
+
+    println!("{}", serde_json::to_string_pretty(&sarif).unwrap());
+}
+
 /// Convert byte offset to line and column (1-indexed)
 fn offset_to_line_col(source: &str, offset: usize) -> (usize, usize) {
     let mut line = 1;
@@ -25,10 +25,15 @@ fn workspace_root() -> PathBuf {
 }
 
 fn run_sqlsift(args: &[&str]) -> std::process::Output {
+    run_sqlsift_with_env(args, &[])
+}
+
+fn run_sqlsift_with_env(args: &[&str], envs: &[(&str, &str)]) -> std::process::Output {
     Command::new("cargo")
         .current_dir(workspace_root())
         .args(["run", "-q", "-p", "sqlsift-cli", "--"])
         .args(args)
+        .envs(envs.iter().copied())
         .output()
         .expect("failed to execute sqlsift via cargo run")
 }
@@ -145,3 +150,508 @@ fn test_verbose_emits_info_log() {
 
     let _ = fs::remove_dir_all(&dir);
 }
+
+#[test]
+fn test_enable_opt_in_rule() {
+    let dir = make_temp_dir("enable");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(&schema, "CREATE TABLE users (id INTEGER PRIMARY KEY);");
+    write_file(&query, "SELECT * FROM users;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+
+    let output = run_sqlsift(&["check", "--schema", &schema_s, &query_s]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains("W1002"),
+        "rule should be off by default:\n{stderr}"
+    );
+
+    let output = run_sqlsift(&[
+        "check", "--enable", "W1002", "--schema", &schema_s, &query_s,
+    ]);
+    assert!(
+        output.status.success(),
+        "warnings alone should not fail the check"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("W1002"),
+        "expected W1002 warning, stderr:\n{stderr}"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_config_from_tool_sqlsift_table() {
+    let dir = make_temp_dir("tool-table");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+    let pyproject = dir.join("pyproject.toml");
+
+    write_file(&schema, "CREATE TABLE users (id INTEGER PRIMARY KEY);");
+    write_file(&query, "SELECT * FROM users;\n");
+    write_file(
+        &pyproject,
+        &format!(
+            "[project]\nname = \"app\"\n\n[tool.sqlsift]\nschema = [{:?}]\nenable = [\"W1002\"]\n",
+            schema.to_string_lossy()
+        ),
+    );
+
+    let pyproject_s = pyproject.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--config", &pyproject_s, &query_s]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr:\n{stderr}");
+    assert!(
+        stderr.contains("W1002"),
+        "expected [tool.sqlsift] enable list to apply, stderr:\n{stderr}"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_bad_column_exit_code() {
+    let dir = make_temp_dir("exit-code");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+    );
+    write_file(&query, "SELECT id,\n       nmae\nFROM users;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--schema", &schema_s, &query_s]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("E0002"),
+        "expected E0002 diagnostic, stderr:\n{stderr}"
+    );
+    assert!(
+        stderr.contains("query.sql:2:8"),
+        "expected the diagnostic location, stderr:\n{stderr}"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_quiet_suppresses_warnings() {
+    let dir = make_temp_dir("quiet-warnings");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(&schema, "CREATE TABLE users (id INTEGER PRIMARY KEY);");
+    write_file(&query, "SELECT * FROM users;\nSELECT missing FROM users;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&[
+        "-q", "check", "--enable", "W1002", "--schema", &schema_s, &query_s,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("E0002"),
+        "errors are still reported, stderr:\n{stderr}"
+    );
+    assert!(
+        !stderr.contains("W1002"),
+        "warnings should be suppressed in quiet mode, stderr:\n{stderr}"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_dialect_from_config() {
+    let dir = make_temp_dir("config-dialect");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+    let config = dir.join("sqlsift.toml");
+
+    write_file(&schema, "CREATE TABLE `users` (`id` INT PRIMARY KEY);");
+    write_file(&query, "SELECT `id` FROM `users`;\n");
+    write_file(
+        &config,
+        &format!(
+            "schema = [{:?}]\ndialect = \"mysql\"\n",
+            schema.to_string_lossy()
+        ),
+    );
+
+    let config_s = config.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--config", &config_s, &query_s]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr:\n{stderr}");
+
+    // The command line still wins over the config file
+    let output = run_sqlsift(&[
+        "check",
+        "--config",
+        &config_s,
+        "--dialect",
+        "postgresql",
+        &query_s,
+    ]);
+    assert!(!output.status.success());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_severity_overrides_from_config() {
+    let dir = make_temp_dir("config-severity");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+    let config = dir.join("sqlsift.toml");
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER, name TEXT);\nCREATE TABLE orders (id INTEGER);",
+    );
+    write_file(
+        &query,
+        "SELECT name FROM users WHERE id = 'one';\nSELECT id FROM users, orders WHERE users.id = orders.id;\n",
+    );
+    write_file(
+        &config,
+        &format!(
+            "schema = [{:?}]\n\n[severity]\nE0003 = \"warning\"\nE0006 = \"off\"\n",
+            schema.to_string_lossy()
+        ),
+    );
+
+    let config_s = config.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--config", &config_s, "--format", "json", &query_s]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "stderr:\n{stderr}");
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let diagnostics = json.as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1, "{json}");
+    assert_eq!(diagnostics[0]["code"], "E0003");
+    assert_eq!(diagnostics[0]["severity"], "warning");
+
+    // Unknown rules are reported rather than ignored
+    write_file(
+        &config,
+        &format!(
+            "schema = [{:?}]\n\n[severity]\nE9999 = \"off\"\n",
+            schema.to_string_lossy()
+        ),
+    );
+    let output = run_sqlsift(&["check", "--config", &config_s, &query_s]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_config_from_explicit_path() {
+    let dir = make_temp_dir("config-explicit");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+    let config = dir.join("ci").join("lint-rules.toml");
+
+    write_file(&schema, "CREATE TABLE users (id INTEGER, name TEXT);");
+    write_file(&query, "SELECT nmae FROM users;\n");
+    fs::create_dir_all(config.parent().unwrap()).unwrap();
+    write_file(
+        &config,
+        &format!(
+            "schema = [{:?}]\ndisable = [\"E0002\"]\n",
+            schema.to_string_lossy()
+        ),
+    );
+
+    // The explicit file is used instead of the sqlsift.toml found from the cwd
+    let config_s = config.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--config", &config_s, &query_s]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "stderr:\n{stderr}");
+
+    let missing = dir.join("missing.toml").to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--config", &missing, &query_s]);
+    assert_ne!(output.status.code(), Some(0));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_json_output_format() {
+    let dir = make_temp_dir("json-output");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+    );
+    write_file(&query, "SELECT id,\n       nmae\nFROM users;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&["check", "--format", "json", "--schema", &schema_s, &query_s]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout).expect("stdout should be a JSON document");
+    let diagnostics = json.as_array().expect("expected a JSON array");
+    assert_eq!(diagnostics.len(), 1);
+    let diag = &diagnostics[0];
+    assert_eq!(diag["code"], "E0002");
+    assert_eq!(diag["severity"], "error");
+    assert_eq!(diag["line"], 2);
+    assert_eq!(diag["column"], 8);
+    assert_eq!(diag["length"], 4);
+    assert!(diag["file"].as_str().unwrap().ends_with("query.sql"));
+    assert!(diag["help"].is_string());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_sarif_output_format() {
+    let dir = make_temp_dir("sarif-output");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+    );
+    write_file(&query, "SELECT 1 FROM user_list;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&[
+        "check", "--format", "sarif", "--schema", &schema_s, &query_s,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let sarif: serde_json::Value =
+        serde_json::from_str(&stdout).expect("stdout should be a JSON document");
+    assert!(sarif["$schema"]
+        .as_str()
+        .unwrap()
+        .contains("sarif-schema-2.1.0"));
+    assert_eq!(sarif["version"], "2.1.0");
+
+    let run = &sarif["runs"][0];
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert!(rules
+        .iter()
+        .any(|r| r["id"] == "E0001" && r["name"] == "table-not-found"));
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "E0001");
+    assert_eq!(results[0]["level"], "error");
+    let rule_index = results[0]["ruleIndex"].as_u64().unwrap() as usize;
+    assert_eq!(rules[rule_index]["id"], "E0001");
+    let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 1);
+    assert_eq!(region["startColumn"], 15);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_github_output_format() {
+    let dir = make_temp_dir("github-output");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+    );
+    write_file(&query, "SELECT 1 FROM user_list;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let output = run_sqlsift(&[
+        "check", "--format", "github", "--schema", &schema_s, &query_s,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [format!(
+            "::error file={},line=1,col=15,endColumn=24,title=E0001::Table 'user_list' not found\
+             %0Ahelp: Check that the table exists in your schema definition",
+            query_s.replace(':', "%3A").replace(',', "%2C")
+        )]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_catalog_cache_rebuilt_when_schema_changes() {
+    let dir = make_temp_dir("catalog-cache");
+    let schema = dir.join("schema.sql");
+    let query = dir.join("query.sql");
+    let cache = dir.join("catalog.json");
+
+    write_file(&schema, "CREATE TABLE users (id INTEGER PRIMARY KEY);");
+    write_file(&query, "SELECT id, email FROM users;\n");
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let query_s = query.to_string_lossy().to_string();
+    let cache_s = cache.to_string_lossy().to_string();
+    let args = [
+        "check", "--cache", &cache_s, "--schema", &schema_s, &query_s,
+    ];
+
+    let output = run_sqlsift(&args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(cache.exists(), "expected the catalog cache to be written");
+
+    // A cache hit gives the same result
+    let output = run_sqlsift(&args);
+    assert_eq!(output.status.code(), Some(1));
+
+    // Schema warnings are kept with the cached catalog
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY);\nCREATE TABLE users (id INTEGER PRIMARY KEY);",
+    );
+    for _ in 0..2 {
+        let output = run_sqlsift(&args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Schema parsing produced 1 warnings"),
+            "stderr:\n{stderr}"
+        );
+    }
+
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT);",
+    );
+    let output = run_sqlsift(&args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stale cache was used, stderr:\n{stderr}"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_parallel_analysis_matches_serial() {
+    let dir = make_temp_dir("parallel");
+    let schema = dir.join("schema.sql");
+    write_file(
+        &schema,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+    );
+
+    // Written in reverse so that creation order differs from path order
+    let mut query_files = Vec::new();
+    for i in (0..40).rev() {
+        let query = dir.join(format!("query{i:02}.sql"));
+        let sql = match i % 4 {
+            0 => "SELECT id, name FROM users;\n".to_string(),
+            1 => format!("SELECT missing_{i} FROM users;\n"),
+            2 => format!("SELECT id FROM table_{i};\n"),
+            _ => "SELECT id FROM users WHERE name = 1;\nSELECT nope FROM users;\n".to_string(),
+        };
+        write_file(&query, &sql);
+        query_files.push(query.to_string_lossy().to_string());
+    }
+
+    let schema_s = schema.to_string_lossy().to_string();
+    let mut args = vec!["check", "--format", "json", "--schema", &schema_s];
+    args.extend(query_files.iter().map(String::as_str));
+
+    let serial = run_sqlsift_with_env(&args, &[("RAYON_NUM_THREADS", "1")]);
+    let parallel = run_sqlsift_with_env(&args, &[("RAYON_NUM_THREADS", "8")]);
+    assert_eq!(serial.status.code(), Some(1));
+    assert_eq!(parallel.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&serial.stdout),
+        String::from_utf8_lossy(&parallel.stdout)
+    );
+
+    // Diagnostics come out sorted by path
+    let json: serde_json::Value = serde_json::from_slice(&parallel.stdout).unwrap();
+    let files: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["file"].as_str().unwrap())
+        .collect();
+    assert!(files.len() >= 30, "{files:?}");
+    assert!(files.windows(2).all(|w| w[0] <= w[1]), "{files:?}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_diff_reports_destructive_changes() {
+    let dir = make_temp_dir("diff");
+    let before = dir.join("before.sql");
+    let after = dir.join("after.sql");
+    let additive = dir.join("additive.sql");
+
+    write_file(
+        &before,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR(100), email TEXT);",
+    );
+    write_file(
+        &after,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR(10));",
+    );
+    write_file(
+        &additive,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR(100), email TEXT, bio TEXT);",
+    );
+
+    let before_s = before.to_string_lossy().to_string();
+    let after_s = after.to_string_lossy().to_string();
+    let output = run_sqlsift(&["diff", "--from", &before_s, "--to", &after_s]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("W1004") && stderr.contains("'email'") && stderr.contains("'name'"),
+        "expected the dropped and narrowed columns, stderr:\n{stderr}"
+    );
+    assert!(
+        stderr.contains("0 error(s), 2 warning(s)"),
+        "stderr:\n{stderr}"
+    );
+
+    let additive_s = additive.to_string_lossy().to_string();
+    let output = run_sqlsift(&["diff", "--from", &before_s, "--to", &additive_s]);
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("W1004"), "stderr:\n{stderr}");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -4,6 +4,8 @@
 //! - `-- sqlsift:disable E0002` (same line: suppress on this line; standalone: suppress on next line)
 //! - `-- sqlsift:disable E0002, E0003` (multiple rules)
 //! - `-- sqlsift:disable` (suppress all rules)
+//! - `-- sqlsift:disable-next-line E0002` (suppress on the next SQL line only,
+//!   whether the comment stands alone or trails a statement)
 
 use std::collections::{HashMap, HashSet};
 
@@ -23,26 +25,31 @@ impl InlineDirectives {
         for (idx, line) in sql.lines().enumerate() {
             let line_num = idx + 1; // 1-indexed to match sqlparser Span
             let trimmed = line.trim();
+            let is_sql = !trimmed.is_empty() && !trimmed.starts_with("--");
 
-            if let Some(codes) = parse_directive_from_line(line) {
-                if trimmed.starts_with("--") {
-                    // Standalone comment line: accumulate and apply to next SQL line
-                    match &mut pending_codes {
-                        Some(existing) => {
-                            merge_codes(existing, codes);
-                        }
-                        None => {
-                            pending_codes = Some(codes);
-                        }
-                    }
-                } else {
-                    // Inline comment (SQL + -- sqlsift:disable): applies to this line
+            if is_sql {
+                // Non-comment, non-empty line: apply pending disables
+                if let Some(codes) = pending_codes.take() {
                     merge_into_map(&mut disabled_lines, line_num, codes);
                 }
-            } else if pending_codes.is_some() && !trimmed.is_empty() && !trimmed.starts_with("--") {
-                // Non-comment, non-empty line: apply pending disables
-                let codes = pending_codes.take().unwrap();
-                merge_into_map(&mut disabled_lines, line_num, codes);
+            }
+
+            match parse_directive_from_line(line) {
+                // Inline comment (SQL + -- sqlsift:disable): applies to this line
+                Some((Scope::Line, codes)) if is_sql => {
+                    merge_into_map(&mut disabled_lines, line_num, codes);
+                }
+                // Standalone comment line or disable-next-line: accumulate and
+                // apply to the next SQL line
+                Some((_, codes)) => match &mut pending_codes {
+                    Some(existing) => {
+                        merge_codes(existing, codes);
+                    }
+                    None => {
+                        pending_codes = Some(codes);
+                    }
+                },
+                None => {}
             }
         }
 
@@ -59,21 +66,33 @@ impl InlineDirectives {
     }
 }
 
-/// Parse a `-- sqlsift:disable ...` directive from a line.
-/// Returns `Some(None)` for "disable all", `Some(Some(set))` for specific codes.
+/// Which line a directive applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// `sqlsift:disable`: its own line, or the next one when standalone
+    Line,
+    /// `sqlsift:disable-next-line`: always the next SQL line
+    NextLine,
+}
+
+/// Parse a `-- sqlsift:disable[-next-line] ...` directive from a line.
+/// The codes are `None` for "disable all", `Some(set)` for specific codes.
 /// Returns `None` if no directive is found.
-fn parse_directive_from_line(line: &str) -> Option<Option<HashSet<String>>> {
+fn parse_directive_from_line(line: &str) -> Option<(Scope, Option<HashSet<String>>)> {
     // Find `--` that's not inside a string literal
     let comment_start = find_line_comment(line)?;
     let comment = &line[comment_start + 2..]; // skip "--"
 
-    // Look for "sqlsift:disable"
+    // Look for "sqlsift:disable" or "sqlsift:disable-next-line"
     let trimmed = comment.trim();
-    let rest = trimmed.strip_prefix("sqlsift:disable")?;
+    let (scope, rest) = match trimmed.strip_prefix("sqlsift:disable-next-line") {
+        Some(rest) => (Scope::NextLine, rest),
+        None => (Scope::Line, trimmed.strip_prefix("sqlsift:disable")?),
+    };
 
     if rest.is_empty() {
         // `-- sqlsift:disable` (no codes = disable all)
-        return Some(None);
+        return Some((scope, None));
     }
 
     // Must be followed by whitespace or comma
@@ -89,9 +108,9 @@ fn parse_directive_from_line(line: &str) -> Option<Option<HashSet<String>>> {
         .collect();
 
     if codes.is_empty() {
-        Some(None)
+        Some((scope, None))
     } else {
-        Some(Some(codes))
+        Some((scope, Some(codes)))
     }
 }
 
@@ -278,4 +297,38 @@ mod tests {
         let directives = InlineDirectives::parse(sql);
         assert!(directives.is_suppressed("E0002", 1));
     }
+
+    #[test]
+    fn test_disable_next_line_only_next_line() {
+        let sql = "-- sqlsift:disable-next-line E0002\nSELECT bad_col FROM users;\nSELECT bad_col FROM users;";
+        let directives = InlineDirectives::parse(sql);
+        assert!(directives.is_suppressed("E0002", 2));
+        assert!(!directives.is_suppressed("E0001", 2));
+        assert!(!directives.is_suppressed("E0002", 3));
+    }
+
+    #[test]
+    fn test_disable_next_line_skips_blank_and_comment_lines() {
+        let sql = "-- sqlsift:disable-next-line E0002\n\n-- fetch users\nSELECT bad_col FROM users";
+        let directives = InlineDirectives::parse(sql);
+        assert!(directives.is_suppressed("E0002", 4));
+    }
+
+    #[test]
+    fn test_trailing_disable_next_line() {
+        let sql = "SELECT a, -- sqlsift:disable-next-line\n  bad_col\nFROM users";
+        let directives = InlineDirectives::parse(sql);
+        assert!(!directives.is_suppressed("E0002", 1));
+        assert!(directives.is_suppressed("E0002", 2));
+        assert!(!directives.is_suppressed("E0002", 3));
+    }
+
+    #[test]
+    fn test_disable_next_line_combines_with_inline() {
+        let sql =
+            "-- sqlsift:disable-next-line E0001\nSELECT bad_col FROM t -- sqlsift:disable E0002";
+        let directives = InlineDirectives::parse(sql);
+        assert!(directives.is_suppressed("E0001", 2));
+        assert!(directives.is_suppressed("E0002", 2));
+    }
 }
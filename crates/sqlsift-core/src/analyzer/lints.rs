@@ -0,0 +1,77 @@
+//! Opt-in lints - style and best-practice checks
+//!
+//! Lints report regardless of configuration; `Analyzer` drops those whose
+//! rule is not enabled (see `DiagnosticKind::is_enabled_by_default`).
+
+use sqlparser::ast::{Insert, Query, SelectItem, SetExpr, Statement};
+
+use crate::error::{Diagnostic, DiagnosticKind, Span};
+
+/// Runs opt-in lint rules over a statement
+pub(super) struct Linter {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter {
+    pub(super) fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Run all lints on a statement
+    pub(super) fn check_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Query(query) => self.check_select_star(query),
+            Statement::Insert(Insert {
+                source: Some(source),
+                ..
+            }) => self.check_select_star(source),
+            _ => {}
+        }
+    }
+
+    /// W1002: `*` / `t.*` in the statement's own projection
+    ///
+    /// Subqueries (`EXISTS (SELECT * ...)`) and `COUNT(*)` are not reported.
+    fn check_select_star(&mut self, query: &Query) {
+        self.check_select_star_in_set_expr(&query.body);
+    }
+
+    fn check_select_star_in_set_expr(&mut self, set_expr: &SetExpr) {
+        match set_expr {
+            SetExpr::Select(select) => {
+                for item in &select.projection {
+                    let (options, target) = match item {
+                        SelectItem::Wildcard(options) => (options, "*".to_string()),
+                        SelectItem::QualifiedWildcard(name, options) => {
+                            (options, format!("{}.*", name))
+                        }
+                        _ => continue,
+                    };
+                    self.diagnostics.push(
+                        Diagnostic::warning(
+                            DiagnosticKind::SelectStar,
+                            format!("Avoid '{}' in SELECT; list the columns explicitly", target),
+                        )
+                        .with_span(Span::from_sqlparser(&options.wildcard_token.0.span))
+                        .with_help(
+                            "Explicit column lists keep results stable when the table changes",
+                        ),
+                    );
+                }
+            }
+            SetExpr::Query(query) => self.check_select_star(query),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.check_select_star_in_set_expr(left);
+                self.check_select_star_in_set_expr(right);
+            }
+            _ => {}
+        }
+    }
+
+    /// Consume the linter and return collected diagnostics
+    pub(super) fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
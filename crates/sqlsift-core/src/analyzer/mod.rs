@@ -1,24 +1,93 @@
 //! SQL analyzer module
 
 mod comment_directives;
+mod lints;
+mod parse;
+mod plpgsql;
 mod resolver;
 mod type_resolver;
 
-use sqlparser::parser::Parser;
+use sqlparser::ast::{SetExpr, Spanned, Statement};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use crate::dialect::SqlDialect;
-use crate::error::{Diagnostic, DiagnosticKind, Span};
-use crate::schema::Catalog;
+use crate::error::{dedup_diagnostics, Diagnostic, DiagnosticKind, DiagnosticSummary, Span};
+use crate::schema::{Catalog, ColumnRef, SchemaBuilder};
 
 use comment_directives::InlineDirectives;
+use lints::Linter;
+use parse::{parse_script, split_statements, Script};
 pub use resolver::NameResolver;
 use type_resolver::TypeResolver;
 
+/// A statement of a document, as reported by [`Analyzer::analyze_statements`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementInfo {
+    /// What kind of statement this is
+    pub kind: StatementKind,
+    /// Where the statement is in the document, excluding its `;`
+    pub span: Span,
+}
+
+/// The kind of a statement
+///
+/// `WITH ... INSERT` and `WITH ... UPDATE` are classified by their main statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Merge,
+    Create,
+    Alter,
+    Drop,
+    /// A PostgreSQL `DO` block
+    Do,
+    /// Any other statement, or one that doesn't parse
+    Other,
+}
+
+impl StatementKind {
+    fn of(stmt: &Statement) -> Self {
+        match stmt {
+            Statement::Query(query) => match query.body.as_ref() {
+                SetExpr::Insert(stmt) | SetExpr::Update(stmt) => Self::of(stmt),
+                _ => Self::Select,
+            },
+            Statement::Insert(_) => Self::Insert,
+            Statement::Update { .. } => Self::Update,
+            Statement::Delete(_) => Self::Delete,
+            Statement::Merge { .. } => Self::Merge,
+            Statement::CreateTable(_)
+            | Statement::CreateView { .. }
+            | Statement::CreateIndex(_)
+            | Statement::CreateSchema { .. }
+            | Statement::CreateType { .. }
+            | Statement::CreateFunction(_)
+            | Statement::CreateSequence { .. }
+            | Statement::CreateExtension { .. } => Self::Create,
+            Statement::AlterTable { .. }
+            | Statement::AlterView { .. }
+            | Statement::AlterIndex { .. } => Self::Alter,
+            Statement::Drop { .. } | Statement::DropFunction { .. } => Self::Drop,
+            _ => Self::Other,
+        }
+    }
+}
+
 /// SQL Analyzer - validates SQL against a schema catalog
 pub struct Analyzer<'a> {
     catalog: &'a Catalog,
     diagnostics: Vec<Diagnostic>,
     dialect: SqlDialect,
+    /// Opt-in rules that have been enabled
+    enabled_rules: HashSet<DiagnosticKind>,
+    /// Whether type-derived diagnostics are reported
+    type_checking: bool,
+    /// Whether SQL inside PL/pgSQL `DO` blocks is analyzed
+    plpgsql: bool,
 }
 
 impl<'a> Analyzer<'a> {
@@ -38,6 +107,9 @@ impl<'a> Analyzer<'a> {
             catalog,
             diagnostics: Vec::new(),
             dialect: SqlDialect::default(),
+            enabled_rules: HashSet::new(),
+            type_checking: true,
+            plpgsql: false,
         }
     }
 
@@ -58,9 +130,57 @@ impl<'a> Analyzer<'a> {
             catalog,
             diagnostics: Vec::new(),
             dialect,
+            enabled_rules: HashSet::new(),
+            type_checking: true,
+            plpgsql: false,
         }
     }
 
+    /// Enable an opt-in rule (e.g. `DiagnosticKind::SelectStar`)
+    ///
+    /// Rules that are on by default are always reported; use inline
+    /// directives or the caller's disable list to silence them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::Analyzer;
+    /// use sqlsift_core::error::DiagnosticKind;
+    /// use sqlsift_core::schema::Catalog;
+    ///
+    /// let catalog = Catalog::default();
+    /// let mut analyzer = Analyzer::new(&catalog);
+    /// analyzer.enable_rule(DiagnosticKind::SelectStar);
+    /// ```
+    pub fn enable_rule(&mut self, kind: DiagnosticKind) {
+        self.enabled_rules.insert(kind);
+    }
+
+    /// Turn type checking on or off (on by default)
+    ///
+    /// With type checking off, only name-resolution and other non-type rules
+    /// are reported; type mismatches and NOT NULL violations are skipped.
+    pub fn set_type_checking(&mut self, enabled: bool) {
+        self.type_checking = enabled;
+    }
+
+    /// Turn analysis of PL/pgSQL code on or off (off by default)
+    ///
+    /// PostgreSQL `DO $$ ... $$` blocks are always skipped so that the rest of
+    /// the script still parses; with this on, the SQL statements inside them
+    /// are analyzed too.
+    pub fn set_plpgsql_analysis(&mut self, enabled: bool) {
+        self.plpgsql = enabled;
+    }
+
+    /// Check whether a rule's diagnostics are reported by this analyzer
+    pub fn is_rule_enabled(&self, kind: DiagnosticKind) -> bool {
+        if !self.type_checking && kind.is_type_check() {
+            return false;
+        }
+        kind.is_enabled_by_default() || self.enabled_rules.contains(&kind)
+    }
+
     /// Analyze a SQL query and return diagnostics
     ///
     /// Validates SQL against the schema catalog and returns a list of diagnostics.
@@ -81,43 +201,166 @@ impl<'a> Analyzer<'a> {
     /// assert!(diagnostics.is_empty());
     /// ```
     pub fn analyze(&mut self, sql: &str) -> Vec<Diagnostic> {
-        self.diagnostics.clear();
-
         // Parse inline disable directives from comments
         let directives = InlineDirectives::parse(sql);
+        let diagnostics = self.analyze_script(sql, false);
+        self.filter_diagnostics(diagnostics, &directives)
+    }
+
+    /// Analyze a SQL document whose DDL changes the schema as it goes
+    ///
+    /// Unlike [`analyze`](Self::analyze), which checks every statement against
+    /// the catalog as given, CREATE TABLE / VIEW / TYPE / INDEX, ALTER TABLE
+    /// and DROP TABLE statements are applied to a copy of the catalog, and
+    /// each later statement is checked against the schema as it stands at
+    /// that point. The analyzer's own catalog is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::Analyzer;
+    /// use sqlsift_core::schema::Catalog;
+    ///
+    /// let catalog = Catalog::new();
+    /// let mut analyzer = Analyzer::new(&catalog);
+    /// let sql = "CREATE TABLE tmp_ids (id INTEGER);\nSELECT id FROM tmp_ids;";
+    /// assert!(analyzer.analyze_document_sequential(sql).is_empty());
+    /// assert!(!analyzer.analyze(sql).is_empty());
+    /// ```
+    pub fn analyze_document_sequential(&mut self, sql: &str) -> Vec<Diagnostic> {
+        let directives = InlineDirectives::parse(sql);
+        let diagnostics = self.analyze_script(sql, true);
+        self.filter_diagnostics(diagnostics, &directives)
+    }
+
+    /// Analyze a SQL document statement by statement
+    ///
+    /// Each statement is analyzed on its own, so a statement that doesn't
+    /// parse only affects its own entry. Spans, including the statement's own,
+    /// refer to positions in the whole document.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::{Analyzer, StatementKind};
+    /// use sqlsift_core::schema::SchemaBuilder;
+    ///
+    /// let mut builder = SchemaBuilder::new();
+    /// builder.parse("CREATE TABLE users (id INTEGER, name TEXT);").unwrap();
+    /// let (catalog, _) = builder.build();
+    ///
+    /// let mut analyzer = Analyzer::new(&catalog);
+    /// let statements = analyzer.analyze_statements("SELECT id FROM users;\nDELETE FROM users WHERE nme = 'x';");
+    /// assert_eq!(statements.len(), 2);
+    /// assert!(statements[0].1.is_empty());
+    /// assert_eq!(statements[1].0.kind, StatementKind::Delete);
+    /// assert_eq!(statements[1].1.len(), 1);
+    /// ```
+    pub fn analyze_statements(&mut self, sql: &str) -> Vec<(StatementInfo, Vec<Diagnostic>)> {
+        let directives = InlineDirectives::parse(sql);
+        split_statements(self.dialect, sql)
+            .into_iter()
+            .map(|range| {
+                let text = plpgsql::isolate(sql, &range, &[]);
+                let start = statement_location(sql, range.start);
+                let info = StatementInfo {
+                    kind: self.statement_kind(&text),
+                    span: Span {
+                        offset: range.start,
+                        length: range.len(),
+                        ..start
+                    },
+                };
+                let diagnostics = self.analyze_script(&text, false);
+                (info, self.filter_diagnostics(diagnostics, &directives))
+            })
+            .collect()
+    }
+
+    /// Run every analysis over a script, before rule and directive filtering
+    ///
+    /// With `sequential`, DDL statements update a copy of the catalog that
+    /// the statements after them are checked against.
+    fn analyze_script(&mut self, sql: &str, sequential: bool) -> Vec<Diagnostic> {
+        self.diagnostics.clear();
+
+        // sqlparser can't parse DO blocks; blank them out of the script
+        let do_blocks = if self.dialect == SqlDialect::PostgreSQL {
+            plpgsql::find_do_blocks(sql)
+        } else {
+            Vec::new()
+        };
+        let script = if do_blocks.is_empty() {
+            Cow::Borrowed(sql)
+        } else {
+            let ranges: Vec<_> = do_blocks.iter().map(|b| b.statement.clone()).collect();
+            Cow::Owned(plpgsql::blank(sql, &ranges))
+        };
 
         // Parse the SQL
-        let dialect = self.dialect.parser_dialect();
-        let statements = match Parser::parse_sql(dialect.as_ref(), sql) {
-            Ok(stmts) => stmts,
+        let script = match parse_script(self.dialect, &script) {
+            Ok(script) => script,
             Err(e) => {
+                let start = sql.len() - sql.trim_start().len();
                 self.diagnostics.push(
                     Diagnostic::error(DiagnosticKind::ParseError, format!("Parse error: {}", e))
-                        .with_span(Span::new(0, sql.len().min(50))),
+                        .with_span(Span::new(start, (sql.len() - start).min(50))),
                 );
                 return std::mem::take(&mut self.diagnostics);
             }
         };
 
         // Analyze each statement
-        for stmt in &statements {
-            // Phase 1: Name resolution
-            let mut resolver = NameResolver::new(self.catalog);
-            resolver.resolve_statement(stmt);
+        let mut schema =
+            sequential.then(|| SchemaBuilder::from_catalog(self.catalog.clone(), self.dialect));
+        for stmt in &script.statements {
+            let catalog = schema.as_ref().map_or(self.catalog, SchemaBuilder::catalog);
+            self.analyze_statement(catalog, stmt, &script);
+            if let Some(schema) = &mut schema {
+                schema.process_statement(stmt);
+            }
+        }
+        // DO blocks come last, so they see the schema the whole script leaves
+        let catalog = schema.as_ref().map_or(self.catalog, SchemaBuilder::catalog);
 
-            // Phase 2: Type inference and checking
-            let mut type_resolver = TypeResolver::new(self.catalog);
-            type_resolver.inherit_scope(&resolver);
-            type_resolver.check_statement(stmt);
+        // Statements inside DO blocks; ones that aren't plain SQL are skipped
+        if self.plpgsql {
+            for block in &do_blocks {
+                for range in &block.statements {
+                    let text = plpgsql::isolate(sql, range, &block.into_targets);
+                    let Ok(block_script) = parse_script(self.dialect, &text) else {
+                        continue;
+                    };
+                    let start = self.diagnostics.len();
+                    for stmt in &block_script.statements {
+                        self.analyze_statement(catalog, stmt, &block_script);
+                    }
 
-            // Collect diagnostics from both phases
-            self.diagnostics.extend(resolver.into_diagnostics());
-            self.diagnostics.extend(type_resolver.into_diagnostics());
+                    // Block variables look like unknown columns to the resolver
+                    let block_diagnostics = self.diagnostics.split_off(start);
+                    self.diagnostics.extend(
+                        block_diagnostics
+                            .into_iter()
+                            .filter(|d| !is_block_variable_reference(d, &text, &block.variables)),
+                    );
+                }
+            }
         }
 
-        // Filter out diagnostics suppressed by inline directives
+        // The same problem can be reached from more than one walk path
+        dedup_diagnostics(&mut self.diagnostics);
         std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Drop diagnostics of rules that are off or suppressed by inline directives
+    fn filter_diagnostics(
+        &self,
+        diagnostics: Vec<Diagnostic>,
+        directives: &InlineDirectives,
+    ) -> Vec<Diagnostic> {
+        diagnostics
             .into_iter()
+            .filter(|d| self.is_rule_enabled(d.kind))
             .filter(|d| {
                 if let Some(span) = &d.span {
                     !directives.is_suppressed(d.code(), span.line)
@@ -127,4 +370,177 @@ impl<'a> Analyzer<'a> {
             })
             .collect()
     }
+
+    /// Classify an isolated statement
+    fn statement_kind(&self, sql: &str) -> StatementKind {
+        if self.dialect == SqlDialect::PostgreSQL && !plpgsql::find_do_blocks(sql).is_empty() {
+            return StatementKind::Do;
+        }
+        parse_script(self.dialect, sql)
+            .ok()
+            .and_then(|script| script.statements.first().map(StatementKind::of))
+            .unwrap_or(StatementKind::Other)
+    }
+
+    /// Run name resolution, type checking and lints on one statement
+    ///
+    /// `script` is the script the statement was parsed from, with the clauses
+    /// removed before parsing.
+    fn analyze_statement(&mut self, catalog: &Catalog, stmt: &Statement, script: &Script) {
+        // Phase 1: Name resolution
+        let mut resolver = NameResolver::new(catalog)
+            .with_dialect(self.dialect)
+            .with_overriding(script.overriding.clone())
+            .with_conflict_predicates(script.conflict_predicates.clone());
+        resolver.resolve_statement(stmt);
+
+        // Phase 2: Type inference and checking
+        let mut type_resolver = TypeResolver::new(catalog).with_dialect(self.dialect);
+        type_resolver.inherit_scope(&resolver);
+        type_resolver.check_statement(stmt);
+
+        // Phase 3: Opt-in lints
+        let mut linter = Linter::new();
+        linter.check_statement(stmt);
+
+        // Collect diagnostics from all phases
+        self.diagnostics.extend(resolver.into_diagnostics());
+        self.diagnostics.extend(type_resolver.into_diagnostics());
+        self.diagnostics.extend(linter.into_diagnostics());
+    }
+
+    /// Analyze a SQL query and count the resulting diagnostics by severity
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::Analyzer;
+    /// use sqlsift_core::schema::SchemaBuilder;
+    ///
+    /// let mut builder = SchemaBuilder::new();
+    /// builder.parse("CREATE TABLE users (id INTEGER, name TEXT);").unwrap();
+    /// let (catalog, _) = builder.build();
+    ///
+    /// let mut analyzer = Analyzer::new(&catalog);
+    /// let summary = analyzer.analyze_summary("SELECT nme FROM users");
+    /// assert_eq!(summary.errors, 1);
+    /// ```
+    pub fn analyze_summary(&mut self, sql: &str) -> DiagnosticSummary {
+        DiagnosticSummary::from_diagnostics(&self.analyze(sql))
+    }
+
+    /// Resolve the column reference at a source position to the column it refers to
+    ///
+    /// `line` and `column` are 1-indexed. References are followed through table
+    /// aliases, CTEs, and derived tables down to the underlying table or view.
+    /// Returns `None` if the position is not on a column reference, or if the
+    /// reference cannot be resolved (unknown, ambiguous, or computed columns).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::Analyzer;
+    /// use sqlsift_core::schema::{ColumnRef, SchemaBuilder};
+    ///
+    /// let mut builder = SchemaBuilder::new();
+    /// builder.parse("CREATE TABLE users (id INTEGER, name TEXT);").unwrap();
+    /// let (catalog, _) = builder.build();
+    ///
+    /// let analyzer = Analyzer::new(&catalog);
+    /// let origin = analyzer.resolve_column("SELECT u.name FROM users u", 1, 10);
+    /// assert_eq!(origin, Some(ColumnRef::new("public", "users", "name")));
+    /// ```
+    pub fn resolve_column(&self, sql: &str, line: usize, column: usize) -> Option<ColumnRef> {
+        let script = parse_script(self.dialect, sql).ok()?;
+
+        for stmt in &script.statements {
+            let mut resolver = NameResolver::new(self.catalog).with_dialect(self.dialect);
+            resolver.resolve_statement(stmt);
+            let found = resolver.resolved_columns.into_iter().find(|(span, _)| {
+                span.line == line && span.column <= column && column < span.column + span.length
+            });
+            if let Some((_, origin)) = found {
+                return Some(origin);
+            }
+        }
+        None
+    }
+
+    /// Count the references to each column across a document
+    ///
+    /// Only references that resolve to a table or view column are counted,
+    /// following aliases, CTEs, and derived tables as [`Analyzer::resolve_column`]
+    /// does. A document that fails to parse has no usage.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlsift_core::analyzer::Analyzer;
+    /// use sqlsift_core::schema::{ColumnRef, SchemaBuilder};
+    ///
+    /// let mut builder = SchemaBuilder::new();
+    /// builder.parse("CREATE TABLE users (id INTEGER, name TEXT);").unwrap();
+    /// let (catalog, _) = builder.build();
+    ///
+    /// let analyzer = Analyzer::new(&catalog);
+    /// let usage = analyzer.column_usage("SELECT name FROM users WHERE name <> ''");
+    /// assert_eq!(usage[&ColumnRef::new("public", "users", "name")], 2);
+    /// ```
+    pub fn column_usage(&self, sql: &str) -> HashMap<ColumnRef, usize> {
+        let mut usage = HashMap::new();
+        let Ok(script) = parse_script(self.dialect, sql) else {
+            return usage;
+        };
+
+        for stmt in &script.statements {
+            let mut resolver = NameResolver::new(self.catalog).with_dialect(self.dialect);
+            resolver.resolve_statement(stmt);
+            // A reference may be resolved more than once (e.g. ORDER BY); count it once
+            let mut seen = HashSet::new();
+            for (span, origin) in resolver.resolved_columns {
+                if seen.insert(span) {
+                    *usage.entry(origin).or_insert(0) += 1;
+                }
+            }
+        }
+        usage
+    }
+}
+
+/// Line and column (1-indexed) of a byte offset
+fn statement_location(sql: &str, offset: usize) -> Span {
+    let before = &sql[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Span::with_location(
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+        0,
+    )
+}
+
+/// Where a diagnostic on `node` goes: its own span, or `fallback` if it has none
+///
+/// sqlparser gives literals (numbers, strings, NULL) no source position, so
+/// a diagnostic on one points at a node around it instead, such as the
+/// target column, the clause or the query.
+fn span_or(node: &impl Spanned, fallback: Span) -> Span {
+    match Span::from_sqlparser(&node.span()) {
+        span if span.line > 0 => span,
+        _ => fallback,
+    }
+}
+
+/// Whether an unknown column/table diagnostic points at a `DO` block variable
+fn is_block_variable_reference(diagnostic: &Diagnostic, sql: &str, variables: &[String]) -> bool {
+    if !matches!(
+        diagnostic.kind,
+        DiagnosticKind::ColumnNotFound | DiagnosticKind::TableNotFound
+    ) {
+        return false;
+    }
+    diagnostic
+        .span
+        .as_ref()
+        .and_then(|span| plpgsql::span_text(sql, span))
+        .is_some_and(|name| variables.contains(&name.to_lowercase()))
 }
@@ -0,0 +1,593 @@
+//! Script parsing
+//!
+//! Some PostgreSQL syntax isn't understood by sqlparser. Where it only differs
+//! from supported syntax in ways that don't matter for analysis, the token
+//! stream is rewritten to the supported form before parsing:
+//!
+//! - `TABLE name` starting a statement, a parenthesized query (a CTE,
+//!   subquery or derived table) or a set operation's operand becomes
+//!   `SELECT * FROM name`
+//! - `FOR NO KEY UPDATE` / `FOR KEY SHARE` become `FOR UPDATE` / `FOR SHARE`
+//! - `FOR UPDATE OF a, b` becomes `FOR UPDATE OF a FOR UPDATE OF b`
+//! - `FROM t RETURNING` becomes `FROM t AS t RETURNING`, since sqlparser
+//!   would otherwise take `RETURNING` as the table's alias
+//! - `INSERT ... OVERRIDING SYSTEM VALUE` / `OVERRIDING USER VALUE` is
+//!   removed, and recorded in the parsed [`Script`] for the resolver
+//! - The index predicate of `ON CONFLICT (cols) WHERE pred DO ...` is
+//!   removed, and recorded in the parsed [`Script`] for the resolver
+//!
+//! Inserted tokens take the span of the token they replace, so diagnostics
+//! keep pointing into the source.
+
+use sqlparser::ast::{Expr, Statement};
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use sqlparser::tokenizer::{Location, Token, TokenWithSpan, Tokenizer};
+
+use crate::dialect::SqlDialect;
+
+/// A parsed script
+pub(super) struct Script {
+    pub(super) statements: Vec<Statement>,
+    /// The `OVERRIDING` clause of each INSERT that has one, keyed by the
+    /// location of the INSERT's table name
+    pub(super) overriding: HashMap<Location, Overriding>,
+    /// The index predicate of each `ON CONFLICT (cols) WHERE pred`, keyed by
+    /// the location of the INSERT's table name
+    pub(super) conflict_predicates: HashMap<Location, Expr>,
+}
+
+/// `INSERT ... OVERRIDING {SYSTEM | USER} VALUE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Overriding {
+    /// Supplied values are written to identity columns, even GENERATED ALWAYS
+    System,
+    /// Supplied values for identity columns are discarded and generated instead
+    User,
+}
+
+/// Parse a script into statements
+pub(super) fn parse_script(dialect: SqlDialect, sql: &str) -> Result<Script, ParserError> {
+    let parser_dialect = dialect.parser_dialect();
+    let mut tokens = Tokenizer::new(parser_dialect.as_ref(), sql)
+        .with_unescape(true)
+        .tokenize_with_location()?;
+    let mut overriding = HashMap::new();
+    let mut conflict_predicates = HashMap::new();
+    if dialect == SqlDialect::PostgreSQL {
+        tokens = expand_table_shorthand(tokens);
+        tokens = normalize_locking_clauses(tokens);
+        tokens = alias_tables_before_returning(tokens);
+        (tokens, overriding) = remove_overriding_clauses(tokens);
+        (tokens, conflict_predicates) = remove_conflict_predicates(dialect, tokens);
+    }
+    let statements = Parser::new(parser_dialect.as_ref())
+        .with_tokens_with_locations(tokens)
+        .parse_statements()?;
+    Ok(Script {
+        statements,
+        overriding,
+        conflict_predicates,
+    })
+}
+
+/// Byte ranges of the statements of a script, each ending before its `;`
+///
+/// A script that doesn't tokenize is returned as a single statement.
+pub(super) fn split_statements(dialect: SqlDialect, sql: &str) -> Vec<Range<usize>> {
+    let whole = || {
+        let start = sql.len() - sql.trim_start().len();
+        let end = sql.trim_end().len();
+        (start < end).then_some(start..end).into_iter().collect()
+    };
+    let parser_dialect = dialect.parser_dialect();
+    let Ok(tokens) = Tokenizer::new(parser_dialect.as_ref(), sql).tokenize_with_location() else {
+        return whole();
+    };
+    let lines = LineIndex::new(sql);
+    let mut statements = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    for token in &tokens {
+        match &token.token {
+            Token::Whitespace(_) | Token::EOF => {}
+            Token::SemiColon => statements.extend(current.take()),
+            _ => {
+                let (Some(start), Some(end)) =
+                    (lines.offset(token.span.start), lines.offset(token.span.end))
+                else {
+                    return whole();
+                };
+                match &mut current {
+                    Some(range) => range.end = end,
+                    None => current = Some(start..end),
+                }
+            }
+        }
+    }
+    statements.extend(current);
+    statements
+}
+
+/// Replace each `TABLE` keyword that starts a query with `SELECT * FROM`
+///
+/// sqlparser parses `UNION TABLE name` itself, but the analysis only looks
+/// into SELECTs, so set operation operands are expanded too.
+fn expand_table_shorthand(tokens: Vec<TokenWithSpan>) -> Vec<TokenWithSpan> {
+    let mut expanded = Vec::with_capacity(tokens.len());
+    let mut query_start = true;
+    for token in tokens {
+        if matches!(token.token, Token::Whitespace(_)) {
+            expanded.push(token);
+            continue;
+        }
+        if query_start && is_keyword(&token, Keyword::TABLE) {
+            let span = token.span;
+            expanded.extend(
+                [
+                    Token::make_keyword("SELECT"),
+                    Token::Mul,
+                    Token::make_keyword("FROM"),
+                ]
+                .into_iter()
+                .map(|t| TokenWithSpan::new(t, span)),
+            );
+        } else {
+            expanded.push(token);
+        }
+        query_start = expanded.last().is_some_and(|t| match &t.token {
+            Token::SemiColon | Token::LParen => true,
+            Token::Word(w) => matches!(
+                w.keyword,
+                Keyword::UNION
+                    | Keyword::INTERSECT
+                    | Keyword::EXCEPT
+                    | Keyword::ALL
+                    | Keyword::DISTINCT
+            ),
+            _ => false,
+        });
+    }
+    expanded
+}
+
+/// Reduce PostgreSQL locking clauses to the forms sqlparser parses
+///
+/// The lock strength doesn't matter for analysis, and a clause locking
+/// several tables is equivalent to one clause per table.
+fn normalize_locking_clauses(tokens: Vec<TokenWithSpan>) -> Vec<TokenWithSpan> {
+    // Significant (non-whitespace) tokens, for lookahead
+    let significant: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !matches!(t.token, Token::Whitespace(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let keyword_at = |pos: usize| match significant.get(pos).map(|&idx| &tokens[idx].token) {
+        Some(Token::Word(w)) => Some(w.keyword),
+        _ => None,
+    };
+
+    let mut dropped = vec![false; tokens.len()];
+    // Lock strength keyword of the clause whose OF list is being read
+    let mut of_list: Option<Token> = None;
+    let mut separators = Vec::new();
+    for (pos, &idx) in significant.iter().enumerate() {
+        let token = &tokens[idx];
+        if let Some(strength) = &of_list {
+            match &token.token {
+                Token::Comma => {
+                    separators.push((idx, strength.clone()));
+                    continue;
+                }
+                // A schema-qualified name is one entry
+                Token::Period => continue,
+                Token::Word(w) if w.keyword != Keyword::FOR => continue,
+                _ => of_list = None,
+            }
+        }
+        if keyword_at(pos) != Some(Keyword::FOR) {
+            continue;
+        }
+        let strength_pos = match (
+            keyword_at(pos + 1),
+            keyword_at(pos + 2),
+            keyword_at(pos + 3),
+        ) {
+            (Some(Keyword::NO), Some(Keyword::KEY), Some(Keyword::UPDATE)) => {
+                dropped[significant[pos + 1]] = true;
+                dropped[significant[pos + 2]] = true;
+                pos + 3
+            }
+            (Some(Keyword::KEY), Some(Keyword::SHARE), _) => {
+                dropped[significant[pos + 1]] = true;
+                pos + 2
+            }
+            (Some(Keyword::UPDATE | Keyword::SHARE), _, _) => pos + 1,
+            _ => continue,
+        };
+        if keyword_at(strength_pos + 1) == Some(Keyword::OF) {
+            of_list = Some(tokens[significant[strength_pos]].token.clone());
+        }
+    }
+
+    let mut normalized = Vec::with_capacity(tokens.len() + separators.len() * 2);
+    let mut separators = separators.into_iter().peekable();
+    for (idx, token) in tokens.into_iter().enumerate() {
+        if dropped[idx] {
+            continue;
+        }
+        match separators.next_if(|(sep, _)| *sep == idx) {
+            Some((_, strength)) => normalized.extend(
+                [
+                    Token::make_keyword("FOR"),
+                    strength,
+                    Token::make_keyword("OF"),
+                ]
+                .into_iter()
+                .map(|t| TokenWithSpan::new(t, token.span)),
+            ),
+            None => normalized.push(token),
+        }
+    }
+    normalized
+}
+
+/// Give a bare table name directly followed by `RETURNING` an explicit alias
+///
+/// The alias repeats the table's own name, so the table is referenced the
+/// same way as before. Only names after `FROM`, `JOIN` and `USING` are
+/// rewritten; that covers `DELETE FROM t RETURNING` and an `INSERT ... SELECT`
+/// source ending in its FROM clause.
+fn alias_tables_before_returning(tokens: Vec<TokenWithSpan>) -> Vec<TokenWithSpan> {
+    let significant: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !matches!(t.token, Token::Whitespace(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    // Index of the last name part of each table to alias
+    let mut aliased = Vec::new();
+    for (pos, &idx) in significant.iter().enumerate() {
+        if !is_keyword(&tokens[idx], Keyword::RETURNING) || pos == 0 {
+            continue;
+        }
+        let name_end = significant[pos - 1];
+        if !matches!(tokens[name_end].token, Token::Word(_)) {
+            continue;
+        }
+        // Walk back over a qualified name `a.b.c`
+        let mut start = pos - 1;
+        while start >= 2
+            && tokens[significant[start - 1]].token == Token::Period
+            && matches!(tokens[significant[start - 2]].token, Token::Word(_))
+        {
+            start -= 2;
+        }
+        let follows_table_keyword = start > 0
+            && [Keyword::FROM, Keyword::JOIN, Keyword::USING]
+                .into_iter()
+                .any(|keyword| is_keyword(&tokens[significant[start - 1]], keyword));
+        if follows_table_keyword {
+            aliased.push(name_end);
+        }
+    }
+
+    let mut rewritten = Vec::with_capacity(tokens.len() + aliased.len() * 2);
+    let mut aliased = aliased.into_iter().peekable();
+    for (idx, token) in tokens.into_iter().enumerate() {
+        let alias = aliased.next_if_eq(&idx).map(|_| token.clone());
+        rewritten.push(token);
+        if let Some(alias) = alias {
+            rewritten.push(TokenWithSpan::new(Token::make_keyword("AS"), alias.span));
+            rewritten.push(alias);
+        }
+    }
+    rewritten
+}
+
+/// Remove `OVERRIDING {SYSTEM | USER} VALUE` clauses, which sqlparser doesn't parse
+///
+/// Each clause is returned keyed by the location of the table name after the
+/// nearest preceding `INTO`.
+fn remove_overriding_clauses(
+    tokens: Vec<TokenWithSpan>,
+) -> (Vec<TokenWithSpan>, HashMap<Location, Overriding>) {
+    let significant: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !matches!(t.token, Token::Whitespace(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let word_at = |pos: usize| match significant.get(pos).map(|&idx| &tokens[idx].token) {
+        Some(Token::Word(w)) => Some(w.value.to_uppercase()),
+        _ => None,
+    };
+
+    let mut dropped = vec![false; tokens.len()];
+    let mut overriding = HashMap::new();
+    for pos in 0..significant.len() {
+        if word_at(pos).as_deref() != Some("OVERRIDING") {
+            continue;
+        }
+        let kind = match (word_at(pos + 1).as_deref(), word_at(pos + 2).as_deref()) {
+            (Some("SYSTEM"), Some("VALUE")) => Overriding::System,
+            (Some("USER"), Some("VALUE")) => Overriding::User,
+            _ => continue,
+        };
+        let table = (0..pos)
+            .rev()
+            .find(|&p| is_keyword(&tokens[significant[p]], Keyword::INTO))
+            .and_then(|p| significant.get(p + 1));
+        if let Some(&table) = table {
+            overriding.insert(tokens[table].span.start, kind);
+        }
+        for &idx in &significant[pos..pos + 3] {
+            dropped[idx] = true;
+        }
+    }
+
+    let kept = tokens
+        .into_iter()
+        .zip(dropped)
+        .filter_map(|(token, dropped)| (!dropped).then_some(token))
+        .collect();
+    (kept, overriding)
+}
+
+/// Remove the index predicate from `ON CONFLICT (cols) WHERE pred DO ...`,
+/// which sqlparser doesn't parse
+///
+/// Each predicate is parsed on its own and returned keyed by the location of
+/// the table name after the nearest preceding `INTO`. A predicate that doesn't
+/// parse is left in place, so the statement reports the parse error.
+fn remove_conflict_predicates(
+    dialect: SqlDialect,
+    tokens: Vec<TokenWithSpan>,
+) -> (Vec<TokenWithSpan>, HashMap<Location, Expr>) {
+    let significant: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !matches!(t.token, Token::Whitespace(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let token_at = |pos: usize| significant.get(pos).map(|&idx| &tokens[idx]);
+
+    let mut dropped = vec![false; tokens.len()];
+    let mut predicates = HashMap::new();
+    for pos in 0..significant.len() {
+        let is_target = token_at(pos).is_some_and(|t| is_keyword(t, Keyword::ON))
+            && token_at(pos + 1).is_some_and(|t| is_keyword(t, Keyword::CONFLICT))
+            && token_at(pos + 2).is_some_and(|t| t.token == Token::LParen);
+        if !is_target {
+            continue;
+        }
+        // Skip the column list to the WHERE after it
+        let mut depth = 0;
+        let mut where_pos = None;
+        for p in pos + 2..significant.len() {
+            match tokens[significant[p]].token {
+                Token::LParen => depth += 1,
+                Token::RParen => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                where_pos = Some(p + 1)
+                    .filter(|&w| token_at(w).is_some_and(|t| is_keyword(t, Keyword::WHERE)));
+                break;
+            }
+        }
+        let Some(where_pos) = where_pos else {
+            continue;
+        };
+        // The predicate runs to the DO outside any parentheses
+        let mut depth = 0;
+        let Some(do_pos) = (where_pos + 1..significant.len()).find(|&p| {
+            match tokens[significant[p]].token {
+                Token::LParen => depth += 1,
+                Token::RParen => depth -= 1,
+                _ => {}
+            }
+            depth == 0 && is_keyword(&tokens[significant[p]], Keyword::DO)
+        }) else {
+            continue;
+        };
+
+        let predicate_tokens = tokens[significant[where_pos + 1]..significant[do_pos]].to_vec();
+        let parser_dialect = dialect.parser_dialect();
+        let Ok(predicate) = Parser::new(parser_dialect.as_ref())
+            .with_tokens_with_locations(predicate_tokens)
+            .parse_expr()
+        else {
+            continue;
+        };
+        let table = (0..pos)
+            .rev()
+            .find(|&p| is_keyword(&tokens[significant[p]], Keyword::INTO))
+            .and_then(|p| significant.get(p + 1));
+        if let Some(&table) = table {
+            predicates.insert(tokens[table].span.start, predicate);
+        }
+        for flag in &mut dropped[significant[where_pos]..significant[do_pos]] {
+            *flag = true;
+        }
+    }
+
+    let kept = tokens
+        .into_iter()
+        .zip(dropped)
+        .filter_map(|(token, dropped)| (!dropped).then_some(token))
+        .collect();
+    (kept, predicates)
+}
+
+fn is_keyword(token: &TokenWithSpan, keyword: Keyword) -> bool {
+    matches!(&token.token, Token::Word(w) if w.keyword == keyword)
+}
+
+/// Converts tokenizer locations (1-indexed line and character) to byte offsets
+pub(super) struct LineIndex<'s> {
+    sql: &'s str,
+    starts: Vec<usize>,
+}
+
+impl<'s> LineIndex<'s> {
+    pub(super) fn new(sql: &'s str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(sql.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { sql, starts }
+    }
+
+    pub(super) fn offset(&self, location: Location) -> Option<usize> {
+        let start = *self.starts.get((location.line as usize).checked_sub(1)?)?;
+        let column = (location.column as usize).checked_sub(1)?;
+        let line = &self.sql[start..];
+        let within = line
+            .char_indices()
+            .nth(column)
+            .map_or(line.len(), |(i, _)| i);
+        Some(start + within)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reparse(sql: &str) -> String {
+        parse_script(SqlDialect::PostgreSQL, sql)
+            .unwrap()
+            .statements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    #[test]
+    fn test_table_shorthand() {
+        assert_eq!(
+            reparse("TABLE users; CREATE TABLE t (id INT)"),
+            "SELECT * FROM users; CREATE TABLE t (id INT)"
+        );
+        assert_eq!(
+            reparse("WITH u AS (TABLE users) SELECT 1 UNION TABLE u"),
+            "WITH u AS (SELECT * FROM users) SELECT 1 UNION SELECT * FROM u"
+        );
+    }
+
+    #[test]
+    fn test_locking_clause_strengths() {
+        assert_eq!(
+            reparse("SELECT id FROM users FOR NO KEY UPDATE SKIP LOCKED"),
+            "SELECT id FROM users FOR UPDATE SKIP LOCKED"
+        );
+        assert_eq!(
+            reparse("SELECT id FROM users FOR KEY SHARE NOWAIT"),
+            "SELECT id FROM users FOR SHARE NOWAIT"
+        );
+    }
+
+    #[test]
+    fn test_locking_clause_of_list() {
+        assert_eq!(
+            reparse("SELECT 1 FROM users u, orders o FOR SHARE OF u, o NOWAIT"),
+            "SELECT 1 FROM users AS u, orders AS o FOR SHARE OF u FOR SHARE OF o NOWAIT"
+        );
+        assert_eq!(
+            reparse("SELECT 1 FROM public.users, orders FOR UPDATE OF public.users, orders"),
+            "SELECT 1 FROM public.users, orders FOR UPDATE OF public.users FOR UPDATE OF orders"
+        );
+    }
+
+    #[test]
+    fn test_for_outside_locking_clause() {
+        assert_eq!(
+            reparse("SELECT SUBSTRING(name FROM 1 FOR 2), 3 FROM users"),
+            "SELECT SUBSTRING(name FROM 1 FOR 2), 3 FROM users"
+        );
+    }
+
+    #[test]
+    fn test_alias_before_returning() {
+        assert_eq!(
+            reparse("DELETE FROM public.users RETURNING id"),
+            "DELETE FROM public.users AS users RETURNING id"
+        );
+        assert_eq!(
+            reparse("INSERT INTO orders (user_id) SELECT id FROM \"Users\" RETURNING user_id"),
+            "INSERT INTO orders (user_id) SELECT id FROM \"Users\" AS \"Users\" RETURNING user_id"
+        );
+        assert_eq!(
+            reparse("DELETE FROM users u RETURNING u.id"),
+            "DELETE FROM users AS u RETURNING u.id"
+        );
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "-- first\nSELECT ';' FROM users;\n\n  DO $$ BEGIN NULL; END $$;;\nSELECT 2";
+        let statements: Vec<&str> = split_statements(SqlDialect::PostgreSQL, sql)
+            .into_iter()
+            .map(|range| &sql[range])
+            .collect();
+        assert_eq!(
+            statements,
+            [
+                "SELECT ';' FROM users",
+                "DO $$ BEGIN NULL; END $$",
+                "SELECT 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overriding_clause() {
+        let sql = "INSERT INTO accounts (id) OVERRIDING SYSTEM VALUE VALUES (1);\n\
+                   INSERT INTO public.accounts OVERRIDING USER VALUE SELECT 1;\n\
+                   INSERT INTO accounts VALUES (1)";
+        let script = parse_script(SqlDialect::PostgreSQL, sql).unwrap();
+        assert_eq!(
+            script.statements[0].to_string(),
+            "INSERT INTO accounts (id) VALUES (1)"
+        );
+        assert_eq!(
+            script.statements[1].to_string(),
+            "INSERT INTO public.accounts SELECT 1"
+        );
+        let mut overriding: Vec<_> = script
+            .overriding
+            .into_iter()
+            .map(|(location, kind)| (location.line, location.column, kind))
+            .collect();
+        overriding.sort_by_key(|&(line, column, _)| (line, column));
+        assert_eq!(
+            overriding,
+            [(1, 13, Overriding::System), (2, 13, Overriding::User)]
+        );
+    }
+
+    #[test]
+    fn test_conflict_predicate() {
+        let sql = "INSERT INTO users (email) VALUES ('a')\n\
+                   ON CONFLICT (email) WHERE (active AND deleted_at IS NULL) DO NOTHING";
+        let script = parse_script(SqlDialect::PostgreSQL, sql).unwrap();
+        assert_eq!(
+            script.statements[0].to_string(),
+            "INSERT INTO users (email) VALUES ('a') ON CONFLICT(email) DO NOTHING"
+        );
+        let predicates: Vec<_> = script
+            .conflict_predicates
+            .iter()
+            .map(|(location, expr)| (location.line, location.column, expr.to_string()))
+            .collect();
+        assert_eq!(
+            predicates,
+            [(1, 13, "(active AND deleted_at IS NULL)".to_string())]
+        );
+    }
+}
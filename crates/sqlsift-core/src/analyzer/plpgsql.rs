@@ -0,0 +1,344 @@
+//! PostgreSQL `DO $$ ... $$` anonymous blocks
+//!
+//! sqlparser can't parse `DO`, so blocks are blanked out of a script before it
+//! is parsed. With PL/pgSQL analysis on, the plain SQL statements in a block's
+//! body are analyzed as well: each one is parsed from a copy of the script in
+//! which everything else is blanked, so its spans point into the block.
+//!
+//! Only statements that start a PL/pgSQL statement (optionally after `BEGIN`,
+//! `THEN`, `ELSE` or `LOOP`) are picked up; `PERFORM`, assignments, `RETURN
+//! QUERY` and the like are skipped.
+//!
+//! In PL/pgSQL, `SELECT ... INTO x` and `... RETURNING ... INTO x` assign to
+//! variables rather than create a table, so those `INTO` clauses are blanked
+//! as well and the rest of the statement is analyzed as a plain query.
+
+use std::ops::Range;
+
+use sqlparser::dialect::PostgreSqlDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Location, Token, TokenWithSpan, Tokenizer};
+
+use crate::error::Span;
+
+use super::parse::LineIndex;
+
+/// A `DO` statement in a script
+pub(super) struct DoBlock {
+    /// Byte range of the whole statement, from `DO` through its `;`
+    pub(super) statement: Range<usize>,
+    /// Byte ranges of the SQL statements in the body
+    pub(super) statements: Vec<Range<usize>>,
+    /// Byte ranges of the `INTO [STRICT] target, ...` clauses of those statements
+    pub(super) into_targets: Vec<Range<usize>>,
+    /// Variables declared in the body's DECLARE section (lowercased)
+    pub(super) variables: Vec<String>,
+}
+
+/// Find the `DO` blocks of a script (none if it doesn't tokenize)
+pub(super) fn find_do_blocks(sql: &str) -> Vec<DoBlock> {
+    let Some(tokens) = tokenize(sql) else {
+        return Vec::new();
+    };
+    let lines = LineIndex::new(sql);
+
+    let mut blocks = Vec::new();
+    let mut statement_start = true;
+    for (i, token) in tokens.iter().enumerate() {
+        if statement_start && is_keyword(token, &[Keyword::DO]) {
+            if let Some(block) = parse_do_block(sql, &lines, &tokens[i..]) {
+                blocks.push(block);
+            }
+        }
+        statement_start = token.token == Token::SemiColon;
+    }
+    blocks
+}
+
+/// `DO [LANGUAGE plpgsql] $$ body $$ [LANGUAGE plpgsql] [;]`, starting at `DO`
+fn parse_do_block(sql: &str, lines: &LineIndex, tokens: &[TokenWithSpan]) -> Option<DoBlock> {
+    let mut rest = skip_language(&tokens[1..]);
+    let (body_token, after) = rest.split_first()?;
+    let Token::DollarQuotedString(body) = &body_token.token else {
+        return None;
+    };
+    rest = skip_language(after);
+    let end = match rest.first() {
+        Some(t) if t.token == Token::SemiColon => t.span.end,
+        None => body_token.span.end,
+        Some(_) => return None,
+    };
+
+    let delimiter = body.tag.as_ref().map_or(0, |tag| tag.len()) + 2;
+    let body_start = lines.offset(body_token.span.start)? + delimiter;
+    let body_end = body_start + body.value.len();
+    let body = body_statements(&sql[body_start..body_end]);
+    let shift = |ranges: Vec<Range<usize>>| -> Vec<Range<usize>> {
+        ranges
+            .into_iter()
+            .map(|r| r.start + body_start..r.end + body_start)
+            .collect()
+    };
+
+    Some(DoBlock {
+        statement: lines.offset(tokens[0].span.start)?..lines.offset(end)?,
+        statements: shift(body.statements),
+        into_targets: shift(body.into_targets),
+        variables: body.variables,
+    })
+}
+
+/// Skip an optional `LANGUAGE name`
+fn skip_language(tokens: &[TokenWithSpan]) -> &[TokenWithSpan] {
+    match tokens {
+        [language, _, rest @ ..] if is_keyword(language, &[Keyword::LANGUAGE]) => rest,
+        _ => tokens,
+    }
+}
+
+/// What a PL/pgSQL body holds, as byte ranges relative to the body
+#[derive(Default)]
+struct Body {
+    statements: Vec<Range<usize>>,
+    into_targets: Vec<Range<usize>>,
+    variables: Vec<String>,
+}
+
+/// The SQL statements in a PL/pgSQL body, and its declared variables
+fn body_statements(body: &str) -> Body {
+    let Some(tokens) = tokenize(body) else {
+        return Body::default();
+    };
+    let lines = LineIndex::new(body);
+
+    let mut result = Body::default();
+    let mut in_declare = false;
+    for chunk in tokens.split(|t| t.token == Token::SemiColon) {
+        let mut chunk = chunk;
+        if let [first, ..] = chunk {
+            if is_keyword(first, &[Keyword::DECLARE]) {
+                in_declare = true;
+                chunk = &chunk[1..];
+            }
+        }
+        if in_declare {
+            match chunk.iter().position(|t| is_keyword(t, &[Keyword::BEGIN])) {
+                Some(begin) => {
+                    in_declare = false;
+                    chunk = &chunk[begin..];
+                }
+                None => {
+                    if let Some(Token::Word(name)) = chunk.first().map(|t| &t.token) {
+                        result.variables.push(name.value.to_lowercase());
+                    }
+                    continue;
+                }
+            }
+        }
+
+        let Some(start) = chunk.iter().position(|t| {
+            is_keyword(
+                t,
+                &[
+                    Keyword::SELECT,
+                    Keyword::INSERT,
+                    Keyword::UPDATE,
+                    Keyword::DELETE,
+                    Keyword::WITH,
+                ],
+            )
+        }) else {
+            continue;
+        };
+        if start > 0 && !starts_block_statement(&chunk[start - 1]) {
+            continue;
+        }
+        if let (Some(from), Some(to)) = (
+            lines.offset(chunk[start].span.start),
+            chunk.last().and_then(|t| lines.offset(t.span.end)),
+        ) {
+            result.statements.push(from..to);
+        }
+        if let Some(target) = into_target(&chunk[start..]) {
+            if let (Some(from), Some(to)) = (
+                lines.offset(target[0].span.start),
+                target.last().and_then(|t| lines.offset(t.span.end)),
+            ) {
+                result.into_targets.push(from..to);
+            }
+        }
+    }
+    result
+}
+
+/// The variable-assigning `INTO [STRICT] target, ...` clause of a statement
+///
+/// That's a top-level `INTO` other than the table-naming one of `INSERT INTO`
+/// or `MERGE INTO`; targets are (possibly qualified) names separated by commas.
+fn into_target(tokens: &[TokenWithSpan]) -> Option<&[TokenWithSpan]> {
+    let mut depth = 0usize;
+    let mut start = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth = depth.saturating_sub(1),
+            _ if depth == 0
+                && is_keyword(token, &[Keyword::INTO])
+                && !(i > 0 && is_keyword(&tokens[i - 1], &[Keyword::INSERT, Keyword::MERGE])) =>
+            {
+                start = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let start = start?;
+
+    let mut end = start + 1;
+    if tokens
+        .get(end)
+        .is_some_and(|t| is_keyword(t, &[Keyword::STRICT]))
+    {
+        end += 1;
+    }
+    loop {
+        if !matches!(tokens.get(end).map(|t| &t.token), Some(Token::Word(_))) {
+            return None;
+        }
+        end += 1;
+        while matches!(tokens.get(end).map(|t| &t.token), Some(Token::Period))
+            && matches!(tokens.get(end + 1).map(|t| &t.token), Some(Token::Word(_)))
+        {
+            end += 2;
+        }
+        if tokens.get(end).map(|t| &t.token) != Some(&Token::Comma) {
+            return Some(&tokens[start..end]);
+        }
+        end += 1;
+    }
+}
+
+/// Replace the characters in `ranges` with spaces, keeping line breaks
+pub(super) fn blank(sql: &str, ranges: &[Range<usize>]) -> String {
+    sql.char_indices()
+        .map(|(i, c)| {
+            if c != '\n' && ranges.iter().any(|r| r.contains(&i)) {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Blank everything but `keep` (and `holes` within it), so spans still refer
+/// to the original script
+pub(super) fn isolate(sql: &str, keep: &Range<usize>, holes: &[Range<usize>]) -> String {
+    let mut ranges = vec![0..keep.start, keep.end..sql.len()];
+    ranges.extend(holes.iter().cloned());
+    blank(sql, &ranges)
+}
+
+/// The source text a diagnostic span covers
+pub(super) fn span_text<'s>(sql: &'s str, span: &Span) -> Option<&'s str> {
+    let start = LineIndex::new(sql).offset(Location::new(span.line as u64, span.column as u64))?;
+    let len = sql[start..]
+        .char_indices()
+        .nth(span.length)
+        .map_or(sql.len() - start, |(i, _)| i);
+    Some(&sql[start..start + len])
+}
+
+fn tokenize(sql: &str) -> Option<Vec<TokenWithSpan>> {
+    let tokens = Tokenizer::new(&PostgreSqlDialect {}, sql)
+        .tokenize_with_location()
+        .ok()?;
+    Some(
+        tokens
+            .into_iter()
+            .filter(|t| !matches!(t.token, Token::Whitespace(_)))
+            .collect(),
+    )
+}
+
+/// `BEGIN`, `THEN`, `ELSE` or `LOOP`: a statement may follow directly
+fn starts_block_statement(token: &TokenWithSpan) -> bool {
+    match &token.token {
+        Token::Word(word) => {
+            matches!(word.keyword, Keyword::BEGIN | Keyword::THEN | Keyword::ELSE)
+                || word.value.eq_ignore_ascii_case("loop")
+        }
+        _ => false,
+    }
+}
+
+fn is_keyword(token: &TokenWithSpan, keywords: &[Keyword]) -> bool {
+    matches!(&token.token, Token::Word(word) if keywords.contains(&word.keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_do_block() {
+        let sql = "SELECT 1;\nDO $$\nDECLARE n integer;\nBEGIN\n  UPDATE users SET name = 'x';\n  PERFORM 1;\nEND\n$$;\nSELECT 2;";
+        let blocks = find_do_blocks(sql);
+        assert_eq!(blocks.len(), 1);
+
+        let block = &blocks[0];
+        assert_eq!(&sql[block.statement.clone()], &sql[10..sql.len() - 10]);
+        assert_eq!(block.variables, vec!["n"]);
+        assert_eq!(block.statements.len(), 1);
+        assert_eq!(
+            &sql[block.statements[0].clone()],
+            "UPDATE users SET name = 'x'"
+        );
+    }
+
+    #[test]
+    fn test_find_do_block_with_tag_and_language() {
+        let sql = "DO LANGUAGE plpgsql $body$ BEGIN IF true THEN DELETE FROM t; END IF; END $body$";
+        let blocks = find_do_blocks(sql);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].statement, 0..sql.len());
+        assert_eq!(&sql[blocks[0].statements[0].clone()], "DELETE FROM t");
+    }
+
+    #[test]
+    fn test_do_as_identifier_is_not_a_block() {
+        assert!(find_do_blocks("SELECT do FROM t").is_empty());
+        assert!(find_do_blocks("INSERT INTO t VALUES (1) ON CONFLICT DO NOTHING").is_empty());
+    }
+
+    #[test]
+    fn test_find_into_targets() {
+        let sql = "DO $$ BEGIN SELECT id INTO STRICT n FROM t; INSERT INTO t VALUES (1) RETURNING id INTO r.a, m; SELECT (SELECT 1) FROM t; END $$";
+        let blocks = find_do_blocks(sql);
+        let targets: Vec<_> = blocks[0]
+            .into_targets
+            .iter()
+            .map(|r| &sql[r.clone()])
+            .collect();
+        assert_eq!(targets, vec!["INTO STRICT n", "INTO r.a, m"]);
+    }
+
+    #[test]
+    fn test_table_into_is_not_a_target() {
+        for sql in [
+            "INSERT INTO t VALUES (1)",
+            "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED THEN DELETE",
+        ] {
+            let tokens = tokenize(sql).unwrap();
+            assert!(into_target(&tokens).is_none(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_isolate_keeps_positions() {
+        let sql = "ab\ncdé\nfg";
+        assert_eq!(isolate(sql, &(4..7), &[]), "  \n dé\n  ");
+        assert_eq!(isolate(sql, &(0..7), &[1..2, 3..4]), "a \n dé\n  ");
+        assert_eq!(span_text(sql, &Span::with_location(2, 2, 2)), Some("dé"));
+    }
+}
@@ -1,13 +1,27 @@
 //! Name resolver - resolves table and column references
 
 use sqlparser::ast::{
-    Assignment, AssignmentTarget, Delete, Expr, GroupByExpr, Ident, Insert, ObjectName, Query,
-    Select, SelectItem, SetExpr, Statement, Subscript, TableFactor, TableWithJoins, Values,
+    visit_expressions, visit_expressions_mut, Assignment, AssignmentTarget, BinaryOperator,
+    ConflictTarget, Delete, Expr, Function, FunctionArguments, GroupByExpr, Ident, Insert,
+    MergeAction, MergeClause, MergeInsertKind, ObjectName, OnConflict, OnConflictAction, OnInsert,
+    OrderByExpr, Query, Select, SelectItem, SetExpr, Spanned, Statement, Subscript, TableAlias,
+    TableFactor, TableWithJoins, TruncateTableTarget, UnaryOperator, Value, Values, VisitMut,
+    Visitor, VisitorMut,
 };
+use sqlparser::tokenizer::Location;
 use std::collections::HashMap;
+use std::ops::{ControlFlow, Range};
 
+use crate::dialect::SqlDialect;
 use crate::error::{Diagnostic, DiagnosticKind, Span};
-use crate::schema::{Catalog, QualifiedName, TableDef};
+use crate::schema::{
+    Catalog, ColumnDef, ColumnRef, CompositeTypeDef, IdentityKind, QualifiedName, SourceLocation,
+    TableDef,
+};
+use crate::types::SqlType;
+
+use super::parse::Overriding;
+use super::span_or;
 
 /// Resolved table reference in a query
 #[derive(Debug, Clone)]
@@ -24,6 +38,8 @@ pub(super) struct TableRef {
     pub(super) view_columns: Option<Vec<String>>,
     /// If this is a derived table (subquery in FROM), the inferred column names
     pub(super) derived_columns: Option<Vec<String>>,
+    /// For derived tables, where each (lowercased) output column originates from
+    pub(super) column_origins: HashMap<String, ColumnRef>,
 }
 
 /// CTE (Common Table Expression) definition
@@ -36,7 +52,23 @@ pub(super) struct CteDefinition {
     #[allow(dead_code)]
     pub(super) name: String,
     /// Column names inferred from the CTE query
+    ///
+    /// Empty when the columns can't be inferred (e.g. `SELECT *`), in which
+    /// case references to the CTE are not validated.
     pub(super) columns: Vec<String>,
+    /// Where each (lowercased) output column originates from
+    pub(super) column_origins: HashMap<String, ColumnRef>,
+}
+
+/// A `JOIN ... ON` seen in the current query, for the unused-join-table check
+#[derive(Debug, Clone)]
+struct JoinOn {
+    /// Scope key (alias or table name) of the joined relation
+    key: String,
+    /// Span of the joined relation's name
+    span: Span,
+    /// Entries of `NameResolver::table_uses` recorded while resolving its ON
+    on_uses: Range<usize>,
 }
 
 /// Name resolver for SQL queries
@@ -50,6 +82,18 @@ pub struct NameResolver<'a> {
     pub(super) ctes: HashMap<String, CteDefinition>,
     /// SELECT aliases visible in ORDER BY (set before resolving ORDER BY)
     select_aliases: Vec<String>,
+    /// Successfully resolved column references and the column they point at
+    pub(super) resolved_columns: Vec<(Span, ColumnRef)>,
+    /// Scope key of the table each resolved column (or wildcard) came from
+    table_uses: Vec<String>,
+    /// JOIN ... ON clauses of the queries currently being resolved
+    join_ons: Vec<JoinOn>,
+    /// Dialect-specific grouping rules
+    dialect: SqlDialect,
+    /// `OVERRIDING` clauses of the script's INSERTs, by table name location
+    overriding: HashMap<Location, Overriding>,
+    /// `ON CONFLICT (cols) WHERE` index predicates, by table name location
+    conflict_predicates: HashMap<Location, Expr>,
     /// Collected diagnostics
     diagnostics: Vec<Diagnostic>,
 }
@@ -65,10 +109,37 @@ impl<'a> NameResolver<'a> {
             outer_tables: HashMap::new(),
             select_aliases: Vec::new(),
             ctes: HashMap::new(),
+            resolved_columns: Vec::new(),
+            table_uses: Vec::new(),
+            join_ons: Vec::new(),
+            dialect: SqlDialect::default(),
+            overriding: HashMap::new(),
+            conflict_predicates: HashMap::new(),
             diagnostics: Vec::new(),
         }
     }
 
+    /// Use the grouping rules of a specific SQL dialect
+    pub fn with_dialect(mut self, dialect: SqlDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Set the `OVERRIDING` clauses removed from the script before parsing
+    pub(super) fn with_overriding(mut self, overriding: HashMap<Location, Overriding>) -> Self {
+        self.overriding = overriding;
+        self
+    }
+
+    /// Set the `ON CONFLICT` index predicates removed from the script before parsing
+    pub(super) fn with_conflict_predicates(
+        mut self,
+        conflict_predicates: HashMap<Location, Expr>,
+    ) -> Self {
+        self.conflict_predicates = conflict_predicates;
+        self
+    }
+
     /// Resolve names in a statement
     ///
     /// Validates all table and column references in the statement against the catalog.
@@ -91,66 +162,202 @@ impl<'a> NameResolver<'a> {
             Statement::Delete(delete) => {
                 self.resolve_delete(delete);
             }
+            Statement::Truncate { table_names, .. } => {
+                self.resolve_truncate(table_names);
+            }
+            Statement::Merge {
+                table,
+                source,
+                on,
+                clauses,
+                ..
+            } => {
+                self.resolve_merge(table, source, on, clauses);
+            }
             _ => {}
         }
     }
 
+    /// Resolve names in a MERGE statement
+    ///
+    /// The ON condition and the WHEN clauses see both the target and the
+    /// source; the columns written by UPDATE SET and INSERT belong to the target.
+    fn resolve_merge(
+        &mut self,
+        table: &TableFactor,
+        source: &TableFactor,
+        on: &Expr,
+        clauses: &[MergeClause],
+    ) {
+        self.resolve_table_factor(table);
+        self.resolve_table_factor(source);
+        self.resolve_expr(on);
+
+        let table_name = match table {
+            TableFactor::Table { name, .. } => Some(self.dialect.qualified_name(name)),
+            _ => None,
+        };
+        let table_def = table_name.as_ref().and_then(|n| self.catalog.get_table(n));
+
+        for clause in clauses {
+            if let Some(predicate) = &clause.predicate {
+                self.resolve_expr(predicate);
+            }
+            let (columns, values): (Vec<&Ident>, Vec<&Expr>) = match &clause.action {
+                MergeAction::Update { assignments } => assignments
+                    .iter()
+                    .filter_map(|assignment| match &assignment.target {
+                        AssignmentTarget::ColumnName(name) => {
+                            Some((name.0.last()?, &assignment.value))
+                        }
+                        AssignmentTarget::Tuple(_) => None,
+                    })
+                    .unzip(),
+                MergeAction::Insert(insert) => {
+                    let values = match &insert.kind {
+                        MergeInsertKind::Values(Values { rows, .. }) => {
+                            for row in rows.iter().filter(|row| {
+                                !insert.columns.is_empty() && row.len() != insert.columns.len()
+                            }) {
+                                self.diagnostics.push(
+                                    Diagnostic::error(
+                                        DiagnosticKind::ColumnCountMismatch,
+                                        format!(
+                                            "INSERT has {} value(s) but {} column(s) were specified",
+                                            row.len(),
+                                            insert.columns.len()
+                                        ),
+                                    )
+                                    .with_span(Span::from_sqlparser(&insert.columns[0].span))
+                                    .with_help(format!(
+                                        "Provide {} value(s) to match the column list",
+                                        insert.columns.len()
+                                    )),
+                                );
+                            }
+                            rows.iter().flatten().collect()
+                        }
+                        MergeInsertKind::Row => Vec::new(),
+                    };
+                    (insert.columns.iter().collect(), values)
+                }
+                MergeAction::Delete => continue,
+            };
+
+            if let (Some(def), Some(name)) = (table_def, &table_name) {
+                for col_ident in columns.into_iter().filter(|c| !def.column_exists(&c.value)) {
+                    self.diagnostics
+                        .push(column_not_found_in_table(def, name, col_ident));
+                }
+            }
+            for value in values.into_iter().filter(|v| !is_default_keyword(v)) {
+                self.resolve_expr(value);
+            }
+        }
+    }
+
+    /// Check that every table named in a TRUNCATE exists
+    fn resolve_truncate(&mut self, targets: &[TruncateTableTarget]) {
+        for target in targets {
+            let table_name = self.dialect.qualified_name(&target.name);
+            self.check_ambiguous_table(&target.name, &table_name);
+            if self.catalog.get_table(&table_name).is_none() {
+                let diag = table_not_found(self.catalog, &target.name, &table_name, false);
+                self.diagnostics.push(diag);
+            }
+        }
+    }
+
     /// Resolve names in an INSERT statement
     fn resolve_insert(&mut self, insert: &Insert) {
-        let table_name = object_name_to_qualified(&insert.table_name);
+        let table_name = self.dialect.qualified_name(&insert.table_name);
+        self.check_ambiguous_table(&insert.table_name, &table_name);
+
+        // `INSERT OR REPLACE` / `INSERT OR IGNORE` etc. only change what happens
+        // on a conflict, so the rest of the INSERT is checked as usual
+        if let Some(action) = &insert.or {
+            if self.dialect != SqlDialect::SQLite {
+                let span = insert
+                    .table_name
+                    .0
+                    .first()
+                    .map(|id| Span::from_sqlparser(&id.span));
+                let mut diag = Diagnostic::error(
+                    DiagnosticKind::UnsupportedSyntax,
+                    format!("INSERT {} is only supported by SQLite", action),
+                )
+                .with_help(match self.dialect {
+                    SqlDialect::MySQL => "Use INSERT IGNORE or REPLACE INTO instead",
+                    _ => "Use INSERT ... ON CONFLICT instead",
+                });
+                if let Some(span) = span {
+                    diag = diag.with_span(span);
+                }
+                self.diagnostics.push(diag);
+            }
+        }
 
         // Check if table exists
         let table_def = if let Some(def) = self.catalog.get_table(&table_name) {
             def
         } else {
-            let table_span = insert
-                .table_name
-                .0
-                .last()
-                .map(|id| Span::from_sqlparser(&id.span));
-            let mut diag = Diagnostic::error(
-                DiagnosticKind::TableNotFound,
-                format!("Table '{}' not found", table_name),
-            )
-            .with_help("Check that the table exists in your schema definition");
-            if let Some(span) = table_span {
-                diag = diag.with_span(span);
-            }
+            let diag = table_not_found(self.catalog, &insert.table_name, &table_name, false);
             self.diagnostics.push(diag);
             return;
         };
 
         // Check if specified columns exist
         let specified_columns: Vec<&Ident> = insert.columns.iter().collect();
-        for col_ident in &specified_columns {
-            if !table_def.column_exists(&col_ident.value) {
-                let similar = find_similar_column(table_def, &col_ident.value);
-                let mut diag = Diagnostic::error(
-                    DiagnosticKind::ColumnNotFound,
-                    format!(
-                        "Column '{}' not found in table '{}'",
-                        col_ident.value, table_name
-                    ),
-                )
-                .with_span(Span::from_sqlparser(&col_ident.span));
-                if let Some(suggestion) = similar {
-                    diag = diag.with_help(format!("Did you mean '{}'?", suggestion));
+        let value_rows = insert
+            .source
+            .as_ref()
+            .and_then(|source| match source.body.as_ref() {
+                SetExpr::Values(Values { rows, .. }) => Some(rows),
+                _ => None,
+            });
+        for (position, col_ident) in specified_columns.iter().enumerate() {
+            if let Some(col) = table_def.get_column(&col_ident.value) {
+                // DEFAULT is always valid in a generated column's position
+                let only_defaults = value_rows.is_some_and(|rows| {
+                    rows.iter()
+                        .all(|row| row.get(position).map_or(true, is_default_keyword))
+                });
+                if col.is_generated && !only_defaults {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            DiagnosticKind::GeneratedColumnInsert,
+                            format!(
+                                "Cannot insert into generated column '{}' of table '{}'",
+                                col_ident.value, table_name
+                            ),
+                        )
+                        .with_span(Span::from_sqlparser(&col_ident.span))
+                        .with_help(
+                            "Generated columns are computed from other columns; remove it from the column list",
+                        ),
+                    );
                 }
-                self.diagnostics.push(diag);
+            } else {
+                self.diagnostics
+                    .push(column_not_found_in_table(table_def, &table_name, col_ident));
             }
         }
 
         // Check column count vs value count
         if let Some(source) = &insert.source {
+            let expected_count = if specified_columns.is_empty() {
+                table_def.columns.len()
+            } else {
+                specified_columns.len()
+            };
+            // PostgreSQL fills trailing columns of a column-less INSERT with defaults
+            let fills_trailing =
+                specified_columns.is_empty() && self.dialect == SqlDialect::PostgreSQL;
             if let SetExpr::Values(Values { rows, .. }) = source.body.as_ref() {
-                let expected_count = if specified_columns.is_empty() {
-                    table_def.columns.len()
-                } else {
-                    specified_columns.len()
-                };
-
                 for row in rows {
-                    if row.len() != expected_count {
+                    if fills_trailing && row.len() < expected_count {
+                        self.check_omitted_columns(insert, table_def, row.len());
+                    } else if row.len() != expected_count {
                         self.diagnostics.push(
                             Diagnostic::error(
                                 DiagnosticKind::ColumnCountMismatch,
@@ -172,17 +379,321 @@ impl<'a> NameResolver<'a> {
                     }
 
                     // Resolve expressions in values (for subqueries, etc.)
-                    for expr in row {
+                    for expr in row.iter().filter(|e| !is_default_keyword(e)) {
                         self.resolve_expr(expr);
                     }
                 }
             } else {
                 // INSERT ... SELECT - resolve the subquery
                 self.resolve_set_expr(&source.body);
+
+                // Compare widths while the SELECT's FROM scope is still active
+                let width = match source.body.as_ref() {
+                    SetExpr::Select(select) => self.projection_width(&select.projection),
+                    _ => None,
+                };
+                if let Some(width) = width.filter(|w| fills_trailing && *w < expected_count) {
+                    self.check_omitted_columns(insert, table_def, width);
+                } else if let Some(width) = width.filter(|w| *w != expected_count) {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            DiagnosticKind::ColumnCountMismatch,
+                            format!(
+                                "INSERT has {} expression(s) but {} column(s) were specified",
+                                width, expected_count
+                            ),
+                        )
+                        .with_help(format!(
+                            "The SELECT list must produce {} column(s) to match the target",
+                            expected_count
+                        )),
+                    );
+                }
             }
+        } else if specified_columns.is_empty() && self.dialect == SqlDialect::PostgreSQL {
+            // DEFAULT VALUES: every column gets its default. SQLite also fills
+            // rowid aliases, which the catalog doesn't mark, so it's skipped
+            self.check_omitted_columns(insert, table_def, 0);
+        }
+
+        self.check_identity_override(insert, table_def);
+
+        if let Some(OnInsert::OnConflict(on_conflict)) = &insert.on {
+            self.resolve_on_conflict(on_conflict, insert, &table_name, table_def);
         }
     }
 
+    /// E0018: a value for a GENERATED ALWAYS identity column
+    ///
+    /// PostgreSQL rejects these unless the INSERT says `OVERRIDING SYSTEM VALUE`;
+    /// with `OVERRIDING USER VALUE` the value is accepted but thrown away.
+    /// `DEFAULT` is always fine. A column-less `INSERT ... SELECT` isn't checked.
+    fn check_identity_override(&mut self, insert: &Insert, table: &TableDef) {
+        let Some(source) = &insert.source else {
+            return;
+        };
+        let overriding = insert
+            .table_name
+            .0
+            .first()
+            .and_then(|id| self.overriding.get(&id.span.start))
+            .copied();
+        if overriding == Some(Overriding::System) {
+            return;
+        }
+
+        // Target columns by position, with the identifier naming each one
+        let targets: Vec<(Option<&ColumnDef>, Option<&Ident>)> = if insert.columns.is_empty() {
+            table
+                .columns
+                .values()
+                .map(|col| (Some(col), None))
+                .collect()
+        } else {
+            insert
+                .columns
+                .iter()
+                .map(|id| (table.get_column(&id.value), Some(id)))
+                .collect()
+        };
+        let rows = match source.body.as_ref() {
+            SetExpr::Values(Values { rows, .. }) => Some(rows),
+            _ => None,
+        };
+
+        for (position, (column, ident)) in targets.into_iter().enumerate() {
+            let Some(column) = column.filter(|c| matches!(c.identity, Some(IdentityKind::Always)))
+            else {
+                continue;
+            };
+            let supplied = match rows {
+                Some(rows) => rows
+                    .iter()
+                    .any(|row| row.get(position).is_some_and(|e| !is_default_keyword(e))),
+                None => ident.is_some(),
+            };
+            if !supplied {
+                continue;
+            }
+
+            let help = match overriding {
+                Some(_) => "OVERRIDING USER VALUE discards the value and generates one; use OVERRIDING SYSTEM VALUE to keep it",
+                None => "Leave the column out or use DEFAULT, or add OVERRIDING SYSTEM VALUE to keep the value",
+            };
+            let span = ident
+                .or_else(|| insert.table_name.0.last())
+                .map(|id| Span::from_sqlparser(&id.span));
+            let mut diag = Diagnostic::error(
+                DiagnosticKind::GeneratedAlwaysOverride,
+                format!(
+                    "Cannot insert a value into identity column '{}' of table '{}', which is GENERATED ALWAYS",
+                    column.name, table.name
+                ),
+            )
+            .with_help(help);
+            if let Some(span) = span {
+                diag = diag.with_span(span);
+            }
+            self.diagnostics.push(diag);
+        }
+    }
+
+    /// W1007: NOT NULL columns without a default left out of a column-less INSERT
+    ///
+    /// `supplied` is the number of values given (none for `DEFAULT VALUES`); the
+    /// columns after them get their defaults, which for these columns means NULL.
+    fn check_omitted_columns(&mut self, insert: &Insert, table: &TableDef, supplied: usize) {
+        let span = insert
+            .table_name
+            .0
+            .last()
+            .map(|id| Span::from_sqlparser(&id.span));
+        let required: Vec<&ColumnDef> = table
+            .columns
+            .values()
+            .skip(supplied)
+            .filter(|column| column.requires_value())
+            .collect();
+        let help = if insert.source.is_none() {
+            let names: Vec<&str> = required.iter().map(|c| c.name.as_str()).collect();
+            format!(
+                "DEFAULT VALUES can't fill it; use a VALUES list that supplies {}",
+                names.join(", ")
+            )
+        } else {
+            "Supply a value for it, or list the target columns explicitly".to_string()
+        };
+        for column in required {
+            let mut diag = Diagnostic::warning(
+                DiagnosticKind::MissingRequiredColumn,
+                format!(
+                    "INSERT omits NOT NULL column '{}' of table '{}', which has no default",
+                    column.name, table.name
+                ),
+            )
+            .with_help(help.clone());
+            if let Some(span) = span {
+                diag = diag.with_span(span);
+            }
+            self.diagnostics.push(diag);
+        }
+    }
+
+    /// E0019: `ON CONFLICT ON CONSTRAINT name` must name a primary key or
+    /// unique constraint of the target table, or an exclusion constraint
+    /// with `DO NOTHING`
+    fn check_conflict_constraint(
+        &mut self,
+        name: &ObjectName,
+        action: &OnConflictAction,
+        table_name: &QualifiedName,
+        table_def: &TableDef,
+    ) {
+        let Some(ident) = name.0.last() else {
+            return;
+        };
+        let constraints = table_def.unique_constraint_names();
+        if constraints
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&ident.value))
+        {
+            return;
+        }
+        if table_def
+            .exclusion_constraints
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&ident.value))
+        {
+            if let OnConflictAction::DoUpdate(_) = action {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticKind::NoMatchingUniqueConstraint,
+                        format!(
+                            "Constraint '{}' of table '{}' is an exclusion constraint, which ON CONFLICT DO UPDATE doesn't support",
+                            ident.value, table_name
+                        ),
+                    )
+                    .with_span(Span::from_sqlparser(&ident.span))
+                    .with_help("Use DO NOTHING, or name a unique constraint instead"),
+                );
+            }
+            return;
+        }
+        let diag = Diagnostic::error(
+            DiagnosticKind::NoMatchingUniqueConstraint,
+            format!(
+                "Table '{}' has no unique or primary key constraint named '{}'",
+                table_name, ident.value
+            ),
+        )
+        .with_span(Span::from_sqlparser(&ident.span));
+        let closest = constraints
+            .iter()
+            .map(|c| {
+                (
+                    levenshtein_distance(&ident.value.to_lowercase(), &c.to_lowercase()),
+                    c,
+                )
+            })
+            .filter(|(distance, _)| *distance <= 3)
+            .min_by_key(|(distance, _)| *distance);
+        self.diagnostics.push(match closest {
+            Some((_, suggestion)) => with_typo_fix(diag, ident, suggestion),
+            None if constraints.is_empty() => diag.with_help(
+                "The table has no unique constraints; ON CONFLICT needs one to detect conflicts",
+            ),
+            None => diag.with_help(format!("Available constraints: {}", constraints.join(", "))),
+        });
+    }
+
+    /// Resolve `ON CONFLICT` against the INSERT target table
+    ///
+    /// The conflict target must name target columns. `DO UPDATE SET ... WHERE ...`
+    /// sees the target (by name or alias) plus the `excluded` pseudo-table.
+    fn resolve_on_conflict(
+        &mut self,
+        on_conflict: &OnConflict,
+        insert: &Insert,
+        table_name: &QualifiedName,
+        table_def: &TableDef,
+    ) {
+        let saved_tables = std::mem::take(&mut self.tables);
+        let target_key = insert
+            .table_alias
+            .as_ref()
+            .map_or_else(|| written_name(&insert.table_name), |a| a.value.clone());
+
+        match &on_conflict.conflict_target {
+            Some(ConflictTarget::Columns(columns)) => {
+                for col_ident in columns {
+                    if !table_def.column_exists(&col_ident.value) {
+                        self.diagnostics
+                            .push(column_not_found_in_table(table_def, table_name, col_ident));
+                    }
+                }
+                // The index predicate sees only the target table
+                let predicate = insert
+                    .table_name
+                    .0
+                    .first()
+                    .and_then(|id| self.conflict_predicates.get(&id.span.start))
+                    .cloned();
+                if let Some(predicate) = predicate {
+                    self.tables.insert(
+                        target_key.clone(),
+                        TableRef {
+                            table: table_name.clone(),
+                            alias: Some(target_key.clone()),
+                            view_columns: None,
+                            derived_columns: None,
+                            column_origins: HashMap::new(),
+                        },
+                    );
+                    self.resolve_expr(&predicate);
+                }
+            }
+            Some(ConflictTarget::OnConstraint(name)) => {
+                self.check_conflict_constraint(name, &on_conflict.action, table_name, table_def);
+            }
+            None => {}
+        }
+
+        let OnConflictAction::DoUpdate(do_update) = &on_conflict.action else {
+            self.tables = saved_tables;
+            return;
+        };
+
+        for key in [target_key, "excluded".to_string()] {
+            self.tables.insert(
+                key.clone(),
+                TableRef {
+                    table: table_name.clone(),
+                    alias: Some(key),
+                    view_columns: None,
+                    derived_columns: None,
+                    column_origins: HashMap::new(),
+                },
+            );
+        }
+
+        for assignment in &do_update.assignments {
+            if let AssignmentTarget::ColumnName(col_name) = &assignment.target {
+                if let Some(col_ident) = col_name.0.last() {
+                    if !table_def.column_exists(&col_ident.value) {
+                        self.diagnostics
+                            .push(column_not_found_in_table(table_def, table_name, col_ident));
+                    }
+                }
+            }
+            self.resolve_expr(&assignment.value);
+        }
+        if let Some(selection) = &do_update.selection {
+            self.resolve_expr(selection);
+        }
+
+        self.tables = saved_tables;
+    }
+
     /// Resolve names in an UPDATE statement
     fn resolve_update(
         &mut self,
@@ -200,7 +711,10 @@ impl<'a> NameResolver<'a> {
         }
 
         // Get table definition for column validation
-        let table_name = table_with_joins_to_name(&table.relation);
+        let table_name = match &table.relation {
+            TableFactor::Table { name, .. } => Some(self.dialect.qualified_name(name)),
+            _ => None,
+        };
         let table_def = table_name.as_ref().and_then(|n| self.catalog.get_table(n));
 
         // Resolve SET clause columns
@@ -225,8 +739,7 @@ impl<'a> NameResolver<'a> {
                                 )
                                 .with_span(Span::from_sqlparser(&col_ident.span));
                                 if let Some(suggestion) = similar {
-                                    diag =
-                                        diag.with_help(format!("Did you mean '{}'?", suggestion));
+                                    diag = with_typo_fix(diag, col_ident, &suggestion);
                                 }
                                 self.diagnostics.push(diag);
                             }
@@ -239,13 +752,67 @@ impl<'a> NameResolver<'a> {
             }
 
             // Resolve the value expression
-            self.resolve_expr(&assignment.value);
+            if !is_default_keyword(&assignment.value) {
+                self.resolve_expr(&assignment.value);
+            }
+            self.check_self_assignment(table, assignment);
         }
 
         // Resolve WHERE clause
         if let Some(where_expr) = selection {
             self.resolve_expr(where_expr);
+            let nullable_keys = outer_join_nullable_keys(std::iter::once(table).chain(from));
+            self.check_null_predicates(where_expr, &nullable_keys);
+            self.check_unindexed_filters(where_expr);
+            self.check_multi_row_subqueries(where_expr);
+        } else {
+            self.report_unbounded_mutation("UPDATE", table);
+        }
+    }
+
+    /// W1012: `SET col = col`, which leaves the column unchanged
+    ///
+    /// The value must be the bare column itself, optionally qualified; both
+    /// sides must resolve to the same column of the same table reference, so
+    /// a self-join's `SET total = o2.total` isn't a no-op.
+    fn check_self_assignment(&mut self, table: &TableWithJoins, assignment: &Assignment) {
+        let AssignmentTarget::ColumnName(target) = &assignment.target else {
+            return;
+        };
+        let Some(target_ident) = target.0.last() else {
+            return;
+        };
+        let (qualifier, value_ident) = match &assignment.value {
+            Expr::Identifier(ident) => (None, ident),
+            Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+                [.., qualifier, column] => (Some(qualifier), column),
+                _ => return,
+            },
+            _ => return,
+        };
+        let Some((key, _)) = table_factor_scope_key(&table.relation) else {
+            return;
+        };
+        if self.column_scope(qualifier, &value_ident.value) != Some(key.as_str()) {
+            return;
         }
+        let target_origin = self.lookup_column_origin(Some(&Ident::new(key)), &target_ident.value);
+        if target_origin.is_none()
+            || target_origin != self.lookup_column_origin(qualifier, &value_ident.value)
+        {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::info(
+                DiagnosticKind::SelfAssignment,
+                format!(
+                    "Assignment '{} = {}' leaves the column unchanged",
+                    target, assignment.value
+                ),
+            )
+            .with_span(Span::from_sqlparser(&target_ident.span))
+            .with_help("Remove the assignment, or set the column to a new value"),
+        );
     }
 
     /// Resolve names in a DELETE statement
@@ -271,9 +838,39 @@ impl<'a> NameResolver<'a> {
         // Resolve WHERE clause
         if let Some(where_expr) = &delete.selection {
             self.resolve_expr(where_expr);
+            let using_tables = delete.using.iter().flatten();
+            let nullable_keys = outer_join_nullable_keys(tables.iter().chain(using_tables));
+            self.check_null_predicates(where_expr, &nullable_keys);
+            self.check_unindexed_filters(where_expr);
+            self.check_multi_row_subqueries(where_expr);
+        } else if delete.limit.is_none() {
+            if let Some(table) = tables.first() {
+                self.report_unbounded_mutation("DELETE", table);
+            }
         }
     }
 
+    /// E0016: an UPDATE or DELETE with no WHERE clause
+    ///
+    /// Any WHERE, even `WHERE true`, counts as deliberate. Tables that didn't
+    /// resolve have been reported already.
+    fn report_unbounded_mutation(&mut self, verb: &str, table: &TableWithJoins) {
+        let Some((key, span)) = table_factor_scope_key(&table.relation) else {
+            return;
+        };
+        if !self.tables.contains_key(&key) {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::warning(
+                DiagnosticKind::UnboundedMutation,
+                format!("{} without WHERE affects every row of '{}'", verb, key),
+            )
+            .with_span(span)
+            .with_help("Add a WHERE clause, or write WHERE true if every row is meant to change"),
+        );
+    }
+
     /// Resolve names in a query
     fn resolve_query(&mut self, query: &Query) {
         // Handle CTEs (WITH clause)
@@ -285,12 +882,15 @@ impl<'a> NameResolver<'a> {
 
                 // For recursive CTEs, infer columns and register the CTE *before*
                 // resolving the body, so the recursive part can reference itself.
+                // The first (non-recursive) branch defines the column set.
                 let columns = if !cte.alias.columns.is_empty() {
                     cte.alias
                         .columns
                         .iter()
                         .map(|c| c.name.value.clone())
                         .collect()
+                } else if projects_wildcard(&cte.query.body) {
+                    Vec::new()
                 } else {
                     self.infer_cte_columns(&cte.query.body)
                 };
@@ -302,6 +902,7 @@ impl<'a> NameResolver<'a> {
                         CteDefinition {
                             name: cte_name.clone(),
                             columns: columns.clone(),
+                            column_origins: HashMap::new(),
                         },
                     );
                 }
@@ -310,7 +911,18 @@ impl<'a> NameResolver<'a> {
                 let saved_tables = self.tables.clone();
 
                 // Resolve the CTE query (to validate it) in isolated scope
+                let uses_start = self.table_uses.len();
+                let joins_start = self.join_ons.len();
                 self.resolve_set_expr(&cte.query.body);
+                self.check_unused_joins(&cte.query.body, uses_start, joins_start);
+
+                // Trace output columns back to their source while the CTE's scope is active
+                let aliases = if cte.alias.columns.is_empty() {
+                    &[][..]
+                } else {
+                    &columns[..]
+                };
+                let column_origins = self.projection_origins(&cte.query.body, aliases);
 
                 // Restore table scope (CTEs shouldn't pollute outer scope with their internal tables)
                 self.tables = saved_tables;
@@ -321,23 +933,158 @@ impl<'a> NameResolver<'a> {
                     CteDefinition {
                         name: cte_name,
                         columns,
+                        column_origins,
                     },
                 );
             }
         }
 
         // Resolve the main query body
+        let uses_start = self.table_uses.len();
+        let joins_start = self.join_ons.len();
         self.resolve_set_expr(&query.body);
 
         // Resolve ORDER BY clause (with SELECT aliases in scope)
         if let Some(order_by) = &query.order_by {
-            // Collect SELECT aliases so ORDER BY can reference them
-            let saved_aliases = std::mem::take(&mut self.select_aliases);
-            self.select_aliases = self.collect_select_aliases(&query.body);
+            // Output columns, unless a `*` makes them unknown
+            let output = Some(self.infer_cte_columns(&query.body))
+                .filter(|columns| !columns.is_empty() && !projects_wildcard(&query.body));
+            // The ORDER BY clause has no span of its own, so positions fall
+            // back to the query
+            let fallback = Span::from_sqlparser(&query.span());
             for ob in &order_by.exprs {
-                self.resolve_expr(&ob.expr);
+                self.check_order_by_position(&ob.expr, output.as_deref(), fallback);
+            }
+
+            if matches!(query.body.as_ref(), SetExpr::SetOperation { .. }) {
+                self.check_set_operation_order_by(&order_by.exprs, output.as_deref());
+            } else {
+                // Collect SELECT aliases so ORDER BY can reference them
+                let saved_aliases = std::mem::take(&mut self.select_aliases);
+                self.select_aliases = self.collect_select_aliases(&query.body);
+                for ob in &order_by.exprs {
+                    self.resolve_expr(&ob.expr);
+                }
+                self.select_aliases = saved_aliases;
+            }
+        }
+
+        // FOR UPDATE / FOR SHARE ... OF must name FROM items of this query
+        for lock in &query.locks {
+            if let Some(name) = &lock.of {
+                self.check_lock_target(name);
+            }
+        }
+
+        self.check_unused_joins(&query.body, uses_start, joins_start);
+    }
+
+    /// E0017: `ORDER BY n` must name one of the `output` columns
+    fn check_order_by_position(&mut self, expr: &Expr, output: Option<&[String]>, fallback: Span) {
+        let (Expr::Value(Value::Number(n, _)), Some(output)) = (expr, output) else {
+            return;
+        };
+        let Ok(position) = n.parse::<usize>() else {
+            return;
+        };
+        if (1..=output.len()).contains(&position) {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::error(
+                DiagnosticKind::InvalidOrderByPosition,
+                format!("ORDER BY position {} is not in select list", position),
+            )
+            .with_span(span_or(expr, fallback))
+            .with_help(format!(
+                "The select list has {} column(s); positions start at 1",
+                output.len()
+            )),
+        );
+    }
+
+    /// The ORDER BY of a set operation sorts its result, so names refer to the
+    /// output columns (named by the first branch), not to any branch's tables
+    ///
+    /// Expressions other than a bare name or position aren't checked.
+    fn check_set_operation_order_by(&mut self, exprs: &[OrderByExpr], output: Option<&[String]>) {
+        // Unnamed expressions get a dialect-specific name
+        let Some(output) = output.filter(|o| !o.iter().any(|c| c.starts_with("?column?"))) else {
+            return;
+        };
+        for ob in exprs {
+            let Expr::Identifier(ident) = &ob.expr else {
+                continue;
+            };
+            if output.iter().any(|c| c.eq_ignore_ascii_case(&ident.value)) {
+                continue;
+            }
+            self.diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticKind::ColumnNotFound,
+                    format!(
+                        "Column '{}' not found in the result of the set operation",
+                        ident.value
+                    ),
+                )
+                .with_span(Span::from_sqlparser(&ident.span))
+                .with_help(format!(
+                    "ORDER BY after UNION / INTERSECT / EXCEPT can only use the result columns: {}",
+                    output.join(", ")
+                )),
+            );
+        }
+    }
+
+    /// Check that a locking clause's `OF` target is a table or alias in scope
+    fn check_lock_target(&mut self, name: &ObjectName) {
+        let key = written_name(name);
+        if self.tables.contains_key(&key) {
+            return;
+        }
+        let mut diag = Diagnostic::error(
+            DiagnosticKind::TableNotFound,
+            format!("Table or alias '{}' not found in FROM clause", key),
+        )
+        .with_help("A locking clause can only lock tables of the query's FROM clause, by alias if they have one");
+        if let Some(ident) = name.0.last() {
+            diag = diag.with_span(Span::from_sqlparser(&ident.span));
+        }
+        self.diagnostics.push(diag);
+    }
+
+    /// W1001: report joined tables whose columns are only used in their own ON
+    ///
+    /// `uses_start`/`joins_start` mark where this query's entries begin in
+    /// `table_uses`/`join_ons`. Nested queries have already consumed their own
+    /// joins, so the remaining ones belong to this query's FROM clause.
+    fn check_unused_joins(&mut self, body: &SetExpr, uses_start: usize, joins_start: usize) {
+        let joins = self.join_ons.split_off(joins_start);
+        // Set operations share one scope map, so aliases can't be told apart
+        if !matches!(body, SetExpr::Select(_)) {
+            return;
+        }
+
+        for join in joins {
+            let used_elsewhere = self.table_uses[uses_start..]
+                .iter()
+                .enumerate()
+                .any(|(idx, key)| *key == join.key && !join.on_uses.contains(&(uses_start + idx)));
+            if !used_elsewhere {
+                self.diagnostics.push(
+                    Diagnostic::info(
+                        DiagnosticKind::UnusedJoinTable,
+                        format!(
+                            "Joined table '{}' is only referenced in its own JOIN condition",
+                            join.key
+                        ),
+                    )
+                    .with_span(join.span)
+                    .with_help(
+                        "Remove the join if it is not needed, or express it as EXISTS (...)",
+                    ),
+                );
             }
-            self.select_aliases = saved_aliases;
         }
     }
 
@@ -368,6 +1115,13 @@ impl<'a> NameResolver<'a> {
             return self.infer_cte_columns(left);
         }
 
+        // VALUES lists have no column names; PostgreSQL and SQLite name
+        // them column1, column2, ...
+        if let SetExpr::Values(values) = set_expr {
+            let width = values.rows.first().map_or(0, Vec::len);
+            return (1..=width).map(|i| format!("column{}", i)).collect();
+        }
+
         let select_items: Option<&[SelectItem]> = match set_expr {
             SetExpr::Select(select) => Some(&select.projection),
             SetExpr::Insert(Statement::Insert(Insert { returning, .. })) => returning.as_deref(),
@@ -415,15 +1169,75 @@ impl<'a> NameResolver<'a> {
             SetExpr::Select(select) => self.resolve_select(select),
             SetExpr::Query(query) => self.resolve_query(query),
             SetExpr::SetOperation { left, right, .. } => {
+                // Each branch has its own FROM clause
+                let enclosing = self.tables.clone();
                 self.resolve_set_expr(left);
+                let left_tables = std::mem::replace(&mut self.tables, enclosing);
                 self.resolve_set_expr(right);
+
+                // Later phases see the tables of every branch
+                for (key, table_ref) in left_tables {
+                    self.tables.entry(key).or_insert(table_ref);
+                }
             }
             SetExpr::Insert(stmt) => self.resolve_statement(stmt),
             SetExpr::Update(stmt) => self.resolve_statement(stmt),
+            SetExpr::Values(values) => {
+                for row in &values.rows {
+                    for expr in row {
+                        self.resolve_expr(expr);
+                    }
+                }
+            }
             _ => {}
         }
     }
 
+    /// Check that a VALUES table source has uniform rows and no more column
+    /// aliases than values per row
+    fn check_values_arity(&mut self, values: &Values, alias: Option<&TableAlias>) {
+        let Some(first) = values.rows.first() else {
+            return;
+        };
+        let width = first.len();
+
+        if let Some(row) = values.rows.iter().find(|row| row.len() != width) {
+            let mut diag = Diagnostic::error(
+                DiagnosticKind::ColumnCountMismatch,
+                format!(
+                    "VALUES lists must all be the same length: expected {} value(s) but found {}",
+                    width,
+                    row.len()
+                ),
+            );
+            if let Some(alias) = alias {
+                diag = diag.with_span(Span::from_sqlparser(&alias.name.span));
+            }
+            self.diagnostics.push(diag);
+        }
+
+        if let Some(alias) = alias {
+            if alias.columns.len() > width {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        DiagnosticKind::ColumnCountMismatch,
+                        format!(
+                            "Table '{}' has {} column(s) available but {} column alias(es) specified",
+                            alias.name.value,
+                            width,
+                            alias.columns.len()
+                        ),
+                    )
+                    .with_span(Span::from_sqlparser(&alias.name.span))
+                    .with_help(format!(
+                        "Each VALUES row provides {} value(s); remove the extra column aliases",
+                        width
+                    )),
+                );
+            }
+        }
+    }
+
     /// Resolve names in a SELECT statement
     fn resolve_select(&mut self, select: &Select) {
         // First, resolve FROM clause to build table scope
@@ -434,13 +1248,23 @@ impl<'a> NameResolver<'a> {
         // Then resolve SELECT items
         let select_span = Span::from_sqlparser(&select.select_token.0.span);
         for item in &select.projection {
-            self.resolve_select_item(item, &select_span);
+            self.resolve_select_item(item, &select_span, !select.from.is_empty());
         }
 
-        // Resolve WHERE clause
+        // Resolve WHERE clause, one conjunct at a time to see which tables each links
+        let mut conjunct_uses = Vec::new();
         if let Some(selection) = &select.selection {
-            self.resolve_expr(selection);
+            for conjunct in conjuncts(selection) {
+                let uses_start = self.table_uses.len();
+                self.resolve_expr(conjunct);
+                conjunct_uses.push(uses_start..self.table_uses.len());
+            }
+            let nullable_keys = outer_join_nullable_keys(&select.from);
+            self.check_null_predicates(selection, &nullable_keys);
+            self.check_unindexed_filters(selection);
+            self.check_multi_row_subqueries(selection);
         }
+        self.check_cross_joins(&select.from, &conjunct_uses);
 
         // Resolve GROUP BY
         match &select.group_by {
@@ -449,6 +1273,10 @@ impl<'a> NameResolver<'a> {
                 for expr in exprs {
                     self.resolve_expr(expr);
                 }
+                if !exprs.is_empty() {
+                    self.check_grouped_aggregates(select, exprs);
+                    self.check_ungrouped_columns(select, exprs);
+                }
             }
         }
 
@@ -458,6 +1286,196 @@ impl<'a> NameResolver<'a> {
         }
     }
 
+    /// E0020: aggregate or window calls in GROUP BY, including through an
+    /// ordinal or output alias (`GROUP BY 2` where the second item is `COUNT(*)`)
+    ///
+    /// Aggregates inside a subquery belong to that subquery and are allowed.
+    fn check_grouped_aggregates(&mut self, select: &Select, group_by: &[Expr]) {
+        let mut finder = AggregateFinder::default();
+        for expr in self.grouping_exprs(select, group_by) {
+            let _ = sqlparser::ast::Visit::visit(expr, &mut finder);
+        }
+        for (kind, name) in finder.found {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticKind::AggregateInGroupBy,
+                    format!("{} function '{}' is not allowed in GROUP BY", kind, name.value),
+                )
+                .with_span(Span::from_sqlparser(&name.span))
+                .with_help(
+                    "Group by the columns the aggregate is computed from, and filter on aggregates with HAVING",
+                ),
+            );
+        }
+    }
+
+    /// E0010: SELECT-list columns that are neither grouped nor aggregated
+    ///
+    /// Select items are compared with the GROUP BY expressions structurally:
+    /// `GROUP BY date_trunc('day', created_at)` groups that whole expression,
+    /// not a bare `created_at`. Grouping by a table's primary key covers the
+    /// table's other columns. SQLite allows bare columns.
+    fn check_ungrouped_columns(&mut self, select: &Select, group_by: &[Expr]) {
+        if self.dialect == SqlDialect::SQLite {
+            return;
+        }
+
+        let grouped: Vec<String> = self
+            .grouping_exprs(select, group_by)
+            .into_iter()
+            .map(|expr| self.canonical_expr(expr))
+            .collect();
+
+        let mut ungrouped = Vec::new();
+        for item in &select.projection {
+            let (SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. }) = item
+            else {
+                continue;
+            };
+            let mut remaining = expr.clone();
+            let _ = remaining.visit(&mut GroupedPruner {
+                resolver: self,
+                grouped: &grouped,
+            });
+            let _ = visit_expressions(&remaining, |e| {
+                if let Some((table, column)) = column_reference(e) {
+                    if let Some(key) = self.local_column_key(table, column) {
+                        ungrouped.push((key, column.clone()));
+                    }
+                }
+                ControlFlow::<()>::Continue(())
+            });
+        }
+
+        for (key, column) in ungrouped {
+            if self.grouped_by_primary_key(&key, &grouped) {
+                continue;
+            }
+            self.diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticKind::UngroupedColumn,
+                    format!(
+                        "Column '{}' must appear in GROUP BY or be used in an aggregate function",
+                        column.value
+                    ),
+                )
+                .with_span(Span::from_sqlparser(&column.span))
+                .with_help("Add it to GROUP BY, or wrap it in an aggregate such as MAX(...)"),
+            );
+        }
+    }
+
+    /// GROUP BY expressions, with ordinals and output aliases replaced by the
+    /// select item they refer to
+    fn grouping_exprs<'q>(&self, select: &'q Select, group_by: &'q [Expr]) -> Vec<&'q Expr> {
+        let projected = |item: &'q SelectItem| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => Some(expr),
+            _ => None,
+        };
+
+        let mut exprs = Vec::new();
+        for expr in group_by {
+            match expr {
+                Expr::GroupingSets(sets) | Expr::Cube(sets) | Expr::Rollup(sets) => {
+                    exprs.extend(sets.iter().flatten());
+                }
+                Expr::Value(Value::Number(n, _)) => {
+                    let item = n
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| select.projection.get(n.checked_sub(1)?))
+                        .and_then(projected);
+                    exprs.extend(item);
+                }
+                // An input column takes precedence over an output alias
+                Expr::Identifier(name) if self.local_column_key(None, name).is_none() => {
+                    let aliased = select.projection.iter().find_map(|item| match item {
+                        SelectItem::ExprWithAlias { expr, alias }
+                            if alias.value.eq_ignore_ascii_case(&name.value) =>
+                        {
+                            Some(expr)
+                        }
+                        _ => None,
+                    });
+                    exprs.push(aliased.unwrap_or(expr));
+                }
+                _ => exprs.push(expr),
+            }
+        }
+        exprs
+    }
+
+    /// Text of an expression with column references written as `key.column`,
+    /// for comparing expressions regardless of qualification and case
+    fn canonical_expr(&self, expr: &Expr) -> String {
+        let mut expr = expr.clone();
+        let _ = visit_expressions_mut(&mut expr, |e| {
+            let canonical = column_reference(e).and_then(|(table, column)| {
+                let key = self.local_column_key(table, column)?;
+                Some(Expr::CompoundIdentifier(vec![
+                    Ident::new(key),
+                    Ident::new(&column.value),
+                ]))
+            });
+            if let Some(canonical) = canonical {
+                *e = canonical;
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        expr.to_string().to_lowercase()
+    }
+
+    /// Scope key of the current query's table a column reference belongs to
+    ///
+    /// `None` for outer references, unknown columns and ambiguous names.
+    fn local_column_key(&self, table: Option<&Ident>, column: &Ident) -> Option<String> {
+        if let Some(table) = table {
+            let table_ref = self.tables.get(&table.value)?;
+            return self
+                .table_ref_has_column(table_ref, &column.value)
+                .then(|| table.value.clone());
+        }
+        let mut matches = self
+            .tables
+            .iter()
+            .filter(|(_, t)| self.table_ref_has_column(t, &column.value));
+        let (key, _) = matches.next()?;
+        matches.next().is_none().then(|| key.clone())
+    }
+
+    /// Whether the GROUP BY includes every primary key column of a base table
+    fn grouped_by_primary_key(&self, key: &str, grouped: &[String]) -> bool {
+        let Some(table_ref) = self.tables.get(key) else {
+            return false;
+        };
+        if table_ref.derived_columns.is_some()
+            || table_ref.view_columns.is_some()
+            || self.ctes.contains_key(&table_ref.table.name)
+        {
+            return false;
+        }
+        let Some(table) = self.catalog.get_table(&table_ref.table) else {
+            return false;
+        };
+        let primary_key: Vec<&str> = match &table.primary_key {
+            Some(pk) => pk.columns.iter().map(String::as_str).collect(),
+            None => table
+                .columns
+                .values()
+                .filter(|c| c.is_primary_key)
+                .map(|c| c.name.as_str())
+                .collect(),
+        };
+        !primary_key.is_empty()
+            && primary_key.iter().all(|column| {
+                grouped.contains(
+                    &Expr::CompoundIdentifier(vec![Ident::new(key), Ident::new(*column)])
+                        .to_string()
+                        .to_lowercase(),
+                )
+            })
+    }
+
     /// Resolve a table reference in FROM clause
     fn resolve_table_with_joins(&mut self, table: &TableWithJoins) {
         self.resolve_table_factor(&table.relation);
@@ -465,7 +1483,18 @@ impl<'a> NameResolver<'a> {
         for join in &table.joins {
             self.resolve_table_factor(&join.relation);
             // Resolve join condition
+            let uses_start = self.table_uses.len();
             self.resolve_join_condition(&join.join_operator);
+
+            if join_has_on_condition(&join.join_operator) {
+                if let Some((key, span)) = table_factor_scope_key(&join.relation) {
+                    self.join_ons.push(JoinOn {
+                        key,
+                        span,
+                        on_uses: uses_start..self.table_uses.len(),
+                    });
+                }
+            }
         }
     }
 
@@ -480,20 +1509,383 @@ impl<'a> NameResolver<'a> {
             CrossJoin | CrossApply | OuterApply | AsOf { .. } | Anti(_) | Semi(_) => None,
         };
 
-        if let Some(constraint) = constraint {
-            match constraint {
-                JoinConstraint::On(expr) => {
-                    self.resolve_expr(expr);
-                }
-                JoinConstraint::Using(columns) => {
-                    // For USING clause, check that columns exist in both tables
-                    for col in columns {
-                        self.resolve_column(None, col);
-                    }
+        if let Some(constraint) = constraint {
+            match constraint {
+                JoinConstraint::On(expr) => {
+                    self.resolve_expr(expr);
+                }
+                JoinConstraint::Using(columns) => {
+                    // For USING clause, check that columns exist in both tables
+                    for col in columns {
+                        self.resolve_column(None, col);
+                    }
+                }
+                JoinConstraint::Natural | JoinConstraint::None => {}
+            }
+        }
+    }
+
+    /// W1006: `IS NULL` / `IS NOT NULL` on a NOT NULL column in a WHERE clause
+    ///
+    /// Only AND/OR/NOT combinations of the condition are inspected. Columns of
+    /// relations on the nullable side of an outer join (`nullable_keys`) can be
+    /// NULL regardless of their declaration and are skipped.
+    fn check_null_predicates(&mut self, expr: &Expr, nullable_keys: &[String]) {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And | BinaryOperator::Or,
+                right,
+            } => {
+                self.check_null_predicates(left, nullable_keys);
+                self.check_null_predicates(right, nullable_keys);
+            }
+            Expr::Nested(inner)
+            | Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr: inner,
+            } => self.check_null_predicates(inner, nullable_keys),
+            Expr::IsNull(inner) => self.check_null_test(inner, false, nullable_keys),
+            Expr::IsNotNull(inner) => self.check_null_test(inner, true, nullable_keys),
+            _ => {}
+        }
+    }
+
+    fn check_null_test(&mut self, expr: &Expr, negated: bool, nullable_keys: &[String]) {
+        let (table_ident, column_ident) = match expr {
+            Expr::Identifier(ident) => (None, ident),
+            Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+                [.., table, column] => (Some(table), column),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        // Only columns of base tables in the current FROM scope, unambiguously resolved
+        let column_name = &column_ident.value;
+        let key = match table_ident {
+            Some(table_id) => table_id.value.clone(),
+            None => {
+                let mut keys = self
+                    .tables
+                    .iter()
+                    .filter(|(_, t)| self.table_ref_has_column(t, column_name))
+                    .map(|(key, _)| key);
+                match (keys.next(), keys.next()) {
+                    (Some(key), None) => key.clone(),
+                    _ => return,
+                }
+            }
+        };
+        if nullable_keys.contains(&key) {
+            return;
+        }
+        let Some(table_ref) = self.tables.get(&key) else {
+            return;
+        };
+        if table_ref.derived_columns.is_some()
+            || table_ref.view_columns.is_some()
+            || self.ctes.contains_key(&table_ref.table.name)
+        {
+            return;
+        }
+        let Some(column) = self
+            .catalog
+            .get_table(&table_ref.table)
+            .and_then(|t| t.get_column(column_name))
+        else {
+            return;
+        };
+        if column.nullable {
+            return;
+        }
+
+        let (test, outcome) = if negated {
+            ("IS NOT NULL", "always true")
+        } else {
+            ("IS NULL", "never true")
+        };
+        self.diagnostics.push(
+            Diagnostic::warning(
+                DiagnosticKind::AlwaysFalsePredicate,
+                format!(
+                    "'{} {}' is {}: column '{}' is declared NOT NULL",
+                    expr, test, outcome, column.name
+                ),
+            )
+            .with_span(Span::from_sqlparser(&column_ident.span))
+            .with_help(format!(
+                "Column '{}' of table '{}' cannot hold NULL",
+                column.name, table_ref.table
+            )),
+        );
+    }
+
+    /// W1011: comma-separated FROM items that no WHERE predicate links
+    ///
+    /// `conjunct_uses` are the `table_uses` entries of each top-level AND term
+    /// of the WHERE clause; a term using tables of two FROM items links them.
+    /// Only plain tables are checked: derived tables and functions in FROM are
+    /// often single rows or reference the other items themselves.
+    fn check_cross_joins(&mut self, from: &[TableWithJoins], conjunct_uses: &[Range<usize>]) {
+        if from.len() < 2 {
+            return;
+        }
+        let items: Vec<Vec<String>> = from
+            .iter()
+            .map(|item| {
+                std::iter::once(&item.relation)
+                    .chain(item.joins.iter().map(|j| &j.relation))
+                    .filter_map(|f| table_factor_scope_key(f).map(|(key, _)| key))
+                    .collect()
+            })
+            .collect();
+
+        // Union-find over FROM items; anything but a plain table links to all
+        let mut parent: Vec<usize> = (0..from.len()).collect();
+        for (i, item) in from.iter().enumerate() {
+            if !matches!(item.relation, TableFactor::Table { args: None, .. }) {
+                for j in 0..from.len() {
+                    union(&mut parent, i, j);
+                }
+            }
+        }
+        for uses in conjunct_uses {
+            let linked: Vec<usize> = (0..items.len())
+                .filter(|&i| {
+                    self.table_uses[uses.clone()]
+                        .iter()
+                        .any(|k| items[i].contains(k))
+                })
+                .collect();
+            for pair in linked.windows(2) {
+                union(&mut parent, pair[0], pair[1]);
+            }
+        }
+
+        let mut reported = vec![find(&mut parent, 0)];
+        for (i, item) in from.iter().enumerate().skip(1) {
+            let component = find(&mut parent, i);
+            if reported.contains(&component) {
+                continue;
+            }
+            reported.push(component);
+            let (Some((key, span)), Some(first)) =
+                (table_factor_scope_key(&item.relation), items[0].first())
+            else {
+                continue;
+            };
+            self.diagnostics.push(
+                Diagnostic::warning(
+                    DiagnosticKind::UnintentionalCrossJoin,
+                    format!(
+                        "'{}' is cross joined with '{}': no WHERE condition links them",
+                        key, first
+                    ),
+                )
+                .with_span(span)
+                .with_help(
+                    "Add a join condition, or write CROSS JOIN if every combination of rows is intended",
+                ),
+            );
+        }
+    }
+
+    /// W1003: WHERE predicates on a column that no index leads with
+    ///
+    /// Only statements reading a single base table are inspected, and only
+    /// AND/OR combinations of comparisons between a column and a value
+    /// (`=`, `<`, `BETWEEN`, `IN (...)`, ...). Each column is reported once.
+    fn check_unindexed_filters(&mut self, expr: &Expr) {
+        let mut scope = self.tables.iter();
+        let (Some((key, table_ref)), None) = (scope.next(), scope.next()) else {
+            return;
+        };
+        if table_ref.derived_columns.is_some()
+            || table_ref.view_columns.is_some()
+            || self.ctes.contains_key(&table_ref.table.name)
+        {
+            return;
+        }
+        let Some(table) = self.catalog.get_table(&table_ref.table) else {
+            return;
+        };
+
+        let mut reported: Vec<&str> = Vec::new();
+        for (qualifier, column_ident) in filtered_columns(expr) {
+            if qualifier.is_some_and(|q| !q.value.eq_ignore_ascii_case(key)) {
+                continue;
+            }
+            let Some(column) = table.get_column(&column_ident.value) else {
+                continue;
+            };
+            if table.has_index_on(&column.name) || reported.contains(&column.name.as_str()) {
+                continue;
+            }
+            reported.push(&column.name);
+            self.diagnostics.push(
+                Diagnostic::warning(
+                    DiagnosticKind::MissingIndex,
+                    format!(
+                        "Filter on column '{}' of table '{}' may scan the whole table: no index covers it",
+                        column.name, table.name
+                    ),
+                )
+                .with_span(Span::from_sqlparser(&column_ident.span))
+                .with_help(format!(
+                    "Consider CREATE INDEX ON {} ({})",
+                    table.name, column.name
+                )),
+            );
+        }
+    }
+
+    /// W1014: `column = (SELECT ...)` where the subquery may find several rows
+    ///
+    /// Only comparisons combined with AND/OR/NOT are looked at, and only
+    /// subqueries of the common shape: one base table, no LIMIT 1 or FETCH,
+    /// no aggregate or GROUP BY, and no `=` on every column of a unique key.
+    fn check_multi_row_subqueries(&mut self, expr: &Expr) {
+        match expr {
+            Expr::BinaryOp { left, op, right } if is_comparison(op) => {
+                for side in [left, right] {
+                    let Expr::Subquery(query) = side.as_ref() else {
+                        continue;
+                    };
+                    let Some(table) = self.multi_row_source(query) else {
+                        continue;
+                    };
+                    self.diagnostics.push(
+                        Diagnostic::warning(
+                            DiagnosticKind::PossiblyMultiRowSubquery,
+                            format!(
+                                "Subquery compared with '{}' may return more than one row of '{}'",
+                                op, table
+                            ),
+                        )
+                        .with_span(Span::from_sqlparser(&side.span()))
+                        .with_help(
+                            "Filter the subquery on a unique key, add LIMIT 1, or compare with IN (...) instead",
+                        ),
+                    );
+                }
+            }
+            Expr::BinaryOp { left, right, .. } => {
+                self.check_multi_row_subqueries(left);
+                self.check_multi_row_subqueries(right);
+            }
+            Expr::Nested(inner) | Expr::UnaryOp { expr: inner, .. } => {
+                self.check_multi_row_subqueries(inner)
+            }
+            _ => {}
+        }
+    }
+
+    /// The table a scalar subquery may read several rows from, if it has the
+    /// shape [`check_multi_row_subqueries`](Self::check_multi_row_subqueries) looks for
+    fn multi_row_source(&self, query: &Query) -> Option<QualifiedName> {
+        let at_most_one = |limit: &Expr| matches!(limit, Expr::Value(Value::Number(n, _)) if n == "0" || n == "1");
+        if query.limit.as_ref().is_some_and(at_most_one) || query.fetch.is_some() {
+            return None;
+        }
+        let SetExpr::Select(select) = query.body.as_ref() else {
+            return None;
+        };
+        let [from] = select.from.as_slice() else {
+            return None;
+        };
+        let TableFactor::Table {
+            name,
+            alias,
+            args: None,
+            ..
+        } = &from.relation
+        else {
+            return None;
+        };
+        let grouped = match &select.group_by {
+            GroupByExpr::All(_) => true,
+            GroupByExpr::Expressions(exprs, _) => !exprs.is_empty(),
+        };
+        if !from.joins.is_empty() || grouped || select.having.is_some() {
+            return None;
+        }
+
+        let mut finder = AggregateFinder::default();
+        for item in &select.projection {
+            let _ = sqlparser::ast::Visit::visit(item, &mut finder);
+        }
+        if finder.found.iter().any(|(kind, _)| *kind == "Aggregate") {
+            return None;
+        }
+
+        let table_name = self.dialect.qualified_name(name);
+        if self.ctes.contains_key(&table_name.name) {
+            return None;
+        }
+        let table = self.catalog.get_table(&table_name)?;
+
+        // Columns of this table set equal to something by a WHERE conjunct
+        let qualifier = alias.as_ref().map_or(&table_name.name, |a| &a.name.value);
+        let own_column = |expr: &Expr| match expr {
+            Expr::Identifier(column) => Some(column.value.clone()),
+            Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+                [.., table, column] if table.value.eq_ignore_ascii_case(qualifier) => {
+                    Some(column.value.clone())
                 }
-                JoinConstraint::Natural | JoinConstraint::None => {}
-            }
+                _ => None,
+            },
+            _ => None,
+        };
+        let pinned: Vec<&str> = select
+            .selection
+            .iter()
+            .flat_map(conjuncts)
+            .filter_map(|conjunct| match conjunct {
+                Expr::BinaryOp {
+                    left,
+                    op: BinaryOperator::Eq,
+                    right,
+                } => own_column(left).or_else(|| own_column(right)),
+                _ => None,
+            })
+            .filter_map(|column| table.get_column(&column))
+            .map(|column| column.name.as_str())
+            .collect();
+        let pins_unique_key = table
+            .unique_keys()
+            .iter()
+            .any(|key| key.iter().all(|column| pinned.contains(column)));
+        (!pins_unique_key).then(|| table.name.clone())
+    }
+
+    /// E0014: an unqualified table name found in several search_path schemas
+    ///
+    /// Resolution still proceeds with the first match to avoid cascading errors.
+    fn check_ambiguous_table(&mut self, name: &ObjectName, table_name: &QualifiedName) {
+        if table_name.schema.is_some() {
+            return;
         }
+        let schemas = self.catalog.schemas_containing(table_name);
+        if schemas.len() < 2 {
+            return;
+        }
+        let mut diag = Diagnostic::error(
+            DiagnosticKind::AmbiguousTable,
+            format!(
+                "Table '{}' is ambiguous: it exists in schemas {}",
+                table_name.name,
+                schemas.join(", ")
+            ),
+        )
+        .with_help(format!(
+            "Qualify the table with a schema, e.g. '{}.{}'",
+            schemas[0], table_name.name
+        ));
+        if let Some(ident) = name.0.last() {
+            diag = diag.with_span(Span::from_sqlparser(&ident.span));
+        }
+        self.diagnostics.push(diag);
     }
 
     /// Resolve a table factor (table name, subquery, etc.)
@@ -502,7 +1894,7 @@ impl<'a> NameResolver<'a> {
             TableFactor::Table {
                 name, alias, args, ..
             } => {
-                let table_name = object_name_to_qualified(name);
+                let written = written_name(name);
 
                 // Table-valued function call (e.g., generate_series(...))
                 // Register alias if present, skip table existence check
@@ -521,28 +1913,28 @@ impl<'a> NameResolver<'a> {
                                 alias: Some(a_name),
                                 view_columns: None,
                                 derived_columns: Some(columns),
+                                column_origins: HashMap::new(),
                             },
                         );
                     }
                     return;
                 }
 
-                // Check if it's a CTE first
-                let is_cte = self.ctes.contains_key(&table_name.name);
+                // Check if it's a CTE first; CTEs are scoped by name as written
+                let is_cte = self.ctes.contains_key(&written);
+                let table_name = if is_cte {
+                    QualifiedName::new(&written)
+                } else {
+                    self.dialect.qualified_name(name)
+                };
+                if !is_cte {
+                    self.check_ambiguous_table(name, &table_name);
+                }
 
                 // Check if table or view exists (in catalog or as CTE)
                 let is_view = !is_cte && self.catalog.view_exists(&table_name);
                 if !is_cte && !is_view && !self.catalog.table_exists(&table_name) {
-                    // Get span from the last identifier (table name)
-                    let table_span = name.0.last().map(|id| Span::from_sqlparser(&id.span));
-                    let mut diag = Diagnostic::error(
-                        DiagnosticKind::TableNotFound,
-                        format!("Table '{}' not found", table_name),
-                    )
-                    .with_help("Check that the table exists in your schema definition");
-                    if let Some(span) = table_span {
-                        diag = diag.with_span(span);
-                    }
+                    let diag = table_not_found(self.catalog, name, &table_name, true);
                     self.diagnostics.push(diag);
                     return;
                 }
@@ -558,9 +1950,7 @@ impl<'a> NameResolver<'a> {
 
                 // Register table in scope
                 let alias_name = alias.as_ref().map(|a| a.name.value.clone());
-                let lookup_name = alias_name
-                    .clone()
-                    .unwrap_or_else(|| table_name.name.clone());
+                let lookup_name = alias_name.clone().unwrap_or(written);
 
                 self.tables.insert(
                     lookup_name,
@@ -569,6 +1959,7 @@ impl<'a> NameResolver<'a> {
                         alias: alias_name,
                         view_columns,
                         derived_columns: None,
+                        column_origins: HashMap::new(),
                     },
                 );
             }
@@ -579,10 +1970,15 @@ impl<'a> NameResolver<'a> {
             } => {
                 // Save current table scope so subquery resolution doesn't leak
                 let saved_tables = self.tables.clone();
+                let saved_outer = self.outer_tables.clone();
 
                 // Non-LATERAL subqueries cannot reference outer FROM tables,
-                // so clear the table scope. LATERAL subqueries can see outer tables.
-                if !lateral {
+                // so clear the table scope. LATERAL subqueries see preceding FROM
+                // items like a correlated subquery: as an outer scope, so the
+                // subquery's own tables take precedence for unqualified columns.
+                if *lateral {
+                    self.outer_tables.extend(self.tables.drain());
+                } else {
                     self.tables.clear();
                 }
 
@@ -592,18 +1988,36 @@ impl<'a> NameResolver<'a> {
                 // Infer column names from the subquery projection
                 let derived_columns = self.infer_cte_columns(&subquery.body);
 
+                if let SetExpr::Values(values) = subquery.body.as_ref() {
+                    self.check_values_arity(values, alias.as_ref());
+                }
+
+                // Use explicit column aliases if provided: (SELECT ...) AS v(col1, col2).
+                // Columns beyond the alias list keep their derived names.
+                let columns: Vec<String> = match alias {
+                    Some(a) if !a.columns.is_empty() => {
+                        let mut columns: Vec<String> =
+                            a.columns.iter().map(|c| c.name.value.clone()).collect();
+                        if let SetExpr::Values(_) = subquery.body.as_ref() {
+                            columns.extend(derived_columns.into_iter().skip(a.columns.len()));
+                        }
+                        columns
+                    }
+                    _ => derived_columns,
+                };
+                let aliases = match alias {
+                    Some(a) if !a.columns.is_empty() => &columns[..],
+                    _ => &[][..],
+                };
+                let column_origins = self.projection_origins(&subquery.body, aliases);
+
                 // Restore table scope
                 self.tables = saved_tables;
+                self.outer_tables = saved_outer;
 
                 // Register derived table alias in outer scope
                 if let Some(a) = alias {
                     let alias_name = a.name.value.clone();
-                    // Use explicit column aliases if provided: (SELECT ...) AS v(col1, col2)
-                    let columns = if !a.columns.is_empty() {
-                        a.columns.iter().map(|c| c.name.value.clone()).collect()
-                    } else {
-                        derived_columns
-                    };
                     self.tables.insert(
                         alias_name.clone(),
                         TableRef {
@@ -611,6 +2025,7 @@ impl<'a> NameResolver<'a> {
                             alias: Some(alias_name),
                             view_columns: None,
                             derived_columns: Some(columns),
+                            column_origins,
                         },
                     );
                 }
@@ -634,6 +2049,7 @@ impl<'a> NameResolver<'a> {
                             alias: Some(alias_name),
                             view_columns: None,
                             derived_columns: Some(columns),
+                            column_origins: HashMap::new(),
                         },
                     );
                 }
@@ -643,7 +2059,10 @@ impl<'a> NameResolver<'a> {
     }
 
     /// Resolve a SELECT item
-    fn resolve_select_item(&mut self, item: &SelectItem, select_span: &Span) {
+    ///
+    /// `has_from` is whether the SELECT has a FROM clause; a `*` over FROM items
+    /// that all failed to resolve has already been reported through them.
+    fn resolve_select_item(&mut self, item: &SelectItem, select_span: &Span, has_from: bool) {
         match item {
             SelectItem::UnnamedExpr(expr) => self.resolve_expr(expr),
             SelectItem::ExprWithAlias { expr, .. } => self.resolve_expr(expr),
@@ -651,7 +2070,9 @@ impl<'a> NameResolver<'a> {
                 // table.*
                 if let Some(first_ident) = name.0.first() {
                     let table_name = &first_ident.value;
-                    if !self.tables.contains_key(table_name.as_str()) {
+                    if self.tables.contains_key(table_name.as_str()) {
+                        self.table_uses.push(table_name.clone());
+                    } else {
                         let table_span = Span::from_sqlparser(&first_ident.span);
                         self.diagnostics.push(
                             Diagnostic::error(
@@ -665,7 +2086,8 @@ impl<'a> NameResolver<'a> {
             }
             SelectItem::Wildcard(_) => {
                 // * - valid if we have at least one table
-                if self.tables.is_empty() {
+                self.table_uses.extend(self.tables.keys().cloned());
+                if self.tables.is_empty() && !has_from {
                     self.diagnostics.push(
                         Diagnostic::error(
                             DiagnosticKind::TableNotFound,
@@ -686,17 +2108,48 @@ impl<'a> NameResolver<'a> {
                 self.resolve_column(None, ident);
             }
             Expr::CompoundIdentifier(idents) => {
-                // table.column or schema.table.column
+                // table.column or schema.table.column, where a composite-typed
+                // column may take the place of the qualifier (column.field,
+                // table.column.field)
                 match idents.as_slice() {
+                    [column, field]
+                        if !self.qualifier_in_scope(column)
+                            && self.column_composite_type(None, column).is_some() =>
+                    {
+                        self.resolve_field_access(None, column, field);
+                    }
                     [table, column] => {
                         self.resolve_column(Some(table), column);
                     }
-                    [_schema, table, column] => {
-                        self.resolve_column(Some(table), column);
+                    [table, column, field]
+                        if self.qualifier_in_scope(table)
+                            && self.column_composite_type(Some(table), column).is_some() =>
+                    {
+                        self.resolve_field_access(Some(table), column, field);
+                    }
+                    [schema, table, column] => {
+                        let qualifier_valid = self.check_schema_qualifier(schema, table);
+                        if qualifier_valid {
+                            self.resolve_column(Some(table), column);
+                        }
                     }
                     _ => {}
                 }
             }
+            Expr::CompositeAccess { expr, key } => {
+                // (column).field or (table.column).field
+                let mut inner = expr.as_ref();
+                while let Expr::Nested(nested) = inner {
+                    inner = nested;
+                }
+                match inner {
+                    Expr::Identifier(column) => self.resolve_field_access(None, column, key),
+                    Expr::CompoundIdentifier(idents) if idents.len() == 2 => {
+                        self.resolve_field_access(Some(&idents[0]), &idents[1], key)
+                    }
+                    _ => self.resolve_expr(expr),
+                }
+            }
             Expr::BinaryOp { left, right, .. } => {
                 self.resolve_expr(left);
                 self.resolve_expr(right);
@@ -713,14 +2166,19 @@ impl<'a> NameResolver<'a> {
                 if let Some(filter) = &func.filter {
                     self.resolve_expr(filter);
                 }
-                // Resolve OVER (PARTITION BY ... ORDER BY ...) clause
+                // Resolve OVER (PARTITION BY ... ORDER BY ...) clause. Unlike the
+                // query's ORDER BY, a window's PARTITION BY / ORDER BY only sees
+                // input columns: output aliases are not in scope (PostgreSQL),
+                // even when the window function itself sits in the query's ORDER BY.
                 if let Some(sqlparser::ast::WindowType::WindowSpec(spec)) = &func.over {
+                    let saved_aliases = std::mem::take(&mut self.select_aliases);
                     for e in &spec.partition_by {
                         self.resolve_expr(e);
                     }
                     for ob in &spec.order_by {
                         self.resolve_expr(&ob.expr);
                     }
+                    self.select_aliases = saved_aliases;
                 }
             }
             Expr::InList { expr, list, .. } => {
@@ -939,6 +2397,64 @@ impl<'a> NameResolver<'a> {
         }
     }
 
+    /// Expand `qualifier.*` to the columns of that one table or alias in the
+    /// current FROM scope
+    ///
+    /// Returns `None` when the qualifier is not in scope or its column list
+    /// is unknown (e.g. a CTE that itself projects `*`).
+    fn expand_qualified_wildcard(&self, qualifier: &ObjectName) -> Option<Vec<String>> {
+        let table_ref = self.tables.get(qualifier.0.last()?.value.as_str())?;
+        self.scope_columns(table_ref)
+    }
+
+    /// The columns of a table reference, if known
+    fn scope_columns(&self, table_ref: &TableRef) -> Option<Vec<String>> {
+        let columns = if let Some(derived_cols) = &table_ref.derived_columns {
+            derived_cols.clone()
+        } else if let Some(cte) = self.ctes.get(&table_ref.table.name) {
+            cte.columns.clone()
+        } else if let Some(view_cols) = &table_ref.view_columns {
+            view_cols.clone()
+        } else {
+            let table_def = self.catalog.get_table(&table_ref.table)?;
+            table_def.columns.keys().cloned().collect()
+        };
+        (!columns.is_empty()).then_some(columns)
+    }
+
+    /// The types of the columns `*` expands a table reference to, known
+    /// where a column traces back to a table column
+    pub(super) fn wildcard_column_types(
+        &self,
+        table_ref: &TableRef,
+    ) -> Option<Vec<Option<SqlType>>> {
+        let columns = self.scope_columns(table_ref)?;
+        Some(
+            columns
+                .iter()
+                .map(|column| {
+                    let origin = self.column_origin(table_ref, column)?;
+                    let table_def = self.catalog.get_table(&origin.table_name())?;
+                    Some(table_def.get_column(&origin.column)?.data_type.clone())
+                })
+                .collect(),
+        )
+    }
+
+    /// Number of columns a SELECT list produces in the current scope, if known
+    fn projection_width(&self, projection: &[SelectItem]) -> Option<usize> {
+        projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::QualifiedWildcard(name, _) => {
+                    self.expand_qualified_wildcard(name).map(|cols| cols.len())
+                }
+                SelectItem::Wildcard(_) => None,
+                _ => Some(1),
+            })
+            .sum()
+    }
+
     /// Check if a table reference contains the given column
     fn table_ref_has_column(&self, table_ref: &TableRef, column_name: &str) -> bool {
         if let Some(derived_cols) = &table_ref.derived_columns {
@@ -947,7 +2463,7 @@ impl<'a> NameResolver<'a> {
                     .iter()
                     .any(|c| c.eq_ignore_ascii_case(column_name))
         } else if let Some(cte) = self.ctes.get(&table_ref.table.name) {
-            cte.columns.iter().any(|c| c == column_name)
+            cte.columns.is_empty() || cte.columns.iter().any(|c| c == column_name)
         } else if let Some(view_cols) = &table_ref.view_columns {
             view_cols
                 .iter()
@@ -959,18 +2475,298 @@ impl<'a> NameResolver<'a> {
         }
     }
 
-    /// Resolve a column reference
+    /// Check the schema of a `schema.table.column` reference against the FROM item
+    ///
+    /// Only an unaliased catalog table or view can be qualified with its schema;
+    /// aliases, CTEs and derived tables have none. In MySQL and SQLite an
+    /// unqualified table may be qualified with any database name. Returns
+    /// whether the qualifier is valid (an unknown table is left to column
+    /// resolution to report).
+    fn check_schema_qualifier(&mut self, schema: &Ident, table: &Ident) -> bool {
+        let Some(table_ref) = self
+            .tables
+            .get(&table.value)
+            .or_else(|| self.outer_tables.get(&table.value))
+        else {
+            return true;
+        };
+        let actual = if table_ref.alias.is_some()
+            || table_ref.derived_columns.is_some()
+            || self.ctes.contains_key(&table_ref.table.name)
+        {
+            None
+        } else if let Some(written) = &table_ref.table.schema {
+            Some(written.as_str())
+        } else if self.dialect == SqlDialect::PostgreSQL {
+            self.catalog.resolve_schema(&table_ref.table)
+        } else {
+            // The current database, which the catalog doesn't know
+            return true;
+        };
+        if actual.is_some_and(|actual| actual.eq_ignore_ascii_case(&schema.value)) {
+            return true;
+        }
+
+        let help = match actual {
+            Some(actual) => format!(
+                "'{}' in the FROM clause is '{}.{}'",
+                table.value, actual, table_ref.table.name
+            ),
+            None => format!(
+                "'{}' is not a schema table here; drop the '{}.' qualifier",
+                table.value, schema.value
+            ),
+        };
+        self.diagnostics.push(
+            Diagnostic::error(
+                DiagnosticKind::TableNotFound,
+                format!(
+                    "Table '{}.{}' not found in FROM clause",
+                    schema.value, table.value
+                ),
+            )
+            .with_span(Span::from_sqlparser(&schema.span))
+            .with_help(help),
+        );
+        false
+    }
+
+    /// Resolve a column reference, recording its origin when it is valid
     fn resolve_column(&mut self, table_ident: Option<&Ident>, column_ident: &Ident) {
+        let diagnostic_count = self.diagnostics.len();
+        self.check_column(table_ident, column_ident);
+        if self.diagnostics.len() == diagnostic_count {
+            if let Some(key) = self.column_scope_key(table_ident, &column_ident.value) {
+                self.table_uses.push(key);
+            }
+            if let Some(origin) = self.lookup_column_origin(table_ident, &column_ident.value) {
+                self.resolved_columns
+                    .push((Span::from_sqlparser(&column_ident.span), origin));
+            }
+        }
+    }
+
+    /// Find the scope key (alias or table name) a valid column reference belongs to
+    fn column_scope_key(&self, table_ident: Option<&Ident>, column_name: &str) -> Option<String> {
+        if let Some(table_id) = table_ident {
+            return Some(table_id.value.clone());
+        }
+        [&self.tables, &self.outer_tables]
+            .into_iter()
+            .find_map(|scope| {
+                scope
+                    .iter()
+                    .find(|(_, t)| self.table_ref_has_column(t, column_name))
+                    .map(|(key, _)| key.clone())
+            })
+    }
+
+    /// Find the schema column a (possibly qualified) column reference points at
+    fn lookup_column_origin(
+        &self,
+        table_ident: Option<&Ident>,
+        column_name: &str,
+    ) -> Option<ColumnRef> {
+        if let Some(table_id) = table_ident {
+            let table_ref = self
+                .tables
+                .get(&table_id.value)
+                .or_else(|| self.outer_tables.get(&table_id.value))?;
+            return self.column_origin(table_ref, column_name);
+        }
+
+        for scope in [&self.tables, &self.outer_tables] {
+            let mut matches = scope
+                .values()
+                .filter(|t| self.table_ref_has_column(t, column_name));
+            if let Some(table_ref) = matches.next() {
+                if matches.next().is_some() {
+                    return None;
+                }
+                return self.column_origin(table_ref, column_name);
+            }
+        }
+        None
+    }
+
+    /// The key of the table reference in the current scope that a column
+    /// reference resolves to
+    fn column_scope(&self, table_ident: Option<&Ident>, column_name: &str) -> Option<&str> {
+        if let Some(table_id) = table_ident {
+            return self
+                .tables
+                .get_key_value(&table_id.value)
+                .map(|(key, _)| key.as_str());
+        }
+        let mut matches = self
+            .tables
+            .iter()
+            .filter(|(_, t)| self.table_ref_has_column(t, column_name));
+        match (matches.next(), matches.next()) {
+            (Some((key, _)), None) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Find where a column of a table reference originates from
+    fn column_origin(&self, table_ref: &TableRef, column_name: &str) -> Option<ColumnRef> {
+        if table_ref.derived_columns.is_some() {
+            return table_ref
+                .column_origins
+                .get(&column_name.to_lowercase())
+                .cloned();
+        }
+        if let Some(cte) = self.ctes.get(&table_ref.table.name) {
+            return cte.column_origins.get(&column_name.to_lowercase()).cloned();
+        }
+
+        let schema = self
+            .catalog
+            .resolve_schema(&table_ref.table)
+            .unwrap_or(&self.catalog.default_schema)
+            .to_string();
+        if let Some(view_cols) = &table_ref.view_columns {
+            return view_cols
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(column_name))
+                .map(|c| ColumnRef::new(schema, &table_ref.table.name, c));
+        }
+        self.catalog
+            .get_table(&table_ref.table)
+            .and_then(|t| t.get_column(column_name))
+            .map(|c| ColumnRef::new(schema, &table_ref.table.name, &c.name))
+    }
+
+    /// Where the column a table reference provides is defined in the schema,
+    /// falling back to the table or view definition itself
+    fn column_location(
+        &self,
+        table_ref: &TableRef,
+        column_name: &str,
+    ) -> Option<&'a SourceLocation> {
+        let origin = self.column_origin(table_ref, column_name)?;
+        let name = origin.table_name();
+        if let Some(table) = self.catalog.get_table(&name) {
+            return table
+                .get_column(&origin.column)
+                .and_then(|column| column.location.as_ref())
+                .or(table.location.as_ref());
+        }
+        self.catalog.get_view(&name)?.location.as_ref()
+    }
+
+    /// Map the output columns of a query body to their origins
+    ///
+    /// Must be called while the body's FROM scope is still in `self.tables`.
+    /// Output columns are taken in projection order with wildcards expanded;
+    /// `aliases` (a CTE's or derived table's column list) rename them by
+    /// position. Columns after a wildcard that can't be expanded are skipped.
+    fn projection_origins(
+        &self,
+        set_expr: &SetExpr,
+        aliases: &[String],
+    ) -> HashMap<String, ColumnRef> {
+        let select = match set_expr {
+            SetExpr::Select(select) => select,
+            SetExpr::SetOperation { left, .. } => return self.projection_origins(left, aliases),
+            SetExpr::Query(query) => return self.projection_origins(&query.body, aliases),
+            _ => return HashMap::new(),
+        };
+
+        // (name, origin) of each output column, in order
+        let mut outputs: Vec<(String, Option<ColumnRef>)> = Vec::new();
+        for item in &select.projection {
+            let expanded = match item {
+                SelectItem::Wildcard(_) => select
+                    .from
+                    .iter()
+                    .flat_map(|t| {
+                        std::iter::once(&t.relation).chain(t.joins.iter().map(|j| &j.relation))
+                    })
+                    .map(|factor| self.scope_outputs(&table_factor_scope_key(factor)?.0))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|tables| tables.concat()),
+                SelectItem::QualifiedWildcard(name, _) => {
+                    name.0.last().and_then(|id| self.scope_outputs(&id.value))
+                }
+                SelectItem::UnnamedExpr(expr) => {
+                    let name = match expr {
+                        Expr::Identifier(ident) => ident.value.clone(),
+                        Expr::CompoundIdentifier(idents) => idents
+                            .last()
+                            .map_or_else(String::new, |id| id.value.clone()),
+                        _ => String::new(),
+                    };
+                    Some(vec![(name, self.expr_origin(expr))])
+                }
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    Some(vec![(alias.value.clone(), self.expr_origin(expr))])
+                }
+            };
+            match expanded {
+                Some(columns) => outputs.extend(columns),
+                None => break,
+            }
+        }
+
+        outputs
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, (name, origin))| {
+                let name = aliases.get(idx).unwrap_or(&name);
+                Some((name.to_lowercase(), origin?))
+            })
+            .collect()
+    }
+
+    /// The origin of a bare or qualified column reference
+    fn expr_origin(&self, expr: &Expr) -> Option<ColumnRef> {
+        match expr {
+            Expr::Identifier(ident) => self.lookup_column_origin(None, &ident.value),
+            Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+                [.., table, column] => self.lookup_column_origin(Some(table), &column.value),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The columns of a table or alias in the current FROM scope, with their origins
+    fn scope_outputs(&self, key: &str) -> Option<Vec<(String, Option<ColumnRef>)>> {
+        let table_ref = self.tables.get(key)?;
+        let columns = self.scope_columns(table_ref)?;
+        Some(
+            columns
+                .into_iter()
+                .map(|column| {
+                    let origin = self.column_origin(table_ref, &column);
+                    (column, origin)
+                })
+                .collect(),
+        )
+    }
+
+    /// Validate a column reference, reporting unknown or ambiguous columns
+    fn check_column(&mut self, table_ident: Option<&Ident>, column_ident: &Ident) {
         let column_name = &column_ident.value;
         let column_span = Span::from_sqlparser(&column_ident.span);
 
         if let Some(table_id) = table_ident {
             let table_alias = &table_id.value;
             // Qualified column reference (table.column)
+            // An unquoted qualifier also matches its folded form, e.g. `EXCLUDED`
+            let folded = table_alias.to_lowercase();
             if let Some(table_ref) = self
                 .tables
                 .get(table_alias)
                 .or_else(|| self.outer_tables.get(table_alias))
+                .or_else(|| {
+                    table_id
+                        .quote_style
+                        .is_none()
+                        .then(|| self.tables.get(&folded))
+                        .flatten()
+                })
             {
                 // Check derived table first
                 if let Some(derived_cols) = &table_ref.derived_columns {
@@ -993,8 +2789,8 @@ impl<'a> NameResolver<'a> {
                         );
                     }
                 } else if let Some(cte) = self.ctes.get(&table_ref.table.name) {
-                    // Validate against CTE columns
-                    if !cte.columns.contains(column_name) {
+                    // Validate against CTE columns (unless they couldn't be inferred)
+                    if !cte.columns.is_empty() && !cte.columns.contains(column_name) {
                         self.diagnostics.push(
                             Diagnostic::error(
                                 DiagnosticKind::ColumnNotFound,
@@ -1034,8 +2830,17 @@ impl<'a> NameResolver<'a> {
                             ),
                         )
                         .with_span(column_span);
-                        if let Some(suggestion) = similar {
-                            diag = diag.with_help(format!("Did you mean '{}'?", suggestion));
+                        if let Some((key, table)) =
+                            self.other_table_with_column(table_alias, column_name)
+                        {
+                            diag = diag
+                                .with_help(format!(
+                                    "Column '{}' exists on table '{}'; did you mean '{}.{}'?",
+                                    column_name, table, key, column_name
+                                ))
+                                .with_suggestion(Span::from_sqlparser(&table_id.span), key);
+                        } else if let Some(suggestion) = similar {
+                            diag = with_typo_fix(diag, column_ident, &suggestion);
                         }
                         self.diagnostics.push(diag);
                     }
@@ -1095,8 +2900,8 @@ impl<'a> NameResolver<'a> {
                         format!("Column '{}' not found", column_name),
                     )
                     .with_span(column_span);
-                    if !suggestions.is_empty() {
-                        diag = diag.with_help(format!("Did you mean '{}'?", suggestions[0]));
+                    if let Some(suggestion) = suggestions.first() {
+                        diag = with_typo_fix(diag, column_ident, suggestion);
                     }
                     self.diagnostics.push(diag);
                 }
@@ -1105,27 +2910,122 @@ impl<'a> NameResolver<'a> {
                 }
                 _ => {
                     // Ambiguous - found in multiple tables
-                    self.diagnostics.push(
-                        Diagnostic::error(
-                            DiagnosticKind::AmbiguousColumn,
-                            format!(
-                                "Column '{}' is ambiguous (found in tables: {})",
-                                column_name,
-                                found_in.join(", ")
-                            ),
-                        )
-                        .with_span(column_span)
-                        .with_help(format!(
-                            "Qualify the column with a table name: {}.{}",
-                            found_in[0], column_name
-                        )),
-                    );
+                    let mut diag = Diagnostic::error(
+                        DiagnosticKind::AmbiguousColumn,
+                        format!(
+                            "Column '{}' is ambiguous (found in tables: {})",
+                            column_name,
+                            found_in.join(", ")
+                        ),
+                    )
+                    .with_span(column_span)
+                    .with_help(format!(
+                        "Qualify the column with a table name: {}.{}",
+                        found_in[0], column_name
+                    ));
+                    for name in &found_in {
+                        let location = self
+                            .tables
+                            .get(*name)
+                            .or_else(|| self.outer_tables.get(*name))
+                            .and_then(|table_ref| self.column_location(table_ref, column_name));
+                        if let Some(location) = location {
+                            diag = diag.with_related(
+                                format!("'{}' found in {}", column_name, name),
+                                location.clone(),
+                            );
+                        }
+                    }
+                    self.diagnostics.push(diag);
                 }
             }
         }
     }
 
-    /// Consume the resolver and return collected diagnostics
+    /// Whether an identifier names a table or alias in scope
+    fn qualifier_in_scope(&self, ident: &Ident) -> bool {
+        self.tables.contains_key(&ident.value)
+            || self.outer_tables.contains_key(&ident.value)
+            || (ident.quote_style.is_none()
+                && self.tables.contains_key(&ident.value.to_lowercase()))
+    }
+
+    /// The composite type of a column reference, if it has one
+    fn column_composite_type(
+        &self,
+        table_ident: Option<&Ident>,
+        column_ident: &Ident,
+    ) -> Option<&'a CompositeTypeDef> {
+        let origin = self.lookup_column_origin(table_ident, &column_ident.value)?;
+        let column = self
+            .catalog
+            .get_table(&origin.table_name())?
+            .get_column(&origin.column)?;
+        self.catalog.composite_type(&column.data_type)
+    }
+
+    /// Resolve a field access on a composite-typed column
+    fn resolve_field_access(
+        &mut self,
+        table_ident: Option<&Ident>,
+        column_ident: &Ident,
+        field_ident: &Ident,
+    ) {
+        let diagnostic_count = self.diagnostics.len();
+        self.resolve_column(table_ident, column_ident);
+        if self.diagnostics.len() != diagnostic_count {
+            return;
+        }
+        let Some(composite) = self.column_composite_type(table_ident, column_ident) else {
+            return;
+        };
+        if composite.get_field(&field_ident.value).is_some() {
+            return;
+        }
+
+        let field_lower = field_ident.value.to_lowercase();
+        let similar = composite
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    levenshtein_distance(&field_lower, &f.name.to_lowercase()),
+                    f,
+                )
+            })
+            .filter(|(distance, _)| *distance <= 3)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, f)| f.name.clone());
+        let mut diag = Diagnostic::error(
+            DiagnosticKind::ColumnNotFound,
+            format!(
+                "Field '{}' not found in composite type '{}'",
+                field_ident.value, composite.name
+            ),
+        )
+        .with_span(Span::from_sqlparser(&field_ident.span));
+        if let Some(suggestion) = similar {
+            diag = with_typo_fix(diag, field_ident, &suggestion);
+        }
+        self.diagnostics.push(diag);
+    }
+
+    /// Another FROM item in scope that has the column, for a reference whose
+    /// qualifier names the wrong one: its scope key and table name
+    fn other_table_with_column(
+        &self,
+        qualifier: &str,
+        column_name: &str,
+    ) -> Option<(String, String)> {
+        let mut keys: Vec<&String> = self.tables.keys().filter(|key| *key != qualifier).collect();
+        keys.sort();
+        keys.into_iter().find_map(|key| {
+            let table_ref = &self.tables[key];
+            self.table_ref_has_column(table_ref, column_name)
+                .then(|| (key.clone(), table_ref.table.to_string()))
+        })
+    }
+
     /// Consume the resolver and return collected diagnostics
     ///
     /// Returns all diagnostics collected during name resolution.
@@ -1134,24 +3034,457 @@ impl<'a> NameResolver<'a> {
     }
 }
 
-/// Convert ObjectName to QualifiedName
-fn object_name_to_qualified(name: &ObjectName) -> QualifiedName {
-    match name.0.as_slice() {
-        [table] => QualifiedName::new(&table.value),
-        [schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        [_catalog, schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        _ => QualifiedName::new(name.to_string()),
+/// Replaces grouped sub-expressions, aggregates and subqueries with NULL,
+/// leaving the column references that still need to be grouped
+struct GroupedPruner<'r, 'a> {
+    resolver: &'r NameResolver<'a>,
+    grouped: &'r [String],
+}
+
+impl VisitorMut for GroupedPruner<'_, '_> {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<()> {
+        // Only the subquery of `x IN (SELECT ...)` has its own scope; `x` still
+        // has to be grouped
+        while let Expr::InSubquery { expr: tested, .. } = expr {
+            *expr = std::mem::replace(tested.as_mut(), Expr::Value(Value::Null));
+        }
+        let settled = match expr {
+            // Subqueries have their own scope
+            Expr::Subquery(_) | Expr::Exists { .. } => true,
+            // A call the analyzer doesn't know may be an aggregate (corr,
+            // percentile_cont, user-defined ones), so its arguments are left alone
+            Expr::Function(func) if is_aggregate_or_window(func) || !is_known_scalar(func) => true,
+            _ => self.grouped.contains(&self.resolver.canonical_expr(expr)),
+        };
+        if settled {
+            *expr = Expr::Value(Value::Null);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Aggregate and window calls outside subqueries, with the name they're called by
+#[derive(Default)]
+struct AggregateFinder {
+    /// Subquery nesting depth
+    depth: usize,
+    found: Vec<(&'static str, Ident)>,
+}
+
+impl Visitor for AggregateFinder {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        if let Expr::Function(func) = expr {
+            if self.depth == 0 && is_aggregate_or_window(func) {
+                let kind = if func.over.is_some() {
+                    "Window"
+                } else {
+                    "Aggregate"
+                };
+                if let Some(name) = func.name.0.last() {
+                    self.found.push((kind, name.clone()));
+                }
+            }
+        }
+        ControlFlow::Continue(())
     }
 }
 
-/// Get table name from TableFactor
-fn table_with_joins_to_name(factor: &TableFactor) -> Option<QualifiedName> {
-    match factor {
-        TableFactor::Table { name, .. } => Some(object_name_to_qualified(name)),
+/// `column` or `table.column` (also `schema.table.column`)
+fn column_reference(expr: &Expr) -> Option<(Option<&Ident>, &Ident)> {
+    match expr {
+        Expr::Identifier(column) => Some((None, column)),
+        Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+            [.., table, column] => Some((Some(table), column)),
+            _ => None,
+        },
         _ => None,
     }
 }
 
+/// Union-find: the representative of `i`'s set
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Union-find: merge the sets of `a` and `b`
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    parent[b] = a;
+}
+
+/// `=`, `<>`, `<`, `<=`, `>` or `>=`
+fn is_comparison(op: &BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+    )
+}
+
+/// The top-level AND terms of a predicate
+fn conjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut terms = conjuncts(left);
+            terms.extend(conjuncts(right));
+            terms
+        }
+        Expr::Nested(inner)
+            if matches!(
+                **inner,
+                Expr::BinaryOp {
+                    op: BinaryOperator::And,
+                    ..
+                }
+            ) =>
+        {
+            conjuncts(inner)
+        }
+        _ => vec![expr],
+    }
+}
+
+/// Columns compared with a value in an AND/OR combination of predicates
+fn filtered_columns(expr: &Expr) -> Vec<(Option<&Ident>, &Ident)> {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And | BinaryOperator::Or,
+            right,
+        } => {
+            let mut columns = filtered_columns(left);
+            columns.extend(filtered_columns(right));
+            columns
+        }
+        Expr::Nested(inner) => filtered_columns(inner),
+        Expr::BinaryOp {
+            left,
+            op:
+                BinaryOperator::Eq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq,
+            right,
+        } => match (column_reference(left), column_reference(right)) {
+            (Some(column), None) | (None, Some(column)) => vec![column],
+            _ => Vec::new(),
+        },
+        Expr::Between {
+            expr,
+            negated: false,
+            ..
+        }
+        | Expr::InList {
+            expr,
+            negated: false,
+            ..
+        } => column_reference(expr).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Aggregate and window function calls, which may use ungrouped columns
+fn is_aggregate_or_window(func: &Function) -> bool {
+    const AGGREGATES: &[&str] = &[
+        "COUNT",
+        "SUM",
+        "AVG",
+        "MIN",
+        "MAX",
+        "ARRAY_AGG",
+        "STRING_AGG",
+        "GROUP_CONCAT",
+        "JSON_AGG",
+        "JSONB_AGG",
+        "JSON_OBJECT_AGG",
+        "JSONB_OBJECT_AGG",
+        "JSON_ARRAYAGG",
+        "JSON_OBJECTAGG",
+        "BOOL_AND",
+        "BOOL_OR",
+        "EVERY",
+        "BIT_AND",
+        "BIT_OR",
+        "BIT_XOR",
+        "STDDEV",
+        "STDDEV_POP",
+        "STDDEV_SAMP",
+        "VARIANCE",
+        "VAR_POP",
+        "VAR_SAMP",
+        "ANY_VALUE",
+        "XMLAGG",
+    ];
+
+    let name = func.name.to_string().to_uppercase();
+    let name = name.rsplit('.').next().unwrap_or(&name);
+    func.over.is_some()
+        || func.filter.is_some()
+        || !func.within_group.is_empty()
+        || matches!(func.args, FunctionArguments::Subquery(_))
+        || AGGREGATES.contains(&name)
+}
+
+/// Built-in scalar functions, whose arguments are evaluated per row
+fn is_known_scalar(func: &Function) -> bool {
+    const SCALARS: &[&str] = &[
+        "ABS",
+        "AGE",
+        "CEIL",
+        "CEILING",
+        "CHAR_LENGTH",
+        "CHARACTER_LENGTH",
+        "COALESCE",
+        "CONCAT",
+        "CONCAT_WS",
+        "DATE",
+        "DATE_PART",
+        "DATE_TRUNC",
+        "FLOOR",
+        "GREATEST",
+        "IFNULL",
+        "INITCAP",
+        "LEAST",
+        "LEFT",
+        "LENGTH",
+        "LOWER",
+        "LPAD",
+        "LTRIM",
+        "MD5",
+        "MOD",
+        "NULLIF",
+        "POSITION",
+        "POWER",
+        "REPEAT",
+        "REPLACE",
+        "REVERSE",
+        "RIGHT",
+        "ROUND",
+        "RPAD",
+        "RTRIM",
+        "SIGN",
+        "SPLIT_PART",
+        "SQRT",
+        "STRFTIME",
+        "STRPOS",
+        "SUBSTR",
+        "SUBSTRING",
+        "TO_CHAR",
+        "TRIM",
+        "TRUNC",
+        "UPPER",
+    ];
+
+    let name = func.name.to_string().to_uppercase();
+    let name = name.rsplit('.').next().unwrap_or(&name);
+    SCALARS.contains(&name)
+}
+
+/// The unqualified name of a table as written, which is how the table
+/// scope and CTEs are keyed
+fn written_name(name: &ObjectName) -> String {
+    name.0
+        .last()
+        .map_or_else(|| name.to_string(), |ident| ident.value.clone())
+}
+
+/// Get the key a FROM item is registered under in the table scope, with its span
+fn table_factor_scope_key(factor: &TableFactor) -> Option<(String, Span)> {
+    let alias = match factor {
+        TableFactor::Table { name, alias, .. } => {
+            return match alias {
+                Some(a) => Some((a.name.value.clone(), Span::from_sqlparser(&a.name.span))),
+                None => name
+                    .0
+                    .last()
+                    .map(|id| (id.value.clone(), Span::from_sqlparser(&id.span))),
+            };
+        }
+        TableFactor::Derived { alias, .. }
+        | TableFactor::TableFunction { alias, .. }
+        | TableFactor::Function { alias, .. }
+        | TableFactor::UNNEST { alias, .. } => alias.as_ref()?,
+        _ => return None,
+    };
+    Some((
+        alias.name.value.clone(),
+        Span::from_sqlparser(&alias.name.span),
+    ))
+}
+
+/// The `DEFAULT` placeholder of a VALUES row or SET, which parses as a column name
+fn is_default_keyword(expr: &Expr) -> bool {
+    matches!(expr, Expr::Identifier(ident)
+        if ident.quote_style.is_none() && ident.value.eq_ignore_ascii_case("default"))
+}
+
+/// Whether the first branch of a query body selects `*` or `t.*`
+fn projects_wildcard(set_expr: &SetExpr) -> bool {
+    match set_expr {
+        SetExpr::Select(select) => select.projection.iter().any(|item| {
+            matches!(
+                item,
+                SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..)
+            )
+        }),
+        SetExpr::SetOperation { left, .. } => projects_wildcard(left),
+        SetExpr::Query(query) => projects_wildcard(&query.body),
+        _ => false,
+    }
+}
+
+/// Scope keys of relations on the nullable side of an outer join
+fn outer_join_nullable_keys<'t>(from: impl IntoIterator<Item = &'t TableWithJoins>) -> Vec<String> {
+    use sqlparser::ast::JoinOperator::*;
+
+    let mut keys = Vec::new();
+    for table in from {
+        let mut preceding: Vec<String> = table_factor_scope_key(&table.relation)
+            .map(|(key, _)| key)
+            .into_iter()
+            .collect();
+        for join in &table.joins {
+            let key = table_factor_scope_key(&join.relation).map(|(key, _)| key);
+            match join.join_operator {
+                LeftOuter(_) | OuterApply => keys.extend(key.clone()),
+                RightOuter(_) => keys.extend(preceding.iter().cloned()),
+                FullOuter(_) => {
+                    keys.extend(preceding.iter().cloned());
+                    keys.extend(key.clone());
+                }
+                _ => {}
+            }
+            preceding.extend(key);
+        }
+    }
+    keys
+}
+
+/// Whether a join has an explicit ON condition (USING/NATURAL share columns implicitly)
+fn join_has_on_condition(join_op: &sqlparser::ast::JoinOperator) -> bool {
+    use sqlparser::ast::JoinConstraint;
+    use sqlparser::ast::JoinOperator::*;
+
+    matches!(
+        join_op,
+        Inner(JoinConstraint::On(_))
+            | LeftOuter(JoinConstraint::On(_))
+            | RightOuter(JoinConstraint::On(_))
+            | FullOuter(JoinConstraint::On(_))
+    )
+}
+
+/// E0002 for a column that the target table of a DML statement lacks
+fn column_not_found_in_table(
+    table_def: &TableDef,
+    table_name: &QualifiedName,
+    col_ident: &Ident,
+) -> Diagnostic {
+    let mut diag = Diagnostic::error(
+        DiagnosticKind::ColumnNotFound,
+        format!(
+            "Column '{}' not found in table '{}'",
+            col_ident.value, table_name
+        ),
+    )
+    .with_span(Span::from_sqlparser(&col_ident.span));
+    if let Some(suggestion) = find_similar_column(table_def, &col_ident.value) {
+        diag = with_typo_fix(diag, col_ident, &suggestion);
+    }
+    diag
+}
+
+/// E0001 for a table missing from the catalog, suggesting a similar name if any
+///
+/// Views are only suggested where a view may be used (`include_views`).
+fn table_not_found(
+    catalog: &Catalog,
+    name: &ObjectName,
+    table_name: &QualifiedName,
+    include_views: bool,
+) -> Diagnostic {
+    let mut diag = Diagnostic::error(
+        DiagnosticKind::TableNotFound,
+        format!("Table '{}' not found", table_name),
+    );
+    // Span of the last identifier (the table name itself)
+    let Some(ident) = name.0.last() else {
+        return diag.with_help("Check that the table exists in your schema definition");
+    };
+    diag = diag.with_span(Span::from_sqlparser(&ident.span));
+    match find_similar_table(catalog, table_name, include_views) {
+        Some(suggestion) => with_typo_fix(diag, ident, &suggestion),
+        None => diag.with_help("Check that the table exists in your schema definition"),
+    }
+}
+
+/// Find a similar table (or view) name in the referenced schema (for suggestions)
+fn find_similar_table(
+    catalog: &Catalog,
+    table_name: &QualifiedName,
+    include_views: bool,
+) -> Option<String> {
+    let name_lower = table_name.name.to_lowercase();
+    let mut best_match: Option<(usize, &str)> = None;
+
+    let schemas = catalog
+        .schemas
+        .values()
+        .filter(|schema| match &table_name.schema {
+            Some(name) => schema.name.eq_ignore_ascii_case(name),
+            None => true,
+        });
+    for schema in schemas {
+        let views = schema.views.keys().filter(|_| include_views);
+        for candidate in schema.tables.keys().chain(views) {
+            let distance = levenshtein_distance(&name_lower, &candidate.to_lowercase());
+            if distance <= 3 && best_match.map_or(true, |(best, _)| distance < best) {
+                best_match = Some((distance, candidate));
+            }
+        }
+    }
+
+    best_match.map(|(_, name)| name.to_string())
+}
+
+/// Add a "Did you mean" help and a fix replacing the misspelled identifier
+fn with_typo_fix(diag: Diagnostic, ident: &Ident, suggestion: &str) -> Diagnostic {
+    let replacement = match ident.quote_style {
+        Some('[') => format!("[{}]", suggestion),
+        Some(quote) => format!("{quote}{suggestion}{quote}"),
+        None => suggestion.to_string(),
+    };
+    diag.with_help(format!("Did you mean '{}'?", suggestion))
+        .with_suggestion(Span::from_sqlparser(&ident.span), replacement)
+}
+
 /// Find a similar column name (for suggestions)
 fn find_similar_column(table: &TableDef, name: &str) -> Option<String> {
     let name_lower = name.to_lowercase();
@@ -1171,7 +3504,7 @@ fn find_similar_column(table: &TableDef, name: &str) -> Option<String> {
 }
 
 /// Simple Levenshtein distance implementation
-fn levenshtein_distance(a: &str, b: &str) -> usize {
+pub(super) fn levenshtein_distance(a: &str, b: &str) -> usize {
     let a_chars: Vec<char> = a.chars().collect();
     let b_chars: Vec<char> = b.chars().collect();
     let m = a_chars.len();
@@ -5,11 +5,13 @@
 //! **Supported:**
 //! - WHERE clause type checking (E0003)
 //! - JOIN condition type checking (E0007)
+//! - Column-vs-column comparisons, e.g. a wrong join key (E0015)
 //! - Binary operators: comparisons (=, !=, <, >, <=, >=), arithmetic (+, -, *, /, %)
 //! - Nested expressions: `(a + b) * 2 = c`
 //! - Numeric type compatibility (INTEGER → BIGINT implicit casts)
 //! - INSERT VALUES type checking: `INSERT INTO users (id) VALUES ('text')` → E0003
 //! - UPDATE SET type checking: `UPDATE users SET id = 'text'` → E0003
+//! - INSERT ... SELECT and ON CONFLICT DO UPDATE type checking, including `excluded.col`
 //!
 //! **TODO (Not Yet Implemented):**
 //! - CASE expression type consistency: THEN/ELSE branches must have compatible types
@@ -22,16 +24,19 @@
 //! - Type inference is performed in a separate pass after name resolution
 
 use sqlparser::ast::{
-    AssignmentTarget, BinaryOperator, Expr, Insert, Query, Select, SetExpr, Spanned, Statement,
-    TableFactor, Value, Values,
+    Array, AssignmentTarget, BinaryOperator, Expr, Insert, MergeAction, MergeClause,
+    MergeInsertExpr, MergeInsertKind, OnConflict, OnConflictAction, OnInsert, Query, Select,
+    SelectItem, SetExpr, Spanned, Statement, Subscript, TableFactor, UnaryOperator, Value, Values,
 };
 use std::collections::HashMap;
 
+use crate::dialect::SqlDialect;
 use crate::error::{Diagnostic, DiagnosticKind, Span};
-use crate::schema::{Catalog, QualifiedName};
+use crate::schema::{Catalog, ColumnDef, EnumTypeDef, QualifiedName, TableDef};
 use crate::types::{SqlType, TypeCompatibility};
 
-use super::resolver::NameResolver;
+use super::resolver::{levenshtein_distance, NameResolver};
+use super::span_or;
 
 /// Expression type inference result
 #[derive(Debug, Clone, PartialEq)]
@@ -51,6 +56,8 @@ struct TableRef {
     view_columns: Option<Vec<String>>,
     /// If this is a derived table, the inferred column names
     derived_columns: Option<Vec<String>>,
+    /// Types of the columns `*` expands to, or None when they can't be listed
+    wildcard_types: Option<Vec<ExpressionType>>,
 }
 
 /// Type resolver for SQL expressions
@@ -58,8 +65,12 @@ pub struct TypeResolver<'a> {
     catalog: &'a Catalog,
     /// Current scope's table references (alias or name -> TableRef)
     tables: HashMap<String, TableRef>,
+    /// Dialect-specific operator semantics
+    dialect: SqlDialect,
     /// Collected diagnostics
     diagnostics: Vec<Diagnostic>,
+    /// Innermost expression or statement being checked that has a span
+    enclosing_span: Span,
 }
 
 impl<'a> TypeResolver<'a> {
@@ -68,10 +79,18 @@ impl<'a> TypeResolver<'a> {
         Self {
             catalog,
             tables: HashMap::new(),
+            dialect: SqlDialect::default(),
             diagnostics: Vec::new(),
+            enclosing_span: Span::new(0, 0),
         }
     }
 
+    /// Use the operator semantics of a specific SQL dialect
+    pub fn with_dialect(mut self, dialect: SqlDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
     /// Inherit scope from a NameResolver
     /// This allows TypeResolver to access the same table context as NameResolver
     pub fn inherit_scope(&mut self, resolver: &NameResolver) {
@@ -81,6 +100,12 @@ impl<'a> TypeResolver<'a> {
                 table_name: name_table_ref.table.clone(),
                 view_columns: name_table_ref.view_columns.clone(),
                 derived_columns: name_table_ref.derived_columns.clone(),
+                wildcard_types: resolver.wildcard_column_types(name_table_ref).map(|types| {
+                    types
+                        .into_iter()
+                        .map(|ty| ty.map_or(ExpressionType::Unknown, ExpressionType::Known))
+                        .collect()
+                }),
             };
             self.tables.insert(key.clone(), type_table_ref);
         }
@@ -88,6 +113,7 @@ impl<'a> TypeResolver<'a> {
 
     /// Check types in a statement
     pub fn check_statement(&mut self, stmt: &Statement) {
+        self.enclosing_span = Span::from_sqlparser(&stmt.span());
         match stmt {
             Statement::Query(query) => {
                 self.check_query(query);
@@ -112,13 +138,18 @@ impl<'a> TypeResolver<'a> {
                     self.check_expr_recursive(selection);
                 }
             }
+            Statement::Merge {
+                table, on, clauses, ..
+            } => {
+                self.check_merge(table, on, clauses);
+            }
             _ => {}
         }
     }
 
     /// Check types in an INSERT statement
     fn check_insert(&mut self, insert: &Insert) {
-        let table_name = object_name_to_qualified(&insert.table_name);
+        let table_name = self.dialect.qualified_name(&insert.table_name);
         let table_def = match self.catalog.get_table(&table_name) {
             Some(def) => def,
             None => return, // Table not found - already reported by NameResolver
@@ -131,66 +162,216 @@ impl<'a> TypeResolver<'a> {
         } else {
             insert.columns.iter().map(|c| c.value.clone()).collect()
         };
+        // Diagnostics on values go on the target column in the column list,
+        // or on the table name without one
+        let fallback = insert
+            .table_name
+            .0
+            .last()
+            .map(|id| Span::from_sqlparser(&id.span));
+        let column_spans: Vec<Option<Span>> = (0..target_columns.len())
+            .map(|index| {
+                insert
+                    .columns
+                    .get(index)
+                    .map(|c| Span::from_sqlparser(&c.span))
+                    .or(fallback)
+            })
+            .collect();
 
-        // Check VALUES rows
         if let Some(source) = &insert.source {
-            if let SetExpr::Values(Values { rows, .. }) = source.body.as_ref() {
-                for row in rows {
-                    for (i, value_expr) in row.iter().enumerate() {
-                        if i >= target_columns.len() {
-                            break;
-                        }
-                        let col_name = &target_columns[i];
-                        let col_def = match table_def.get_column(col_name) {
-                            Some(def) => def,
-                            None => continue, // Column not found - already reported
-                        };
-
-                        if !col_def.nullable && matches!(value_expr, Expr::Value(Value::Null)) {
-                            let span = Span::from_sqlparser(&value_expr.span());
-                            self.diagnostics.push(
-                                Diagnostic::error(
-                                    DiagnosticKind::PotentialNullViolation,
-                                    format!(
-                                        "Potential NOT NULL violation: column '{}' cannot be assigned NULL",
-                                        col_name
-                                    ),
-                                )
-                                .with_span(span)
-                                .with_help(
-                                    "This column is defined as NOT NULL. Provide a non-NULL value or change the schema constraint.",
-                                ),
-                            );
-                            continue;
+            match source.body.as_ref() {
+                // Check VALUES rows
+                SetExpr::Values(Values { rows, .. }) => {
+                    for row in rows {
+                        for ((col_name, value_expr), column_span) in
+                            target_columns.iter().zip(row).zip(&column_spans)
+                        {
+                            if let Some(col_def) = table_def.get_column(col_name) {
+                                self.check_assigned_value(
+                                    col_name,
+                                    col_def,
+                                    value_expr,
+                                    *column_span,
+                                );
+                            }
+                            // Column not found - already reported
                         }
+                        self.check_constraint_values(table_def, &target_columns, row, fallback);
+                    }
+                }
+                body => {
+                    // INSERT ... SELECT: the scope still holds the SELECT's FROM tables
+                    self.check_query(source);
+                    if let SetExpr::Select(select) = body {
+                        self.check_insert_select(&target_columns, &column_spans, table_def, select);
+                    }
+                }
+            }
+        }
 
-                        let value_type = self.infer_expr_type(value_expr);
-                        if let ExpressionType::Known(vt) = value_type {
-                            let compat = vt.is_compatible_with(&col_def.data_type);
-                            let compat_rev = col_def.data_type.is_compatible_with(&vt);
-                            if compat == TypeCompatibility::ExplicitCast
-                                && compat_rev == TypeCompatibility::ExplicitCast
-                            {
-                                let span = Span::from_sqlparser(&value_expr.span());
-                                self.diagnostics.push(
-                                    Diagnostic::error(
-                                        DiagnosticKind::TypeMismatch,
-                                        format!(
-                                            "Type mismatch: column '{}' expects {}, but got {}",
-                                            col_name,
-                                            col_def.data_type.display_name(),
-                                            vt.display_name()
-                                        ),
-                                    )
-                                    .with_span(span)
-                                    .with_help(
-                                        "Value type is not compatible with the column type. Consider using explicit CAST.",
-                                    ),
-                                );
+        if let Some(OnInsert::OnConflict(OnConflict {
+            action: OnConflictAction::DoUpdate(do_update),
+            ..
+        })) = &insert.on
+        {
+            // DO UPDATE sees the target table (by name or alias) and the
+            // `excluded` pseudo-table holding the proposed row
+            let saved_tables = std::mem::take(&mut self.tables);
+            let target_key = insert.table_alias.as_ref().map_or_else(
+                || {
+                    insert
+                        .table_name
+                        .0
+                        .last()
+                        .map_or_else(String::new, |id| id.value.clone())
+                },
+                |alias| alias.value.clone(),
+            );
+            for key in [target_key, "excluded".to_string()] {
+                self.tables.insert(
+                    key,
+                    TableRef {
+                        table_name: table_name.clone(),
+                        view_columns: None,
+                        derived_columns: None,
+                        wildcard_types: None,
+                    },
+                );
+            }
+            self.check_assignments(table_def, &do_update.assignments);
+            if let Some(selection) = &do_update.selection {
+                self.check_expr_recursive(selection);
+            }
+            self.tables = saved_tables;
+        }
+    }
+
+    /// Check types in a MERGE statement: the ON condition, the WHEN
+    /// predicates, and the values written to the target by UPDATE and INSERT
+    fn check_merge(&mut self, table: &TableFactor, on: &Expr, clauses: &[MergeClause]) {
+        self.check_expr_recursive(on);
+        let table_def = match table {
+            TableFactor::Table { name, .. } => {
+                self.catalog.get_table(&self.dialect.qualified_name(name))
+            }
+            _ => None,
+        };
+        for clause in clauses {
+            if let Some(predicate) = &clause.predicate {
+                self.check_expr_recursive(predicate);
+            }
+            let Some(table_def) = table_def else {
+                continue; // Table not found - already reported by NameResolver
+            };
+            match &clause.action {
+                MergeAction::Update { assignments } => {
+                    self.check_assignments(table_def, assignments);
+                }
+                MergeAction::Insert(MergeInsertExpr {
+                    columns,
+                    kind: MergeInsertKind::Values(Values { rows, .. }),
+                }) => {
+                    let target_columns: Vec<&String> = if columns.is_empty() {
+                        table_def.columns.keys().collect()
+                    } else {
+                        columns.iter().map(|c| &c.value).collect()
+                    };
+                    for row in rows {
+                        for (index, (col_name, value)) in target_columns.iter().zip(row).enumerate()
+                        {
+                            if let Some(col_def) = table_def.get_column(col_name) {
+                                let column_span =
+                                    columns.get(index).map(|c| Span::from_sqlparser(&c.span));
+                                self.check_assigned_value(col_name, col_def, value, column_span);
                             }
                         }
                     }
                 }
+                _ => {}
+            }
+        }
+    }
+
+    /// W1013: literal INSERT values that provably fail a CHECK constraint
+    ///
+    /// Only constraints of the form `column OP literal` (or `literal OP column`)
+    /// are evaluated, against a number or string literal written for that
+    /// column; strings are only compared with `=` and `<>`, and not at all
+    /// for MySQL, whose default collations ignore case.
+    fn check_constraint_values(
+        &mut self,
+        table_def: &TableDef,
+        target_columns: &[String],
+        row: &[Expr],
+        fallback: Option<Span>,
+    ) {
+        for check in &table_def.check_constraints {
+            let Some((column, op, bound)) = simple_check(&check.expression, self.dialect) else {
+                continue;
+            };
+            if self.dialect == SqlDialect::MySQL && matches!(bound, Literal::Text(_)) {
+                continue;
+            }
+            let Some(index) = target_columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(&column))
+            else {
+                continue;
+            };
+            let Some(value_expr) = row.get(index) else {
+                continue;
+            };
+            let Some(value) = Literal::of(value_expr) else {
+                continue; // Not a constant; can't be evaluated statically
+            };
+            if value.satisfies(&op, &bound) != Some(false) {
+                continue;
+            }
+
+            let constraint = match &check.name {
+                Some(name) => format!("CHECK constraint '{}' ({})", name, check.expression),
+                None => format!("CHECK ({})", check.expression),
+            };
+            let span = fallback.map(|fallback| span_or(value_expr, fallback));
+            let mut diag = Diagnostic::warning(
+                DiagnosticKind::CheckConstraintViolation,
+                format!(
+                    "Value {} for column '{}' violates {}",
+                    value_expr, column, constraint
+                ),
+            )
+            .with_help("The database rejects this row when it is inserted");
+            if let Some(span) = span {
+                diag = diag.with_span(span);
+            }
+            self.diagnostics.push(diag);
+        }
+    }
+
+    /// Check each SELECT-list expression of an INSERT ... SELECT against its
+    /// target column
+    ///
+    /// Skipped when the list has a wildcard, which makes the pairing unclear.
+    fn check_insert_select(
+        &mut self,
+        target_columns: &[String],
+        column_spans: &[Option<Span>],
+        table_def: &TableDef,
+        select: &Select,
+    ) {
+        let mut exprs = Vec::with_capacity(select.projection.len());
+        for item in &select.projection {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    exprs.push(expr)
+                }
+                _ => return,
+            }
+        }
+        for ((col_name, expr), column_span) in target_columns.iter().zip(exprs).zip(column_spans) {
+            if let Some(col_def) = table_def.get_column(col_name) {
+                self.check_assigned_value(col_name, col_def, expr, *column_span);
             }
         }
     }
@@ -202,77 +383,181 @@ impl<'a> TypeResolver<'a> {
         assignments: &[sqlparser::ast::Assignment],
     ) {
         let table_name = match &table.relation {
-            TableFactor::Table { name, .. } => object_name_to_qualified(name),
+            TableFactor::Table { name, .. } => self.dialect.qualified_name(name),
             _ => return,
         };
         let table_def = match self.catalog.get_table(&table_name) {
             Some(def) => def,
             None => return, // Table not found - already reported by NameResolver
         };
+        self.check_assignments(table_def, assignments);
+    }
 
+    /// Check `SET column = value` assignments against the column types
+    fn check_assignments(
+        &mut self,
+        table_def: &TableDef,
+        assignments: &[sqlparser::ast::Assignment],
+    ) {
         for assignment in assignments {
-            let col_name = match &assignment.target {
+            let column = match &assignment.target {
                 AssignmentTarget::ColumnName(name) => match name.0.last() {
-                    Some(ident) => ident.value.clone(),
+                    Some(ident) => ident,
                     None => continue,
                 },
                 AssignmentTarget::Tuple(_) => continue, // Skip tuple assignments
             };
 
-            let col_def = match table_def.get_column(&col_name) {
+            let col_def = match table_def.get_column(&column.value) {
                 Some(def) => def,
                 None => continue, // Column not found - already reported
             };
+            self.check_assigned_value(
+                &column.value,
+                col_def,
+                &assignment.value,
+                Some(Span::from_sqlparser(&column.span)),
+            );
+        }
+    }
+
+    /// Check a value written to a column by INSERT or UPDATE
+    ///
+    /// `column_span` is where the column is named, for values without a span.
+    fn check_assigned_value(
+        &mut self,
+        col_name: &str,
+        col_def: &ColumnDef,
+        value: &Expr,
+        column_span: Option<Span>,
+    ) {
+        if !col_def.nullable && matches!(value, Expr::Value(Value::Null)) {
+            let span = Span::from_sqlparser(&value.span());
+            self.diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticKind::PotentialNullViolation,
+                    format!(
+                        "Potential NOT NULL violation: column '{}' cannot be assigned NULL",
+                        col_name
+                    ),
+                )
+                .with_span(span)
+                .with_help(
+                    "This column is defined as NOT NULL. Provide a non-NULL value or change the schema constraint.",
+                ),
+            );
+            return;
+        }
+
+        // A string literal is read as a label of the column's enum type
+        if let (Some(enum_def), Expr::Value(Value::SingleQuotedString(label))) =
+            (self.enum_type(&col_def.data_type), value)
+        {
+            if !enum_def.values.contains(label) {
+                let mut diag = invalid_enum_value(col_name, enum_def, label);
+                if let Some(span) = column_span {
+                    diag = diag.with_span(span);
+                }
+                self.diagnostics.push(diag);
+            }
+            return;
+        }
 
-            if !col_def.nullable && matches!(&assignment.value, Expr::Value(Value::Null)) {
-                let span = Span::from_sqlparser(&assignment.value.span());
+        let value_type = self.infer_expr_type(value);
+        if let ExpressionType::Known(vt) = value_type {
+            let compat = vt.is_compatible_with(&col_def.data_type);
+            let compat_rev = col_def.data_type.is_compatible_with(&vt);
+            if compat == TypeCompatibility::ExplicitCast
+                && compat_rev == TypeCompatibility::ExplicitCast
+            {
+                let span = Span::from_sqlparser(&value.span());
                 self.diagnostics.push(
                     Diagnostic::error(
-                        DiagnosticKind::PotentialNullViolation,
+                        DiagnosticKind::TypeMismatch,
                         format!(
-                            "Potential NOT NULL violation: column '{}' cannot be assigned NULL",
-                            col_name
+                            "Type mismatch: column '{}' expects {}, but got {}",
+                            col_name,
+                            col_def.data_type.display_name(),
+                            vt.display_name()
                         ),
                     )
                     .with_span(span)
                     .with_help(
-                        "This column is defined as NOT NULL. Provide a non-NULL value or change the schema constraint.",
+                        "Value type is not compatible with the column type. Consider using explicit CAST.",
                     ),
                 );
-                continue;
-            }
-
-            let value_type = self.infer_expr_type(&assignment.value);
-            if let ExpressionType::Known(vt) = value_type {
-                let compat = vt.is_compatible_with(&col_def.data_type);
-                let compat_rev = col_def.data_type.is_compatible_with(&vt);
-                if compat == TypeCompatibility::ExplicitCast
-                    && compat_rev == TypeCompatibility::ExplicitCast
-                {
-                    let span = Span::from_sqlparser(&assignment.value.span());
-                    self.diagnostics.push(
-                        Diagnostic::error(
-                            DiagnosticKind::TypeMismatch,
-                            format!(
-                                "Type mismatch: column '{}' expects {}, but got {}",
-                                col_name,
-                                col_def.data_type.display_name(),
-                                vt.display_name()
-                            ),
-                        )
-                        .with_span(span)
-                        .with_help(
-                            "Value type is not compatible with the column type. Consider using explicit CAST.",
-                        ),
-                    );
-                }
             }
         }
     }
 
+    /// The enum a column type refers to, if it's a `CREATE TYPE ... AS ENUM` type
+    fn enum_type(&self, ty: &SqlType) -> Option<&'a EnumTypeDef> {
+        let SqlType::Custom(name) = ty else {
+            return None;
+        };
+        self.catalog
+            .get_enum(name)
+            .or_else(|| self.catalog.get_enum(&name.to_lowercase()))
+    }
+
+    /// The type of a field of a composite-typed value
+    fn composite_field_type(&self, ty: ExpressionType, field: &str) -> ExpressionType {
+        let ExpressionType::Known(ty) = ty else {
+            return ExpressionType::Unknown;
+        };
+        self.catalog
+            .composite_type(&ty)
+            .and_then(|composite| composite.get_field(field))
+            .map_or(ExpressionType::Unknown, |f| {
+                ExpressionType::Known(f.data_type.clone())
+            })
+    }
+
     /// Check types in a query
     fn check_query(&mut self, query: &Query) {
         self.check_set_expr(&query.body);
+        let query_span = Span::from_sqlparser(&query.span());
+        // `LIMIT ALL` parses as no limit
+        if let Some(limit) = &query.limit {
+            self.check_row_count("LIMIT", limit, query_span);
+        }
+        if let Some(offset) = &query.offset {
+            self.check_row_count("OFFSET", &offset.value, query_span);
+        }
+    }
+
+    /// A row count (LIMIT, OFFSET) must be a non-negative integer
+    ///
+    /// SQLite reads a negative LIMIT as no limit (and a negative OFFSET as
+    /// none), so only the type is checked there.
+    fn check_row_count(&mut self, clause: &str, expr: &Expr, fallback: Span) {
+        let problem = match expr {
+            // Number literals all infer as integer, so look at how they're written
+            Expr::Value(Value::Number(n, _)) if n.contains(['.', 'e', 'E']) => {
+                format!("got {}", n)
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Minus,
+                expr: inner,
+            } if matches!(inner.as_ref(), Expr::Value(Value::Number(..)))
+                && self.dialect != SqlDialect::SQLite =>
+            {
+                format!("got {}", expr)
+            }
+            _ => match self.infer_expr_type(expr) {
+                ExpressionType::Known(ty) if !self.is_integer_type(&ty) => {
+                    format!("got {}", ty.display_name())
+                }
+                _ => return,
+            },
+        };
+        self.diagnostics.push(
+            Diagnostic::error(
+                DiagnosticKind::TypeMismatch,
+                format!("{} must be a non-negative integer, but {}", clause, problem),
+            )
+            .with_span(span_or(expr, fallback)),
+        );
     }
 
     /// Check types in a set expression (SELECT, UNION, INTERSECT, EXCEPT, ...)
@@ -303,7 +588,7 @@ impl<'a> TypeResolver<'a> {
         if left_types.len() != right_types.len() {
             self.diagnostics.push(
                 Diagnostic::error(
-                    DiagnosticKind::TypeMismatch,
+                    DiagnosticKind::ColumnCountMismatch,
                     format!(
                         "Set operation column count mismatch: left has {}, right has {}",
                         left_types.len(),
@@ -316,20 +601,12 @@ impl<'a> TypeResolver<'a> {
             return;
         }
 
-        for (idx, (left_ty, right_ty)) in left_types
-            .into_iter()
-            .zip(right_types.into_iter())
-            .enumerate()
-        {
+        for (idx, (left_ty, right_ty)) in left_types.into_iter().zip(right_types).enumerate() {
             let (ExpressionType::Known(lt), ExpressionType::Known(rt)) = (left_ty, right_ty) else {
                 continue;
             };
 
-            let compat_lr = lt.is_compatible_with(&rt);
-            let compat_rl = rt.is_compatible_with(&lt);
-            if compat_lr == TypeCompatibility::ExplicitCast
-                && compat_rl == TypeCompatibility::ExplicitCast
-            {
+            if SqlType::unify(&[lt.clone(), rt.clone()]).is_none() {
                 self.diagnostics.push(
                     Diagnostic::error(
                         DiagnosticKind::TypeMismatch,
@@ -363,7 +640,7 @@ impl<'a> TypeResolver<'a> {
     }
 
     /// Infer projection types for a SELECT list.
-    /// Returns None when wildcard expansion would be required.
+    /// Returns None when a wildcard can't be expanded.
     fn infer_select_projection_types(&mut self, select: &Select) -> Option<Vec<ExpressionType>> {
         let mut types = Vec::with_capacity(select.projection.len());
         for item in &select.projection {
@@ -372,13 +649,57 @@ impl<'a> TypeResolver<'a> {
                 | sqlparser::ast::SelectItem::ExprWithAlias { expr, .. } => {
                     types.push(self.infer_expr_type(expr));
                 }
-                sqlparser::ast::SelectItem::Wildcard(_)
-                | sqlparser::ast::SelectItem::QualifiedWildcard(_, _) => return None,
+                sqlparser::ast::SelectItem::QualifiedWildcard(name, _) => {
+                    let table_ref = self.tables.get(name.0.last()?.value.as_str())?;
+                    types.extend(table_ref.wildcard_types.clone()?);
+                }
+                sqlparser::ast::SelectItem::Wildcard(_) => {
+                    types.extend(self.expand_wildcard(select)?);
+                }
             }
         }
         Some(types)
     }
 
+    /// Column types produced by an unqualified `*`: the columns of every FROM
+    /// item in order, or None when they can't all be listed
+    fn expand_wildcard(&self, select: &Select) -> Option<Vec<ExpressionType>> {
+        let mut types = Vec::new();
+        for table_with_joins in &select.from {
+            // USING and NATURAL joins merge the shared columns
+            if table_with_joins
+                .joins
+                .iter()
+                .any(|join| merges_join_columns(&join.join_operator))
+            {
+                return None;
+            }
+            let factors = std::iter::once(&table_with_joins.relation)
+                .chain(table_with_joins.joins.iter().map(|join| &join.relation));
+            for factor in factors {
+                let (key, table) = match factor {
+                    TableFactor::Table { name, alias, .. } => {
+                        let table = name.0.last()?;
+                        (alias.as_ref().map_or(table, |a| &a.name), Some(table))
+                    }
+                    TableFactor::Derived {
+                        alias: Some(alias), ..
+                    } => (&alias.name, None),
+                    _ => return None,
+                };
+                let table_ref = self.tables.get(&key.value)?;
+                // Set operation branches share the scope; make sure the key
+                // wasn't taken over by a table of another branch
+                if table.is_some_and(|t| !t.value.eq_ignore_ascii_case(&table_ref.table_name.name))
+                {
+                    return None;
+                }
+                types.extend(table_ref.wildcard_types.clone()?);
+            }
+        }
+        (!types.is_empty()).then_some(types)
+    }
+
     /// Check types in a SELECT statement
     fn check_select(&mut self, select: &Select) {
         // Check JOIN conditions
@@ -391,9 +712,15 @@ impl<'a> TypeResolver<'a> {
         // Check SELECT projection
         for select_item in &select.projection {
             match select_item {
-                sqlparser::ast::SelectItem::UnnamedExpr(expr)
-                | sqlparser::ast::SelectItem::ExprWithAlias { expr, .. } => {
+                sqlparser::ast::SelectItem::UnnamedExpr(expr) => {
+                    self.check_expr_recursive(expr);
+                }
+                sqlparser::ast::SelectItem::ExprWithAlias { expr, alias } => {
+                    // The alias locates an expression made of literals only
+                    let outer = self.enclosing_span;
+                    self.enclosing_span = Span::from_sqlparser(&alias.span);
                     self.check_expr_recursive(expr);
+                    self.enclosing_span = outer;
                 }
                 sqlparser::ast::SelectItem::QualifiedWildcard(_, _)
                 | sqlparser::ast::SelectItem::Wildcard(_) => {
@@ -453,21 +780,22 @@ impl<'a> TypeResolver<'a> {
                         if compat_lr == TypeCompatibility::ExplicitCast
                             && compat_rl == TypeCompatibility::ExplicitCast
                         {
-                            let span = Span::from_sqlparser(&left.span());
-                            self.diagnostics.push(
-                                Diagnostic::error(
-                                    DiagnosticKind::JoinTypeMismatch,
-                                    format!(
-                                        "JOIN condition type mismatch: {} vs {}",
-                                        lt.display_name(),
-                                        rt.display_name()
-                                    ),
-                                )
-                                .with_span(span)
-                                .with_help(
-                                    "JOIN condition should compare compatible types. Consider using explicit CAST.",
-                                ),
-                            );
+                            let diagnostic = column_comparison_mismatch(left, &lt, right, &rt)
+                                .unwrap_or_else(|| {
+                                    Diagnostic::error(
+                                        DiagnosticKind::JoinTypeMismatch,
+                                        format!(
+                                            "JOIN condition type mismatch: {} vs {}",
+                                            lt.display_name(),
+                                            rt.display_name()
+                                        ),
+                                    )
+                                    .with_span(Span::from_sqlparser(&left.span()))
+                                    .with_help(
+                                        "JOIN condition should compare compatible types. Consider using explicit CAST.",
+                                    )
+                                });
+                            self.diagnostics.push(diagnostic);
                         }
                     }
                     // Recursively check subexpressions
@@ -503,6 +831,18 @@ impl<'a> TypeResolver<'a> {
 
     /// Recursively check types in an expression
     fn check_expr_recursive(&mut self, expr: &Expr) {
+        let outer = self.enclosing_span;
+        let span = Span::from_sqlparser(&expr.span());
+        if span.line > 0 {
+            self.enclosing_span = span;
+        }
+        self.check_expr_node(expr);
+        self.enclosing_span = outer;
+    }
+
+    /// Check types in one expression and, through `check_expr_recursive`,
+    /// its subexpressions
+    fn check_expr_node(&mut self, expr: &Expr) {
         match expr {
             Expr::BinaryOp { left, op, right } => {
                 // Check the binary operation
@@ -517,6 +857,10 @@ impl<'a> TypeResolver<'a> {
             Expr::UnaryOp { expr, .. } => {
                 self.check_expr_recursive(expr);
             }
+            Expr::AnyOp { left, right, .. } | Expr::AllOp { left, right, .. } => {
+                self.check_expr_recursive(left);
+                self.check_expr_recursive(right);
+            }
             Expr::InList { expr, list, .. } => {
                 self.check_expr_recursive(expr);
                 for item in list {
@@ -549,12 +893,210 @@ impl<'a> TypeResolver<'a> {
                     self.check_expr_recursive(else_res);
                 }
             }
+            Expr::Like {
+                expr: operand,
+                pattern,
+                escape_char,
+                ..
+            }
+            | Expr::ILike {
+                expr: operand,
+                pattern,
+                escape_char,
+                ..
+            } => {
+                self.check_like("LIKE", expr, operand, pattern, escape_char.as_deref());
+                self.check_expr_recursive(operand);
+                self.check_expr_recursive(pattern);
+            }
+            Expr::SimilarTo {
+                expr: operand,
+                pattern,
+                escape_char,
+                ..
+            } => {
+                self.check_like("SIMILAR TO", expr, operand, pattern, escape_char.as_deref());
+                self.check_expr_recursive(operand);
+                self.check_expr_recursive(pattern);
+            }
+            Expr::Array(array) => {
+                self.check_array_elements(array);
+                for elem in &array.elem {
+                    self.check_expr_recursive(elem);
+                }
+            }
+            Expr::Subscript { expr, subscript } => {
+                self.check_subscript(expr);
+                self.check_expr_recursive(expr);
+                if let Subscript::Index { index } = subscript.as_ref() {
+                    self.check_expr_recursive(index);
+                }
+            }
+            Expr::Function(func) => {
+                let args = unnamed_args(func);
+                let func_name = func.name.to_string().to_uppercase();
+                let name = func_name.rsplit('.').next().unwrap_or(&func_name);
+                if matches!(name, "GREATEST" | "LEAST") {
+                    self.check_common_type(
+                        &format!("{} arguments", name),
+                        &args,
+                        "Cast the arguments to a common type.",
+                        Span::from_sqlparser(&func.span()),
+                    );
+                }
+                for arg in args {
+                    self.check_expr_recursive(arg);
+                }
+            }
             _ => {
                 // Base case: leaf expressions like identifiers, literals
             }
         }
     }
 
+    /// Check that the elements of an ARRAY[...] constructor share a type
+    fn check_array_elements(&mut self, array: &Array) {
+        self.check_common_type(
+            "ARRAY elements",
+            &array.elem.iter().collect::<Vec<_>>(),
+            "Cast the elements to a common type.",
+            span_or(array, self.enclosing_span),
+        );
+    }
+
+    /// Check that values which must resolve to one type (array elements,
+    /// GREATEST/LEAST arguments) can be unified
+    ///
+    /// `fallback` marks a value that has no span of its own (see [`span_or`]).
+    fn check_common_type(&mut self, what: &str, exprs: &[&Expr], help: &str, fallback: Span) {
+        let mut common: Option<SqlType> = None;
+        for elem in exprs {
+            let ExpressionType::Known(ty) = self.infer_expr_type(elem) else {
+                continue;
+            };
+            let Some(acc) = common else {
+                common = Some(ty);
+                continue;
+            };
+            match SqlType::unify(&[acc.clone(), ty.clone()]) {
+                Some(unified) => common = Some(unified),
+                None => {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            DiagnosticKind::TypeMismatch,
+                            format!(
+                                "{} have incompatible types: {} and {}",
+                                what,
+                                acc.display_name(),
+                                ty.display_name()
+                            ),
+                        )
+                        .with_span(span_or(*elem, fallback))
+                        .with_help(help),
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// E0011: subscript on a column whose declared type isn't an array
+    ///
+    /// json/jsonb (PostgreSQL 14+) and user-defined types may support
+    /// subscripting, so only built-in scalar types are reported.
+    fn check_subscript(&mut self, expr: &Expr) {
+        if !matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_)) {
+            return;
+        }
+        let ExpressionType::Known(ty) = self.infer_expr_type(expr) else {
+            return;
+        };
+        if matches!(
+            ty,
+            SqlType::Array(_)
+                | SqlType::Json
+                | SqlType::Jsonb
+                | SqlType::Custom(_)
+                | SqlType::Unknown
+        ) {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::error(
+                DiagnosticKind::InvalidSubscript,
+                format!(
+                    "Cannot subscript {}: it is {}, not an array",
+                    expr,
+                    ty.display_name()
+                ),
+            )
+            .with_span(Span::from_sqlparser(&expr.span())),
+        );
+    }
+
+    /// Check a LIKE / ILIKE / SIMILAR TO: neither operand may be a number,
+    /// boolean or date/time, and ESCAPE must be a single character
+    ///
+    /// The pattern may be any expression, e.g. a column holding patterns.
+    /// Other types are left alone: PostgreSQL matches citext, bytea and many
+    /// domain or extension types with LIKE.
+    fn check_like(
+        &mut self,
+        operator: &str,
+        like: &Expr,
+        expr: &Expr,
+        pattern: &Expr,
+        escape_char: Option<&str>,
+    ) {
+        // MySQL and SQLite implicitly convert non-text operands of LIKE
+        let operands: &[&Expr] = if self.dialect == SqlDialect::PostgreSQL {
+            &[expr, pattern]
+        } else {
+            &[]
+        };
+        let like_span = Span::from_sqlparser(&like.span());
+        for &operand in operands {
+            if let ExpressionType::Known(ty) = self.infer_expr_type(operand) {
+                if self.is_numeric_type(&ty)
+                    || is_temporal_type(&ty)
+                    || matches!(ty, SqlType::Boolean)
+                {
+                    let span = span_or(operand, like_span);
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            DiagnosticKind::TypeMismatch,
+                            format!(
+                                "{} requires text operands, but got {}",
+                                operator,
+                                ty.display_name()
+                            ),
+                        )
+                        .with_span(span)
+                        .with_help("Cast the operand to text, e.g. CAST(col AS TEXT)"),
+                    );
+                }
+            }
+        }
+
+        // An empty escape disables escaping; anything longer than one character is invalid.
+        // The ESCAPE string has no position of its own, so the warning covers the LIKE.
+        if let Some(escape) = escape_char {
+            if escape.chars().count() > 1 {
+                self.diagnostics.push(
+                    Diagnostic::warning(
+                        DiagnosticKind::InvalidLikeEscape,
+                        format!(
+                            "{} ESCAPE must be a single character, but got '{}'",
+                            operator, escape
+                        ),
+                    )
+                    .with_span(like_span)
+                    .with_help("Use a one-character escape such as ESCAPE '\\'"),
+                );
+            }
+        }
+    }
+
     /// Check type compatibility in a binary operation
     fn check_binary_op(&mut self, left: &Expr, op: &BinaryOperator, right: &Expr) {
         let left_type = self.infer_expr_type(left);
@@ -579,6 +1121,11 @@ impl<'a> TypeResolver<'a> {
                         && compat_rl == TypeCompatibility::ExplicitCast
                     {
                         // Types are not implicitly compatible in either direction
+                        if let Some(diagnostic) = column_comparison_mismatch(left, &lt, right, &rt)
+                        {
+                            self.diagnostics.push(diagnostic);
+                            return;
+                        }
                         let span = Span::from_sqlparser(&left.span());
                         self.diagnostics.push(
                             Diagnostic::error(
@@ -600,6 +1147,12 @@ impl<'a> TypeResolver<'a> {
                 | BinaryOperator::Multiply
                 | BinaryOperator::Divide
                 | BinaryOperator::Modulo => {
+                    // Date/time arithmetic (date + 1, ts - ts, ts + interval) is valid
+                    if matches!(op, BinaryOperator::Plus | BinaryOperator::Minus)
+                        && (is_temporal_type(&lt) || is_temporal_type(&rt))
+                    {
+                        return;
+                    }
                     // Check if both types are numeric
                     if !self.is_numeric_type(&lt) {
                         let span = Span::from_sqlparser(&left.span());
@@ -628,6 +1181,29 @@ impl<'a> TypeResolver<'a> {
                         );
                     }
                 }
+                // JSON access operators; MySQL and SQLite also read JSON stored as text
+                BinaryOperator::Arrow
+                | BinaryOperator::LongArrow
+                | BinaryOperator::HashArrow
+                | BinaryOperator::HashLongArrow
+                    if self.dialect == SqlDialect::PostgreSQL
+                        && !matches!(
+                            lt,
+                            SqlType::Json | SqlType::Jsonb | SqlType::Custom(_) | SqlType::Unknown
+                        ) =>
+                {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            DiagnosticKind::TypeMismatch,
+                            format!(
+                                "Operator {} requires json or jsonb, but got {}",
+                                op,
+                                lt.display_name()
+                            ),
+                        )
+                        .with_span(Span::from_sqlparser(&left.span())),
+                    );
+                }
                 // String concatenation operator
                 BinaryOperator::StringConcat => {
                     // PostgreSQL || operator - typically used with strings
@@ -640,6 +1216,14 @@ impl<'a> TypeResolver<'a> {
         }
     }
 
+    /// Check if a type is a character string type
+    fn is_text_type(&self, sql_type: &SqlType) -> bool {
+        matches!(
+            sql_type,
+            SqlType::Char { .. } | SqlType::Varchar { .. } | SqlType::Text
+        )
+    }
+
     /// Check if a type is numeric
     fn is_numeric_type(&self, sql_type: &SqlType) -> bool {
         matches!(
@@ -655,6 +1239,18 @@ impl<'a> TypeResolver<'a> {
         )
     }
 
+    /// Check if a type is an integer type
+    fn is_integer_type(&self, sql_type: &SqlType) -> bool {
+        matches!(
+            sql_type,
+            SqlType::TinyInt
+                | SqlType::SmallInt
+                | SqlType::MediumInt
+                | SqlType::Integer
+                | SqlType::BigInt
+        )
+    }
+
     /// Consume the resolver and return collected diagnostics
     pub fn into_diagnostics(self) -> Vec<Diagnostic> {
         self.diagnostics
@@ -666,9 +1262,16 @@ impl<'a> TypeResolver<'a> {
             Expr::Value(value) => self.infer_literal_type(value),
             Expr::Identifier(ident) => self.infer_column_type_from_ident(&ident.value),
             Expr::CompoundIdentifier(parts) => {
-                if parts.len() == 2 {
+                let qualifier_is_column = parts.len() == 2
+                    && !self.tables.contains_key(&parts[0].value)
+                    && !self.tables.contains_key(&parts[0].value.to_lowercase());
+                if parts.len() == 2 && !qualifier_is_column {
                     // table.column
                     self.infer_column_type_qualified(&parts[0].value, &parts[1].value)
+                } else if qualifier_is_column {
+                    // column.field of a composite-typed column
+                    let column_type = self.infer_column_type_from_ident(&parts[0].value);
+                    self.composite_field_type(column_type, &parts[1].value)
                 } else {
                     // More complex identifier (schema.table.column)
                     ExpressionType::Unknown
@@ -678,6 +1281,10 @@ impl<'a> TypeResolver<'a> {
                 // Recursively infer type of nested expression
                 self.infer_expr_type(inner)
             }
+            Expr::CompositeAccess { expr, key } => {
+                let column_type = self.infer_expr_type(expr);
+                self.composite_field_type(column_type, &key.value)
+            }
             Expr::BinaryOp { left, op, right } => {
                 // Infer result type of binary operation
                 self.infer_binary_op_result_type(left, op, right)
@@ -691,6 +1298,16 @@ impl<'a> TypeResolver<'a> {
                 }
             }
             Expr::Function(func) => self.infer_function_return_type(func),
+            Expr::Array(array) => self.infer_array_type(array),
+            Expr::Subscript { expr, subscript } => match self.infer_expr_type(expr) {
+                // An index gives the element, a slice another array
+                ExpressionType::Known(SqlType::Array(elem)) => match subscript.as_ref() {
+                    Subscript::Index { .. } if *elem == SqlType::Unknown => ExpressionType::Unknown,
+                    Subscript::Index { .. } => ExpressionType::Known(*elem),
+                    Subscript::Slice { .. } => ExpressionType::Known(SqlType::Array(elem)),
+                },
+                _ => ExpressionType::Unknown,
+            },
             // TODO: Add support for more expression types:
             // - Expr::Case => Infer from THEN/ELSE branches (medium, 1-1.5 hours, ROI 20%)
             // - Expr::Subquery => Infer from SELECT projection (complex, 4-6 hours, ROI 15%)
@@ -698,6 +1315,26 @@ impl<'a> TypeResolver<'a> {
         }
     }
 
+    /// Infer the type of an ARRAY[...] constructor from its elements' common type
+    fn infer_array_type(&mut self, array: &Array) -> ExpressionType {
+        match self.infer_common_type(&array.elem.iter().collect::<Vec<_>>()) {
+            ExpressionType::Known(elem) => ExpressionType::Known(SqlType::Array(Box::new(elem))),
+            ExpressionType::Unknown => ExpressionType::Unknown,
+        }
+    }
+
+    /// Infer the type values unify to, ignoring those of unknown type
+    fn infer_common_type(&mut self, exprs: &[&Expr]) -> ExpressionType {
+        let types: Vec<SqlType> = exprs
+            .iter()
+            .filter_map(|expr| match self.infer_expr_type(expr) {
+                ExpressionType::Known(ty) => Some(ty),
+                ExpressionType::Unknown => None,
+            })
+            .collect();
+        SqlType::unify(&types).map_or(ExpressionType::Unknown, ExpressionType::Known)
+    }
+
     /// Infer the return type of a SQL function
     fn infer_function_return_type(&mut self, func: &sqlparser::ast::Function) -> ExpressionType {
         let func_name = func.name.to_string().to_uppercase();
@@ -743,15 +1380,42 @@ impl<'a> TypeResolver<'a> {
                 precision: None,
                 with_timezone: true,
             }),
+            "CLOCK_TIMESTAMP" | "STATEMENT_TIMESTAMP" | "TRANSACTION_TIMESTAMP" => {
+                ExpressionType::Known(SqlType::Timestamp {
+                    precision: None,
+                    with_timezone: true,
+                })
+            }
             "CURRENT_DATE" => ExpressionType::Known(SqlType::Date),
             "CURRENT_TIME" => ExpressionType::Known(SqlType::Time {
                 precision: None,
                 with_timezone: true,
             }),
 
-            // Type casting functions
+            "GEN_RANDOM_UUID" | "UUID_GENERATE_V4" => ExpressionType::Known(SqlType::Uuid),
+
+            // Set-returning functions: in a SELECT list they expand into one row per
+            // element, so the output column has the element type
+            "GENERATE_SERIES" => self.infer_first_arg_type(func),
+            "GENERATE_SUBSCRIPTS" => ExpressionType::Known(SqlType::Integer),
+            "UNNEST" => match self.infer_first_arg_type(func) {
+                ExpressionType::Known(SqlType::Array(elem)) if *elem != SqlType::Unknown => {
+                    ExpressionType::Known(*elem)
+                }
+                _ => ExpressionType::Unknown,
+            },
+            "JSON_ARRAY_ELEMENTS" => ExpressionType::Known(SqlType::Json),
+            "JSONB_ARRAY_ELEMENTS" => ExpressionType::Known(SqlType::Jsonb),
+            "JSON_ARRAY_ELEMENTS_TEXT"
+            | "JSONB_ARRAY_ELEMENTS_TEXT"
+            | "JSON_OBJECT_KEYS"
+            | "JSONB_OBJECT_KEYS"
+            | "REGEXP_SPLIT_TO_TABLE"
+            | "STRING_TO_TABLE" => ExpressionType::Known(SqlType::Text),
+
+            // NULLIF returns its first argument (or NULL)
             "COALESCE" | "NULLIF" | "IFNULL" => self.infer_first_arg_type(func),
-            "GREATEST" | "LEAST" => self.infer_first_arg_type(func),
+            "GREATEST" | "LEAST" => self.infer_common_type(&unnamed_args(func)),
 
             _ => ExpressionType::Unknown,
         }
@@ -806,6 +1470,22 @@ impl<'a> TypeResolver<'a> {
         let left_type = self.infer_expr_type(left);
         let right_type = self.infer_expr_type(right);
 
+        match (op, &left_type) {
+            // SQLite's `->>` returns the SQL value stored at the path, of any type
+            (BinaryOperator::LongArrow, _) if self.dialect == SqlDialect::SQLite => {
+                return ExpressionType::Unknown;
+            }
+            // `->>` and `#>>` extract text; `->` and `#>` keep the JSON type
+            (BinaryOperator::LongArrow | BinaryOperator::HashLongArrow, _) => {
+                return ExpressionType::Known(SqlType::Text);
+            }
+            (
+                BinaryOperator::Arrow | BinaryOperator::HashArrow,
+                ExpressionType::Known(ty @ (SqlType::Json | SqlType::Jsonb)),
+            ) => return ExpressionType::Known(ty.clone()),
+            _ => {}
+        }
+
         match (left_type, right_type) {
             (ExpressionType::Known(lt), ExpressionType::Known(rt)) => {
                 match op {
@@ -816,13 +1496,30 @@ impl<'a> TypeResolver<'a> {
                     | BinaryOperator::Divide
                     | BinaryOperator::Modulo => {
                         if self.is_numeric_type(&lt) && self.is_numeric_type(&rt) {
-                            // Return the "larger" type (simplified)
-                            // In reality, type promotion rules are more complex
-                            ExpressionType::Known(lt)
+                            // Operands widen to their common type: integer * numeric is numeric
+                            SqlType::unify(&[lt, rt])
+                                .map_or(ExpressionType::Unknown, ExpressionType::Known)
                         } else {
                             ExpressionType::Unknown
                         }
                     }
+                    // MySQL reads `||` as a logical OR (unless PIPES_AS_CONCAT is set)
+                    BinaryOperator::StringConcat if self.dialect == SqlDialect::MySQL => {
+                        ExpressionType::Known(SqlType::Boolean)
+                    }
+                    // Array concatenation (array || array, array || element) keeps the array type
+                    BinaryOperator::StringConcat if matches!(lt, SqlType::Array(_)) => {
+                        ExpressionType::Known(lt)
+                    }
+                    BinaryOperator::StringConcat if matches!(rt, SqlType::Array(_)) => {
+                        ExpressionType::Known(rt)
+                    }
+                    // Otherwise `||` concatenates to text when either side is a string
+                    BinaryOperator::StringConcat
+                        if self.is_text_type(&lt) || self.is_text_type(&rt) =>
+                    {
+                        ExpressionType::Known(SqlType::Text)
+                    }
                     // Comparison operators return boolean
                     BinaryOperator::Eq
                     | BinaryOperator::NotEq
@@ -857,6 +1554,7 @@ impl<'a> TypeResolver<'a> {
                 // NULL can be any type (compatible with everything)
                 ExpressionType::Unknown
             }
+            // Also bind parameters ($1, ?, :name): the driver decides their type
             _ => ExpressionType::Unknown,
         }
     }
@@ -897,8 +1595,12 @@ impl<'a> TypeResolver<'a> {
 
     /// Infer type from a qualified column identifier (table.column)
     fn infer_column_type_qualified(&self, table_name: &str, col_name: &str) -> ExpressionType {
-        // Look up table in scope
-        if let Some(table_ref) = self.tables.get(table_name) {
+        // Look up table in scope, also by the folded name (`EXCLUDED`)
+        let table_ref = self
+            .tables
+            .get(table_name)
+            .or_else(|| self.tables.get(&table_name.to_lowercase()));
+        if let Some(table_ref) = table_ref {
             // Check if this is a derived table or view
             if table_ref.derived_columns.is_some() || table_ref.view_columns.is_some() {
                 // We can't infer types for derived tables or views yet
@@ -917,21 +1619,233 @@ impl<'a> TypeResolver<'a> {
     }
 }
 
-/// Convert sqlparser ObjectName to our QualifiedName
-fn object_name_to_qualified(name: &sqlparser::ast::ObjectName) -> QualifiedName {
-    match name.0.as_slice() {
-        [table] => QualifiedName::new(&table.value),
-        [schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        [_catalog, schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        _ => QualifiedName::new(name.to_string()),
+/// Whether a type takes part in date/time arithmetic
+fn is_temporal_type(sql_type: &SqlType) -> bool {
+    matches!(
+        sql_type,
+        SqlType::Date | SqlType::Time { .. } | SqlType::Timestamp { .. } | SqlType::Interval
+    )
+}
+
+/// E0012 for a label missing from an enum, suggesting the closest label if any
+fn invalid_enum_value(col_name: &str, enum_def: &EnumTypeDef, label: &str) -> Diagnostic {
+    let diag = Diagnostic::error(
+        DiagnosticKind::InvalidEnumValue,
+        format!(
+            "Invalid value '{}' for column '{}' of enum type {}",
+            label, col_name, enum_def.name
+        ),
+    );
+    let closest = enum_def
+        .values
+        .iter()
+        .map(|candidate| (levenshtein_distance(label, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance);
+    match closest {
+        Some((_, candidate)) => diag.with_help(format!("Did you mean '{}'?", candidate)),
+        None => diag.with_help(format!(
+            "Allowed values: {}",
+            enum_def
+                .values
+                .iter()
+                .map(|v| format!("'{}'", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// E0015 for a comparison of two columns whose types don't match, or None
+/// when either side is not a plain column reference
+///
+/// Comparing two columns usually means a wrong join key or a mixed-up
+/// column, which deserves its own code apart from column-vs-literal mistakes.
+fn column_comparison_mismatch(
+    left: &Expr,
+    left_type: &SqlType,
+    right: &Expr,
+    right_type: &SqlType,
+) -> Option<Diagnostic> {
+    fn column_ref(expr: &Expr) -> Option<&Expr> {
+        match expr {
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_) => Some(expr),
+            Expr::Nested(inner) => column_ref(inner),
+            _ => None,
+        }
+    }
+    let (left_col, right_col) = (column_ref(left)?, column_ref(right)?);
+    Some(
+        Diagnostic::error(
+            DiagnosticKind::ColumnTypeMismatch,
+            format!(
+                "Column type mismatch: {} is {} but {} is {}",
+                left_col,
+                left_type.display_name(),
+                right_col,
+                right_type.display_name()
+            ),
+        )
+        .with_span(Span::from_sqlparser(&left.span()))
+        .with_help("Check that the right columns are compared, or use an explicit CAST"),
+    )
+}
+
+/// Whether a join merges its shared columns into one (USING / NATURAL)
+fn merges_join_columns(operator: &sqlparser::ast::JoinOperator) -> bool {
+    use sqlparser::ast::{JoinConstraint, JoinOperator};
+
+    match operator {
+        JoinOperator::Inner(c)
+        | JoinOperator::LeftOuter(c)
+        | JoinOperator::RightOuter(c)
+        | JoinOperator::FullOuter(c) => {
+            matches!(c, JoinConstraint::Using(_) | JoinConstraint::Natural)
+        }
+        _ => false,
+    }
+}
+
+/// A CHECK constraint of the form `column OP literal`, with the operator
+/// flipped when the literal comes first
+fn simple_check(
+    expression: &str,
+    dialect: SqlDialect,
+) -> Option<(String, BinaryOperator, Literal)> {
+    let parser_dialect = dialect.parser_dialect();
+    let expr = sqlparser::parser::Parser::new(parser_dialect.as_ref())
+        .try_with_sql(expression)
+        .ok()?
+        .parse_expr()
+        .ok()?;
+    let Expr::BinaryOp { left, op, right } = strip_nested(&expr) else {
+        return None;
+    };
+    let column = |expr: &Expr| match strip_nested(expr) {
+        Expr::Identifier(ident) => Some(ident.value.clone()),
+        _ => None,
+    };
+    if let (Some(column), Some(bound)) = (column(left), Literal::of(right)) {
+        return Some((column, op.clone(), bound));
+    }
+    let flipped = match op {
+        BinaryOperator::Lt => BinaryOperator::Gt,
+        BinaryOperator::LtEq => BinaryOperator::GtEq,
+        BinaryOperator::Gt => BinaryOperator::Lt,
+        BinaryOperator::GtEq => BinaryOperator::LtEq,
+        op => op.clone(),
+    };
+    Some((column(right)?, flipped, Literal::of(left)?))
+}
+
+fn strip_nested(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Nested(inner) => strip_nested(inner),
+        _ => expr,
     }
 }
 
+/// A constant that a simple CHECK constraint can be evaluated with
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+impl Literal {
+    /// The constant an expression denotes: a number (optionally negated) or a string
+    fn of(expr: &Expr) -> Option<Self> {
+        match strip_nested(expr) {
+            Expr::Value(Value::Number(n, _)) => n.parse().ok().map(Literal::Number),
+            Expr::Value(Value::SingleQuotedString(s)) => Some(Literal::Text(s.clone())),
+            Expr::UnaryOp {
+                op: UnaryOperator::Minus,
+                expr,
+            } => match Self::of(expr)? {
+                Literal::Number(n) => Some(Literal::Number(-n)),
+                Literal::Text(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether `self OP bound` holds, or None when it can't be decided
+    fn satisfies(&self, op: &BinaryOperator, bound: &Literal) -> Option<bool> {
+        let ordering = match (self, bound) {
+            (Literal::Number(a), Literal::Number(b)) => a.partial_cmp(b)?,
+            // Ordering strings depends on the collation
+            (Literal::Text(a), Literal::Text(b))
+                if matches!(op, BinaryOperator::Eq | BinaryOperator::NotEq) =>
+            {
+                a.cmp(b)
+            }
+            _ => return None,
+        };
+        Some(match op {
+            BinaryOperator::Eq => ordering.is_eq(),
+            BinaryOperator::NotEq => ordering.is_ne(),
+            BinaryOperator::Lt => ordering.is_lt(),
+            BinaryOperator::LtEq => ordering.is_le(),
+            BinaryOperator::Gt => ordering.is_gt(),
+            BinaryOperator::GtEq => ordering.is_ge(),
+            _ => return None,
+        })
+    }
+}
+
+/// Plain positional arguments of a function call
+fn unnamed_args(func: &sqlparser::ast::Function) -> Vec<&Expr> {
+    let sqlparser::ast::FunctionArguments::List(arg_list) = &func.args else {
+        return Vec::new();
+    };
+    arg_list
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Expr(expr)) => {
+                Some(expr)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::schema::SchemaBuilder;
 
+    #[test]
+    fn test_simple_check() {
+        let dialect = SqlDialect::PostgreSQL;
+        assert_eq!(
+            simple_check("(age >= 18)", dialect),
+            Some((
+                "age".to_string(),
+                BinaryOperator::GtEq,
+                Literal::Number(18.0)
+            ))
+        );
+        assert_eq!(
+            simple_check("0 < price", dialect),
+            Some((
+                "price".to_string(),
+                BinaryOperator::Gt,
+                Literal::Number(0.0)
+            ))
+        );
+        assert_eq!(
+            simple_check("status <> 'void'", dialect),
+            Some((
+                "status".to_string(),
+                BinaryOperator::NotEq,
+                Literal::Text("void".to_string())
+            ))
+        );
+        assert_eq!(simple_check("age >= 18 AND age < 130", dialect), None);
+        assert_eq!(simple_check("starts_at < ends_at", dialect), None);
+    }
+
     #[test]
     fn test_infer_literal_number() {
         let catalog = Catalog::default();
@@ -1046,6 +1960,38 @@ mod tests {
         type_resolver.inherit_scope(&name_resolver);
         type_resolver.check_statement(&statements[0]);
 
+        let diagnostics = type_resolver.into_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        // Both sides are columns, so it's reported as a column type mismatch
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::ColumnTypeMismatch);
+        assert!(diagnostics[0].message.contains("integer"));
+        assert!(diagnostics[0].message.contains("text"));
+    }
+
+    #[test]
+    fn test_join_expression_type_mismatch() {
+        let schema_sql = r#"
+            CREATE TABLE users (id INTEGER, name TEXT);
+            CREATE TABLE orders (order_id INTEGER, user_name TEXT);
+        "#;
+        let mut builder = SchemaBuilder::new();
+        builder.parse(schema_sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let dialect = crate::dialect::SqlDialect::PostgreSQL.parser_dialect();
+        let statements = sqlparser::parser::Parser::parse_sql(
+            dialect.as_ref(),
+            "SELECT * FROM users JOIN orders ON users.id + 1 = orders.user_name",
+        )
+        .unwrap();
+
+        let mut name_resolver = super::super::resolver::NameResolver::new(&catalog);
+        name_resolver.resolve_statement(&statements[0]);
+
+        let mut type_resolver = TypeResolver::new(&catalog);
+        type_resolver.inherit_scope(&name_resolver);
+        type_resolver.check_statement(&statements[0]);
+
         let diagnostics = type_resolver.into_diagnostics();
         assert_eq!(diagnostics.len(), 1);
         assert_eq!(diagnostics[0].kind, DiagnosticKind::JoinTypeMismatch);
@@ -1,8 +1,11 @@
 //! SQL dialect support
 
+use sqlparser::ast::{Ident, ObjectName};
 use sqlparser::dialect::{Dialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
 use std::str::FromStr;
 
+use crate::schema::QualifiedName;
+
 /// Supported SQL dialects
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SqlDialect {
@@ -29,6 +32,30 @@ impl SqlDialect {
             SqlDialect::MySQL | SqlDialect::SQLite => "",
         }
     }
+
+    /// The name an identifier refers to
+    ///
+    /// PostgreSQL folds unquoted identifiers to lower case, so `Users` and
+    /// `users` are the same table while `"Users"` is a different one. Other
+    /// dialects keep identifiers as written.
+    pub fn normalize_ident(&self, ident: &Ident) -> String {
+        match self {
+            SqlDialect::PostgreSQL if ident.quote_style.is_none() => ident.value.to_lowercase(),
+            _ => ident.value.clone(),
+        }
+    }
+
+    /// Convert a possibly schema-qualified object name to the catalog name it refers to
+    pub fn qualified_name(&self, name: &ObjectName) -> QualifiedName {
+        match name.0.as_slice() {
+            [table] => QualifiedName::new(self.normalize_ident(table)),
+            [schema, table] | [_, schema, table] => QualifiedName::with_schema(
+                self.normalize_ident(schema),
+                self.normalize_ident(table),
+            ),
+            _ => QualifiedName::new(name.to_string()),
+        }
+    }
 }
 
 impl FromStr for SqlDialect {
@@ -1,10 +1,14 @@
 //! Error and diagnostic types
 
+use std::collections::{HashMap, HashSet};
+
 use miette::SourceSpan;
 use serde::{Deserialize, Serialize};
 
+use crate::schema::SourceLocation;
+
 /// Source location span
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Span {
     /// Byte offset from start of source (optional, for miette compatibility)
     pub offset: usize,
@@ -79,6 +83,27 @@ pub struct Diagnostic {
     pub span: Option<Span>,
     pub help: Option<String>,
     pub labels: Vec<Label>,
+    /// Machine-applicable fix, e.g. the corrected spelling of a column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+    /// Places in the schema that explain the diagnostic, e.g. each table
+    /// defining an ambiguous column
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedInformation>,
+}
+
+/// Replacement text for a span of the source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// A schema location related to a diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedInformation {
+    pub message: String,
+    pub location: SourceLocation,
 }
 
 /// Label for source annotations
@@ -97,6 +122,8 @@ impl Diagnostic {
             span: None,
             help: None,
             labels: Vec::new(),
+            suggestion: None,
+            related: Vec::new(),
         }
     }
 
@@ -108,6 +135,21 @@ impl Diagnostic {
             span: None,
             help: None,
             labels: Vec::new(),
+            suggestion: None,
+            related: Vec::new(),
+        }
+    }
+
+    pub fn info(kind: DiagnosticKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity: Severity::Info,
+            message: message.into(),
+            span: None,
+            help: None,
+            labels: Vec::new(),
+            suggestion: None,
+            related: Vec::new(),
         }
     }
 
@@ -129,14 +171,129 @@ impl Diagnostic {
         self
     }
 
+    pub fn with_suggestion(mut self, span: Span, replacement: impl Into<String>) -> Self {
+        self.suggestion = Some(Suggestion {
+            span,
+            replacement: replacement.into(),
+        });
+        self
+    }
+
+    pub fn with_related(mut self, message: impl Into<String>, location: SourceLocation) -> Self {
+        self.related.push(RelatedInformation {
+            message: message.into(),
+            location,
+        });
+        self
+    }
+
     /// Get the error code string (e.g., "E0001")
     pub fn code(&self) -> &'static str {
         self.kind.code()
     }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
 }
 
-/// Types of diagnostics
+/// Drop repeated diagnostics, keeping the first of each
+///
+/// Two diagnostics are the same when their code, span and message all match.
+/// Diagnostics without a source position (no span, or line 0) can't be told
+/// apart from distinct ones elsewhere and are always kept.
+pub fn dedup_diagnostics(diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen = HashSet::new();
+    diagnostics.retain(|d| match d.span {
+        Some(span) if span.line > 0 => seen.insert((d.kind, span, d.message.clone())),
+        _ => true,
+    });
+}
+
+/// Configured level of a rule, overriding the severity it reports at
+///
+/// Read from the `[severity]` table of `sqlsift.toml`, e.g. `E0003 = "warning"`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Error,
+    Warning,
+    Info,
+    /// Drop the rule's diagnostics
+    Off,
+}
+
+impl RuleLevel {
+    /// Severity to report at, or `None` when the rule is off
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            RuleLevel::Error => Some(Severity::Error),
+            RuleLevel::Warning => Some(Severity::Warning),
+            RuleLevel::Info => Some(Severity::Info),
+            RuleLevel::Off => None,
+        }
+    }
+}
+
+/// Re-level diagnostics after analysis, dropping those of rules that are off
+pub fn apply_rule_levels(
+    diagnostics: Vec<Diagnostic>,
+    levels: &HashMap<DiagnosticKind, RuleLevel>,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter_map(|mut diagnostic| {
+            if let Some(level) = levels.get(&diagnostic.kind) {
+                diagnostic.severity = level.severity()?;
+            }
+            Some(diagnostic)
+        })
+        .collect()
+}
+
+/// Diagnostic counts by severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticSummary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+impl DiagnosticSummary {
+    /// Count a set of diagnostics
+    pub fn from_diagnostics<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Self {
+        let mut summary = Self::default();
+        for diagnostic in diagnostics {
+            summary.add(diagnostic);
+        }
+        summary
+    }
+
+    /// Count one more diagnostic
+    pub fn add(&mut self, diagnostic: &Diagnostic) {
+        match diagnostic.severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Info => self.infos += 1,
+        }
+    }
+
+    /// Total number of diagnostics counted
+    pub fn total(&self) -> usize {
+        self.errors + self.warnings + self.infos
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+}
+
+/// Types of diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DiagnosticKind {
     /// E0001: Table not found
     TableNotFound,
@@ -152,11 +309,106 @@ pub enum DiagnosticKind {
     AmbiguousColumn,
     /// E0007: JOIN type mismatch
     JoinTypeMismatch,
+    /// E0008: More than one primary key declared for a table
+    MultiplePrimaryKeys,
+    /// E0009: INSERT supplies a value for a generated (computed) column
+    GeneratedColumnInsert,
+    /// E0010: SELECT-list column that is neither in GROUP BY nor aggregated
+    UngroupedColumn,
+    /// E0011: Subscript (`col[1]`) applied to a value that isn't an array
+    InvalidSubscript,
+    /// E0012: String literal assigned to an enum column isn't one of its labels
+    InvalidEnumValue,
+    /// E0014: Unqualified table name exists in more than one search_path schema
+    AmbiguousTable,
+    /// E0015: Comparison between two columns of incompatible types
+    ColumnTypeMismatch,
+    /// E0016: UPDATE or DELETE without a WHERE clause affects every row
+    UnboundedMutation,
+    /// E0017: ORDER BY position outside the select list
+    InvalidOrderByPosition,
+    /// E0018: INSERT supplies a value for a GENERATED ALWAYS identity column
+    /// without OVERRIDING SYSTEM VALUE
+    GeneratedAlwaysOverride,
+    /// E0019: ON CONFLICT target matches no unique or primary key constraint
+    NoMatchingUniqueConstraint,
+    /// E0020: GROUP BY expression contains an aggregate or window function
+    AggregateInGroupBy,
+    /// E0021: Syntax another SQL dialect accepts but the configured one doesn't
+    UnsupportedSyntax,
+    /// W1001: Joined table whose columns are only used in its own ON (opt-in)
+    UnusedJoinTable,
+    /// W1002: `*` in a SELECT projection (opt-in)
+    SelectStar,
+    /// W1003: WHERE filters on a column no index can serve (opt-in)
+    MissingIndex,
+    /// W1004: Schema change that can lose data or fail on existing rows
+    DestructiveMigration,
+    /// W1005: LIKE ESCAPE operand is not a single character
+    InvalidLikeEscape,
+    /// W1006: `IS [NOT] NULL` on a NOT NULL column always has the same result (opt-in)
+    AlwaysFalsePredicate,
+    /// W1007: Column-less INSERT leaves out a NOT NULL column that has no default
+    MissingRequiredColumn,
+    /// W1008: CREATE TABLE (without IF NOT EXISTS) redefines an existing table
+    DuplicateTable,
+    /// W1009: Foreign key column type is incompatible with the referenced column
+    ForeignKeyTypeMismatch,
+    /// W1010: Foreign keys between tables form a cycle (info severity;
+    /// cycles are legal, just awkward to insert into)
+    ForeignKeyCycle,
+    /// W1011: Comma-separated FROM tables with no WHERE condition linking them
+    UnintentionalCrossJoin,
+    /// W1012: UPDATE assigns a column to itself (opt-in)
+    SelfAssignment,
+    /// W1013: INSERT value that provably fails a CHECK constraint (opt-in)
+    CheckConstraintViolation,
+    /// W1014: Subquery compared as a single value may return several rows (opt-in)
+    PossiblyMultiRowSubquery,
     /// Parse error
     ParseError,
 }
 
 impl DiagnosticKind {
+    /// Every diagnostic kind, in code order
+    pub const ALL: &'static [DiagnosticKind] = &[
+        DiagnosticKind::TableNotFound,
+        DiagnosticKind::ColumnNotFound,
+        DiagnosticKind::TypeMismatch,
+        DiagnosticKind::PotentialNullViolation,
+        DiagnosticKind::ColumnCountMismatch,
+        DiagnosticKind::AmbiguousColumn,
+        DiagnosticKind::JoinTypeMismatch,
+        DiagnosticKind::MultiplePrimaryKeys,
+        DiagnosticKind::GeneratedColumnInsert,
+        DiagnosticKind::UngroupedColumn,
+        DiagnosticKind::InvalidSubscript,
+        DiagnosticKind::InvalidEnumValue,
+        DiagnosticKind::AmbiguousTable,
+        DiagnosticKind::ColumnTypeMismatch,
+        DiagnosticKind::UnboundedMutation,
+        DiagnosticKind::InvalidOrderByPosition,
+        DiagnosticKind::GeneratedAlwaysOverride,
+        DiagnosticKind::NoMatchingUniqueConstraint,
+        DiagnosticKind::AggregateInGroupBy,
+        DiagnosticKind::UnsupportedSyntax,
+        DiagnosticKind::UnusedJoinTable,
+        DiagnosticKind::SelectStar,
+        DiagnosticKind::MissingIndex,
+        DiagnosticKind::DestructiveMigration,
+        DiagnosticKind::InvalidLikeEscape,
+        DiagnosticKind::AlwaysFalsePredicate,
+        DiagnosticKind::MissingRequiredColumn,
+        DiagnosticKind::DuplicateTable,
+        DiagnosticKind::ForeignKeyTypeMismatch,
+        DiagnosticKind::ForeignKeyCycle,
+        DiagnosticKind::UnintentionalCrossJoin,
+        DiagnosticKind::SelfAssignment,
+        DiagnosticKind::CheckConstraintViolation,
+        DiagnosticKind::PossiblyMultiRowSubquery,
+        DiagnosticKind::ParseError,
+    ];
+
     pub fn code(&self) -> &'static str {
         match self {
             DiagnosticKind::TableNotFound => "E0001",
@@ -166,6 +418,33 @@ impl DiagnosticKind {
             DiagnosticKind::ColumnCountMismatch => "E0005",
             DiagnosticKind::AmbiguousColumn => "E0006",
             DiagnosticKind::JoinTypeMismatch => "E0007",
+            DiagnosticKind::MultiplePrimaryKeys => "E0008",
+            DiagnosticKind::GeneratedColumnInsert => "E0009",
+            DiagnosticKind::UngroupedColumn => "E0010",
+            DiagnosticKind::InvalidSubscript => "E0011",
+            DiagnosticKind::InvalidEnumValue => "E0012",
+            DiagnosticKind::AmbiguousTable => "E0014",
+            DiagnosticKind::ColumnTypeMismatch => "E0015",
+            DiagnosticKind::UnboundedMutation => "E0016",
+            DiagnosticKind::InvalidOrderByPosition => "E0017",
+            DiagnosticKind::GeneratedAlwaysOverride => "E0018",
+            DiagnosticKind::NoMatchingUniqueConstraint => "E0019",
+            DiagnosticKind::AggregateInGroupBy => "E0020",
+            DiagnosticKind::UnsupportedSyntax => "E0021",
+            DiagnosticKind::UnusedJoinTable => "W1001",
+            DiagnosticKind::SelectStar => "W1002",
+            DiagnosticKind::MissingIndex => "W1003",
+            DiagnosticKind::DestructiveMigration => "W1004",
+            DiagnosticKind::InvalidLikeEscape => "W1005",
+            DiagnosticKind::AlwaysFalsePredicate => "W1006",
+            DiagnosticKind::MissingRequiredColumn => "W1007",
+            DiagnosticKind::DuplicateTable => "W1008",
+            DiagnosticKind::ForeignKeyTypeMismatch => "W1009",
+            DiagnosticKind::ForeignKeyCycle => "W1010",
+            DiagnosticKind::UnintentionalCrossJoin => "W1011",
+            DiagnosticKind::SelfAssignment => "W1012",
+            DiagnosticKind::CheckConstraintViolation => "W1013",
+            DiagnosticKind::PossiblyMultiRowSubquery => "W1014",
             DiagnosticKind::ParseError => "E1000",
         }
     }
@@ -179,7 +458,129 @@ impl DiagnosticKind {
             DiagnosticKind::ColumnCountMismatch => "column-count-mismatch",
             DiagnosticKind::AmbiguousColumn => "ambiguous-column",
             DiagnosticKind::JoinTypeMismatch => "join-type-mismatch",
+            DiagnosticKind::MultiplePrimaryKeys => "multiple-primary-keys",
+            DiagnosticKind::GeneratedColumnInsert => "generated-column-insert",
+            DiagnosticKind::UngroupedColumn => "ungrouped-column",
+            DiagnosticKind::InvalidSubscript => "invalid-subscript",
+            DiagnosticKind::InvalidEnumValue => "invalid-enum-value",
+            DiagnosticKind::AmbiguousTable => "ambiguous-table",
+            DiagnosticKind::ColumnTypeMismatch => "column-type-mismatch",
+            DiagnosticKind::UnboundedMutation => "unbounded-mutation",
+            DiagnosticKind::InvalidOrderByPosition => "invalid-order-by-position",
+            DiagnosticKind::GeneratedAlwaysOverride => "generated-always-override",
+            DiagnosticKind::NoMatchingUniqueConstraint => "no-matching-unique-constraint",
+            DiagnosticKind::AggregateInGroupBy => "aggregate-in-group-by",
+            DiagnosticKind::UnsupportedSyntax => "unsupported-syntax",
+            DiagnosticKind::UnusedJoinTable => "unused-join-table",
+            DiagnosticKind::SelectStar => "select-star",
+            DiagnosticKind::MissingIndex => "missing-index",
+            DiagnosticKind::DestructiveMigration => "destructive-migration",
+            DiagnosticKind::InvalidLikeEscape => "invalid-like-escape",
+            DiagnosticKind::AlwaysFalsePredicate => "always-false-predicate",
+            DiagnosticKind::MissingRequiredColumn => "missing-required-column",
+            DiagnosticKind::DuplicateTable => "duplicate-table",
+            DiagnosticKind::ForeignKeyTypeMismatch => "foreign-key-type-mismatch",
+            DiagnosticKind::ForeignKeyCycle => "foreign-key-cycle",
+            DiagnosticKind::UnintentionalCrossJoin => "unintentional-cross-join",
+            DiagnosticKind::SelfAssignment => "self-assignment",
+            DiagnosticKind::CheckConstraintViolation => "check-constraint-violation",
+            DiagnosticKind::PossiblyMultiRowSubquery => "possibly-multi-row-subquery",
             DiagnosticKind::ParseError => "parse-error",
         }
     }
+
+    /// One-line description of what the rule checks
+    pub fn description(&self) -> &'static str {
+        match self {
+            DiagnosticKind::TableNotFound => "Referenced table does not exist in the schema",
+            DiagnosticKind::ColumnNotFound => "Referenced column does not exist in the table",
+            DiagnosticKind::TypeMismatch => "Expression types are incompatible",
+            DiagnosticKind::PotentialNullViolation => "NULL may be written to a NOT NULL column",
+            DiagnosticKind::ColumnCountMismatch => "INSERT column and value counts differ",
+            DiagnosticKind::AmbiguousColumn => "Unqualified column exists in more than one table",
+            DiagnosticKind::JoinTypeMismatch => "JOIN condition compares incompatible types",
+            DiagnosticKind::MultiplePrimaryKeys => "Table declares more than one primary key",
+            DiagnosticKind::GeneratedColumnInsert => "INSERT writes to a generated column",
+            DiagnosticKind::UngroupedColumn => "Selected column is neither grouped nor aggregated",
+            DiagnosticKind::InvalidSubscript => "Subscript applied to a value that isn't an array",
+            DiagnosticKind::InvalidEnumValue => "Value is not a label of the column's enum type",
+            DiagnosticKind::AmbiguousTable => {
+                "Unqualified table exists in more than one search_path schema"
+            }
+            DiagnosticKind::ColumnTypeMismatch => "Two columns of incompatible types are compared",
+            DiagnosticKind::UnboundedMutation => "UPDATE or DELETE without WHERE affects every row",
+            DiagnosticKind::InvalidOrderByPosition => {
+                "ORDER BY position is outside the select list"
+            }
+            DiagnosticKind::GeneratedAlwaysOverride => {
+                "INSERT supplies a GENERATED ALWAYS identity value"
+            }
+            DiagnosticKind::NoMatchingUniqueConstraint => {
+                "ON CONFLICT target is not a unique or primary key constraint"
+            }
+            DiagnosticKind::AggregateInGroupBy => "GROUP BY contains an aggregate function",
+            DiagnosticKind::UnsupportedSyntax => "Syntax not supported by the SQL dialect",
+            DiagnosticKind::UnusedJoinTable => "Joined table is not used outside its ON clause",
+            DiagnosticKind::SelectStar => "SELECT * used in a projection",
+            DiagnosticKind::MissingIndex => "WHERE filters on a column without an index",
+            DiagnosticKind::DestructiveMigration => {
+                "Schema change can lose data or fail on existing rows"
+            }
+            DiagnosticKind::InvalidLikeEscape => "LIKE ESCAPE is not a single character",
+            DiagnosticKind::AlwaysFalsePredicate => "IS [NOT] NULL on a NOT NULL column",
+            DiagnosticKind::MissingRequiredColumn => {
+                "INSERT omits a NOT NULL column that has no default"
+            }
+            DiagnosticKind::DuplicateTable => "CREATE TABLE redefines an existing table",
+            DiagnosticKind::ForeignKeyTypeMismatch => {
+                "Foreign key column type differs from the referenced column"
+            }
+            DiagnosticKind::ForeignKeyCycle => "Foreign keys between tables form a cycle",
+            DiagnosticKind::UnintentionalCrossJoin => {
+                "Comma-joined tables are not linked by any WHERE condition"
+            }
+            DiagnosticKind::SelfAssignment => "UPDATE sets a column to its own value",
+            DiagnosticKind::CheckConstraintViolation => "INSERT value violates a CHECK constraint",
+            DiagnosticKind::PossiblyMultiRowSubquery => {
+                "Scalar subquery may return more than one row"
+            }
+            DiagnosticKind::ParseError => "SQL could not be parsed",
+        }
+    }
+
+    /// Whether the rule reports by default; opt-in lints must be enabled explicitly
+    pub fn is_enabled_by_default(&self) -> bool {
+        !matches!(
+            self,
+            DiagnosticKind::UnusedJoinTable
+                | DiagnosticKind::SelectStar
+                | DiagnosticKind::MissingIndex
+                | DiagnosticKind::AlwaysFalsePredicate
+                | DiagnosticKind::SelfAssignment
+                | DiagnosticKind::CheckConstraintViolation
+                | DiagnosticKind::PossiblyMultiRowSubquery
+        )
+    }
+
+    /// Whether the rule is derived from type inference (turned off with type checking)
+    pub fn is_type_check(&self) -> bool {
+        matches!(
+            self,
+            DiagnosticKind::TypeMismatch
+                | DiagnosticKind::PotentialNullViolation
+                | DiagnosticKind::JoinTypeMismatch
+                | DiagnosticKind::ColumnTypeMismatch
+                | DiagnosticKind::InvalidSubscript
+                | DiagnosticKind::InvalidEnumValue
+                | DiagnosticKind::CheckConstraintViolation
+        )
+    }
+
+    /// Look up a diagnostic kind by its code (`W1002`) or name (`select-star`)
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|k| k.code().eq_ignore_ascii_case(code) || k.name() == code)
+    }
 }
@@ -0,0 +1,277 @@
+//! Build a catalog by introspecting a live PostgreSQL database
+//!
+//! Only available with the `postgres` feature. Tables, columns, primary and
+//! foreign keys come from `information_schema`; enum types from `pg_enum`.
+//! System schemas are skipped.
+
+use postgres::{Client, NoTls};
+
+use crate::dialect::SqlDialect;
+use crate::schema::{
+    Catalog, ColumnDef, DefaultValue, EnumTypeDef, ForeignKeyDef, IdentityKind, PrimaryKeyDef,
+    QualifiedName, TableDef, UniqueConstraintDef,
+};
+use crate::types::SqlType;
+
+/// A column of a base table, in declaration order
+#[derive(Debug, Clone)]
+pub struct ColumnRow {
+    pub schema: String,
+    pub table: String,
+    pub name: String,
+    /// Type as printed by `format_type`, e.g. `character varying(255)`
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub identity: Option<IdentityKind>,
+    pub is_generated: bool,
+}
+
+/// Kind of a key constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    PrimaryKey,
+    ForeignKey,
+    Unique,
+}
+
+/// A PRIMARY KEY, FOREIGN KEY or UNIQUE constraint
+#[derive(Debug, Clone)]
+pub struct ConstraintRow {
+    pub schema: String,
+    pub table: String,
+    pub name: String,
+    pub kind: ConstraintKind,
+    pub columns: Vec<String>,
+    /// Referenced `(schema, table, columns)` for foreign keys
+    pub references: Option<(String, String, Vec<String>)>,
+}
+
+/// An enum type with its labels in sort order
+#[derive(Debug, Clone)]
+pub struct EnumRow {
+    pub schema: String,
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Where introspection rows come from; implemented for a `postgres::Client`
+pub trait IntrospectionSource {
+    type Error;
+
+    fn columns(&mut self) -> Result<Vec<ColumnRow>, Self::Error>;
+    fn constraints(&mut self) -> Result<Vec<ConstraintRow>, Self::Error>;
+    fn enums(&mut self) -> Result<Vec<EnumRow>, Self::Error>;
+}
+
+const COLUMNS_QUERY: &str = "
+    SELECT c.table_schema::text, c.table_name::text, c.column_name::text,
+           format_type(a.atttypid, a.atttypmod),
+           c.is_nullable = 'YES',
+           c.column_default::text,
+           c.identity_generation::text,
+           c.is_generated = 'ALWAYS'
+    FROM information_schema.columns c
+    JOIN information_schema.tables t
+      ON t.table_schema = c.table_schema AND t.table_name = c.table_name
+    JOIN pg_catalog.pg_attribute a
+      ON a.attrelid = format('%I.%I', c.table_schema, c.table_name)::regclass
+     AND a.attname = c.column_name
+    WHERE t.table_type = 'BASE TABLE'
+      AND c.table_schema NOT IN ('pg_catalog', 'information_schema')
+    ORDER BY c.table_schema, c.table_name, c.ordinal_position";
+
+const CONSTRAINTS_QUERY: &str = "
+    SELECT tc.table_schema::text, tc.table_name::text, tc.constraint_name::text,
+           tc.constraint_type::text,
+           array_agg(kcu.column_name::text ORDER BY kcu.ordinal_position),
+           min(ref.table_schema::text),
+           min(ref.table_name::text),
+           array_remove(array_agg(ref.column_name::text ORDER BY kcu.ordinal_position), NULL)
+    FROM information_schema.table_constraints tc
+    JOIN information_schema.key_column_usage kcu
+      ON kcu.constraint_schema = tc.constraint_schema
+     AND kcu.constraint_name = tc.constraint_name
+     AND kcu.table_name = tc.table_name
+    LEFT JOIN information_schema.referential_constraints rc
+      ON rc.constraint_schema = tc.constraint_schema
+     AND rc.constraint_name = tc.constraint_name
+    LEFT JOIN information_schema.key_column_usage ref
+      ON ref.constraint_schema = rc.unique_constraint_schema
+     AND ref.constraint_name = rc.unique_constraint_name
+     AND ref.ordinal_position = kcu.position_in_unique_constraint
+    WHERE tc.constraint_type IN ('PRIMARY KEY', 'FOREIGN KEY', 'UNIQUE')
+      AND tc.table_schema NOT IN ('pg_catalog', 'information_schema')
+    GROUP BY 1, 2, 3, 4
+    ORDER BY 1, 2, 3";
+
+const ENUMS_QUERY: &str = "
+    SELECT n.nspname::text, t.typname::text,
+           array_agg(e.enumlabel::text ORDER BY e.enumsortorder)
+    FROM pg_catalog.pg_type t
+    JOIN pg_catalog.pg_enum e ON e.enumtypid = t.oid
+    JOIN pg_catalog.pg_namespace n ON n.oid = t.typnamespace
+    WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+    GROUP BY 1, 2
+    ORDER BY 1, 2";
+
+impl IntrospectionSource for Client {
+    type Error = postgres::Error;
+
+    fn columns(&mut self) -> Result<Vec<ColumnRow>, Self::Error> {
+        Ok(self
+            .query(COLUMNS_QUERY, &[])?
+            .iter()
+            .map(|row| ColumnRow {
+                schema: row.get(0),
+                table: row.get(1),
+                name: row.get(2),
+                data_type: row.get(3),
+                nullable: row.get(4),
+                default: row.get(5),
+                identity: match row.get::<_, Option<String>>(6).as_deref() {
+                    Some("ALWAYS") => Some(IdentityKind::Always),
+                    Some("BY DEFAULT") => Some(IdentityKind::ByDefault),
+                    _ => None,
+                },
+                is_generated: row.get(7),
+            })
+            .collect())
+    }
+
+    fn constraints(&mut self) -> Result<Vec<ConstraintRow>, Self::Error> {
+        Ok(self
+            .query(CONSTRAINTS_QUERY, &[])?
+            .iter()
+            .filter_map(|row| {
+                let kind = match row.get::<_, &str>(3) {
+                    "PRIMARY KEY" => ConstraintKind::PrimaryKey,
+                    "FOREIGN KEY" => ConstraintKind::ForeignKey,
+                    "UNIQUE" => ConstraintKind::Unique,
+                    _ => return None,
+                };
+                let references = match (row.get(5), row.get(6)) {
+                    (Some(schema), Some(table)) => Some((schema, table, row.get(7))),
+                    _ => None,
+                };
+                Some(ConstraintRow {
+                    schema: row.get(0),
+                    table: row.get(1),
+                    name: row.get(2),
+                    kind,
+                    columns: row.get(4),
+                    references,
+                })
+            })
+            .collect())
+    }
+
+    fn enums(&mut self) -> Result<Vec<EnumRow>, Self::Error> {
+        Ok(self
+            .query(ENUMS_QUERY, &[])?
+            .iter()
+            .map(|row| EnumRow {
+                schema: row.get(0),
+                name: row.get(1),
+                values: row.get(2),
+            })
+            .collect())
+    }
+}
+
+impl Catalog {
+    /// Build a catalog from the database at `conn_str`
+    ///
+    /// `conn_str` is a libpq-style connection string or URL, e.g.
+    /// `postgresql://user@localhost/app_dev`.
+    pub fn from_postgres(conn_str: &str) -> Result<Catalog, postgres::Error> {
+        let mut client = Client::connect(conn_str, NoTls)?;
+        build_catalog(&mut client)
+    }
+}
+
+/// Build a catalog from the rows of an introspection source
+pub fn build_catalog<S: IntrospectionSource>(source: &mut S) -> Result<Catalog, S::Error> {
+    let mut catalog = Catalog::new();
+
+    for row in source.enums()? {
+        // Named the way `format_type` names a column of the type
+        let name = object_name(&catalog, &row.schema, &row.name).to_string();
+        catalog.add_enum(EnumTypeDef {
+            name,
+            values: row.values,
+            location: None,
+        });
+    }
+
+    for row in source.columns()? {
+        let name = object_name(&catalog, &row.schema, &row.table);
+        if catalog.get_table(&name).is_none() {
+            catalog.add_table(TableDef::new(name.clone()));
+        }
+        // Types this crate doesn't model are kept by name, like a custom type
+        let data_type = SqlType::parse(&row.data_type, SqlDialect::PostgreSQL)
+            .unwrap_or_else(|| SqlType::Custom(row.data_type.clone()));
+        let mut column = ColumnDef::new(&row.name, data_type);
+        column.nullable = row.nullable;
+        column.default = row.default.map(DefaultValue::Expression);
+        column.identity = row.identity;
+        column.is_generated = row.is_generated;
+        if let Some(table) = catalog.get_table_mut(&name) {
+            table.columns.insert(row.name, column);
+        }
+    }
+
+    for row in source.constraints()? {
+        let name = object_name(&catalog, &row.schema, &row.table);
+        let references_table = row
+            .references
+            .as_ref()
+            .map(|(schema, table, _)| object_name(&catalog, schema, table));
+        let Some(table) = catalog.get_table_mut(&name) else {
+            continue;
+        };
+        match row.kind {
+            ConstraintKind::PrimaryKey => {
+                for column in &row.columns {
+                    if let Some(col) = table.columns.get_mut(column) {
+                        col.is_primary_key = true;
+                        col.nullable = false;
+                    }
+                }
+                table.primary_key = Some(PrimaryKeyDef {
+                    name: Some(row.name),
+                    columns: row.columns,
+                });
+            }
+            ConstraintKind::ForeignKey => {
+                let (Some(references_table), Some((_, _, references_columns))) =
+                    (references_table, row.references)
+                else {
+                    continue;
+                };
+                table.foreign_keys.push(ForeignKeyDef {
+                    name: Some(row.name),
+                    columns: row.columns,
+                    references_table,
+                    references_columns,
+                });
+            }
+            ConstraintKind::Unique => table.unique_constraints.push(UniqueConstraintDef {
+                name: Some(row.name),
+                columns: row.columns,
+            }),
+        }
+    }
+
+    Ok(catalog)
+}
+
+/// Objects in the default schema are left unqualified, as in DDL
+fn object_name(catalog: &Catalog, schema: &str, name: &str) -> QualifiedName {
+    if schema == catalog.default_schema {
+        QualifiedName::new(name)
+    } else {
+        QualifiedName::with_schema(schema, name)
+    }
+}
@@ -6,11 +6,16 @@
 pub mod analyzer;
 pub mod dialect;
 pub mod error;
+#[cfg(feature = "postgres")]
+pub mod introspect;
 pub mod schema;
 pub mod types;
 
-pub use analyzer::Analyzer;
+pub use analyzer::{Analyzer, StatementInfo, StatementKind};
 pub use dialect::SqlDialect;
-pub use error::{Diagnostic, DiagnosticKind, Severity, Span};
-pub use schema::{Catalog, ColumnDef, QualifiedName, Schema, TableDef};
+pub use error::{
+    apply_rule_levels, Diagnostic, DiagnosticKind, DiagnosticSummary, RelatedInformation,
+    RuleLevel, Severity, Span, Suggestion,
+};
+pub use schema::{Catalog, ColumnDef, ColumnRef, QualifiedName, Schema, TableDef};
 pub use types::SqlType;
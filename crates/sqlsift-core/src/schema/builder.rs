@@ -1,34 +1,42 @@
 //! Schema builder - converts SQL AST to Catalog
 
 use sqlparser::ast::{
-    AlterTableOperation, ColumnOption, ColumnOptionDef, ObjectName, ObjectType, Statement,
-    TableConstraint, UserDefinedTypeRepresentation,
+    visit_expressions, visit_relations, AlterTableOperation, BinaryOperator, ColumnOption,
+    ColumnOptionDef, CreateIndex, Expr, ObjectName, ObjectType, Statement, TableConstraint,
+    UserDefinedTypeRepresentation,
 };
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+use sqlparser::keywords::Keyword;
 use sqlparser::parser::Parser;
-use sqlparser::tokenizer::Token;
+use sqlparser::tokenizer::{Token, TokenWithSpan, Tokenizer};
 
 use crate::dialect::SqlDialect;
-use crate::error::{Diagnostic, DiagnosticKind};
+use crate::error::{Diagnostic, DiagnosticKind, Span};
 use crate::schema::{
-    Catalog, CheckConstraintDef, ColumnDef, DefaultValue, EnumTypeDef, ForeignKeyDef, IdentityKind,
-    PrimaryKeyDef, QualifiedName, TableDef, UniqueConstraintDef, ViewDef,
+    Catalog, CheckConstraintDef, ColumnDef, CompositeFieldDef, CompositeTypeDef, DefaultValue,
+    EnumTypeDef, ForeignKeyDef, IdentityKind, IndexDef, PrimaryKeyDef, QualifiedName,
+    SourceLocation, TableDef, UniqueConstraintDef, ViewDef,
 };
-use crate::types::SqlType;
+use crate::types::{SqlType, TypeCompatibility};
+
+use super::json;
 
 /// Builder for constructing a Catalog from SQL schema definitions
 pub struct SchemaBuilder {
     catalog: Catalog,
     diagnostics: Vec<Diagnostic>,
     dialect: SqlDialect,
+    /// File being parsed, recorded in source locations
+    file: Option<PathBuf>,
+    /// Line and column where the statement being processed starts in the file
+    statement_origin: (usize, usize),
 }
 
 impl SchemaBuilder {
     pub fn new() -> Self {
-        Self {
-            catalog: Catalog::new(),
-            diagnostics: Vec::new(),
-            dialect: SqlDialect::default(),
-        }
+        Self::with_dialect(SqlDialect::default())
     }
 
     pub fn with_dialect(dialect: SqlDialect) -> Self {
@@ -36,9 +44,51 @@ impl SchemaBuilder {
             catalog: Catalog::new(),
             diagnostics: Vec::new(),
             dialect,
+            file: None,
+            statement_origin: (1, 1),
         }
     }
 
+    /// Continue building on a catalog built earlier
+    pub fn from_catalog(catalog: Catalog, dialect: SqlDialect) -> Self {
+        Self {
+            catalog,
+            ..Self::with_dialect(dialect)
+        }
+    }
+
+    /// Parse SQL schema definitions read from `path`
+    ///
+    /// Same as [`parse`](Self::parse), but tables, columns, views and enums
+    /// remember the file they were declared in.
+    pub fn parse_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        sql: &str,
+    ) -> Result<(), Vec<Diagnostic>> {
+        self.file = Some(path.into());
+        let result = self.parse(sql);
+        self.file = None;
+        result
+    }
+
+    /// Replace the tables, views and types declared in `path` with the
+    /// definitions in `sql`
+    ///
+    /// Used with [`from_catalog`](Self::from_catalog) to pick up an edited
+    /// schema file without re-reading the others. The result is only the
+    /// same as a full rebuild if the file isn't listed in
+    /// [`Catalog::interdependent_files`] before or after the update.
+    pub fn replace_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        sql: &str,
+    ) -> Result<(), Vec<Diagnostic>> {
+        let path = path.into();
+        self.catalog.remove_file(&path);
+        self.parse_file(path, sql)
+    }
+
     /// Parse SQL schema definitions and build the catalog
     pub fn parse(&mut self, sql: &str) -> Result<(), Vec<Diagnostic>> {
         let dialect = self.dialect.parser_dialect();
@@ -56,17 +106,50 @@ impl SchemaBuilder {
             }
         }
 
-        if self
-            .diagnostics
-            .iter()
-            .any(|d| d.severity == crate::error::Severity::Error)
-        {
+        if self.diagnostics.iter().any(|d| d.is_error()) {
             Err(std::mem::take(&mut self.diagnostics))
         } else {
             Ok(())
         }
     }
 
+    /// Load tables from a JSON schema dump
+    ///
+    /// For databases whose schema is easier to export than to write out as
+    /// DDL, e.g. from `information_schema` or `pg_catalog`:
+    ///
+    /// ```json
+    /// {
+    ///   "schemas": [
+    ///     {
+    ///       "name": "public",
+    ///       "tables": [
+    ///         {
+    ///           "name": "users",
+    ///           "columns": [
+    ///             { "name": "id", "type": "integer", "nullable": false, "primary_key": true },
+    ///             { "name": "email", "type": "varchar(255)" },
+    ///             { "name": "created_at", "type": "timestamptz", "default": "now()" }
+    ///           ]
+    ///         }
+    ///       ]
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `nullable` defaults to `true` and `primary_key` to `false`. Types are
+    /// written as they would be in CREATE TABLE, and names are taken exactly
+    /// as given, like quoted identifiers. Tables can be mixed with ones
+    /// declared in DDL; a later definition of a table replaces an earlier one.
+    pub fn load_json(&mut self, json: &str) -> Result<(), Vec<Diagnostic>> {
+        let tables = json::tables_from_json(json, self.dialect, &self.catalog.default_schema)?;
+        for table in tables {
+            self.catalog.add_table(table);
+        }
+        Ok(())
+    }
+
     /// Parse SQL statements individually, skipping those that fail to parse.
     /// This allows sqlsift to handle schema files containing unsupported syntax
     /// (e.g., CREATE FUNCTION, CREATE TRIGGER, CREATE DOMAIN) by gracefully
@@ -80,6 +163,14 @@ impl SchemaBuilder {
                 continue;
             }
 
+            // Spans are relative to the statement; remember where it starts
+            let prefix = &sql[..trimmed.as_ptr() as usize - sql.as_ptr() as usize];
+            let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+            self.statement_origin = (
+                prefix.matches('\n').count() + 1,
+                prefix[line_start..].chars().count() + 1,
+            );
+
             match Parser::parse_sql(dialect.as_ref(), trimmed) {
                 Ok(stmts) => {
                     for stmt in stmts {
@@ -87,14 +178,122 @@ impl SchemaBuilder {
                     }
                 }
                 Err(_) => {
-                    // Silently skip unparseable statements (functions, triggers, etc.)
+                    // Silently skip other unparseable statements (functions, triggers, etc.)
+                    self.parse_without_exclusion_constraints(trimmed);
+                }
+            }
+        }
+        self.statement_origin = (1, 1);
+    }
+
+    /// Apply a CREATE TABLE or ALTER TABLE statement that sqlparser rejects
+    /// for its `EXCLUDE` constraints
+    ///
+    /// The constraints are taken out of the token stream before parsing, and
+    /// only their names are recorded on the table, for `ON CONFLICT ON
+    /// CONSTRAINT`. Unnamed ones get PostgreSQL's generated name, e.g.
+    /// `bookings_room_excl`.
+    fn parse_without_exclusion_constraints(&mut self, sql: &str) {
+        let dialect = self.dialect.parser_dialect();
+        let Ok(mut tokens) = Tokenizer::new(dialect.as_ref(), sql).tokenize_with_location() else {
+            return;
+        };
+        tokens.retain(|t| !matches!(t.token, Token::Whitespace(_)));
+        let constraints = take_exclusion_constraints(&mut tokens);
+        if constraints.is_empty() {
+            return;
+        }
+
+        let name = match Parser::new(dialect.as_ref())
+            .with_tokens_with_locations(tokens.clone())
+            .parse_statements()
+        {
+            Ok(statements) => {
+                let [stmt] = statements.as_slice() else {
+                    return;
+                };
+                let name = match stmt {
+                    Statement::CreateTable(create) => create.name.clone(),
+                    Statement::AlterTable { name, .. } => name.clone(),
+                    _ => return,
+                };
+                self.process_statement(stmt);
+                name
+            }
+            // `ALTER TABLE t ADD CONSTRAINT c EXCLUDE ...` has no operation left
+            Err(_) => {
+                let mut parser = Parser::new(dialect.as_ref()).with_tokens_with_locations(tokens);
+                if !parser.parse_keywords(&[Keyword::ALTER, Keyword::TABLE]) {
+                    return;
+                }
+                let _ = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+                let _ = parser.parse_keyword(Keyword::ONLY);
+                match parser.parse_object_name(false) {
+                    Ok(name)
+                        if matches!(parser.peek_token().token, Token::EOF | Token::SemiColon) =>
+                    {
+                        name
+                    }
+                    _ => return,
                 }
             }
+        };
+
+        let table_name = self.dialect.qualified_name(&name);
+        self.note_dependency(&table_name);
+        let Some(table) = self.catalog.get_table_mut(&table_name) else {
+            return;
+        };
+        for constraint in constraints {
+            let name = constraint.name.unwrap_or_else(|| {
+                format!("{}_{}_excl", table.name.name, constraint.columns.join("_"))
+            });
+            table.exclusion_constraints.push(name);
+        }
+    }
+
+    /// Source location of a name in the statement being processed
+    fn location(&self, span: &sqlparser::tokenizer::Span) -> SourceLocation {
+        let mut span = Span::from_sqlparser(span);
+        let (line, column) = self.statement_origin;
+        if span.line == 1 {
+            span.column += column - 1;
+        }
+        span.line += line - 1;
+        SourceLocation {
+            file: self.file.clone(),
+            span,
+        }
+    }
+
+    /// Note that the statement being processed uses the table or view `name`,
+    /// which may be declared in another file
+    fn note_dependency(&mut self, name: &QualifiedName) {
+        let owner = if let Some(table) = self.catalog.get_table(name) {
+            &table.location
+        } else if let Some(view) = self.catalog.get_view(name) {
+            &view.location
+        } else {
+            return;
+        };
+        let owner = owner.as_ref().and_then(|l| l.file.clone());
+        self.note_owner(owner);
+    }
+
+    /// Note that the current file touches an object declared in `owner`
+    fn note_owner(&mut self, owner: Option<PathBuf>) {
+        if owner == self.file {
+            return;
+        }
+        for file in owner.into_iter().chain(self.file.clone()) {
+            if !self.catalog.is_interdependent(&file) {
+                self.catalog.interdependent_files.push(file);
+            }
         }
     }
 
-    /// Process a single SQL statement
-    fn process_statement(&mut self, stmt: &Statement) {
+    /// Apply a single SQL statement; statements that don't change the schema are ignored
+    pub(crate) fn process_statement(&mut self, stmt: &Statement) {
         match stmt {
             Statement::CreateTable(create) => {
                 self.process_create_table(create);
@@ -119,6 +318,9 @@ impl SchemaBuilder {
             } => {
                 self.process_alter_table(name, operations);
             }
+            Statement::CreateIndex(create_index) => {
+                self.process_create_index(create_index);
+            }
             Statement::Drop {
                 object_type: ObjectType::Table,
                 names,
@@ -134,8 +336,27 @@ impl SchemaBuilder {
 
     /// Process CREATE TABLE statement
     fn process_create_table(&mut self, create: &sqlparser::ast::CreateTable) {
-        let name = object_name_to_qualified(&create.name);
+        let name = self.dialect.qualified_name(&create.name);
+        if self.catalog.table_exists(&name) {
+            self.note_dependency(&name);
+            // Like the database, IF NOT EXISTS leaves the existing table alone
+            if create.if_not_exists {
+                return;
+            }
+            let span = create.name.0.last().map(|id| self.location(&id.span).span);
+            let mut diagnostic = Diagnostic::warning(
+                DiagnosticKind::DuplicateTable,
+                format!("Table '{}' is already defined", name),
+            )
+            .with_help("The later definition replaces the earlier one; use CREATE TABLE IF NOT EXISTS or DROP TABLE first");
+            if let Some(span) = span {
+                diagnostic = diagnostic.with_span(span);
+            }
+            self.diagnostics.push(diagnostic);
+        }
+
         let mut table = TableDef::new(name);
+        table.location = create.name.0.last().map(|id| self.location(&id.span));
 
         // Process columns
         for column in &create.columns {
@@ -143,6 +364,8 @@ impl SchemaBuilder {
             let data_type = SqlType::from_ast(&column.data_type);
 
             let mut col_def = ColumnDef::new(&col_name, data_type);
+            col_def.location = Some(self.location(&column.name.span));
+            col_def.default = serial_default(&table.name, &column.data_type, &col_name);
 
             // Process column options
             for option in &column.options {
@@ -152,14 +375,144 @@ impl SchemaBuilder {
             table.columns.insert(col_name, col_def);
         }
 
+        // CREATE TABLE ... AS SELECT takes its columns from the query; their
+        // types aren't tracked
+        if let (true, Some(query)) = (create.columns.is_empty(), &create.query) {
+            for col_name in self.infer_view_columns(&query.body) {
+                let col_def = ColumnDef::new(&col_name, SqlType::Unknown);
+                table.columns.entry(col_name).or_insert(col_def);
+            }
+        }
+
         // Process table constraints
         for constraint in &create.constraints {
             self.process_table_constraint(&mut table, constraint);
         }
 
+        // CHECK expressions may reference any column, so they are resolved
+        // once the whole column list is known
+        let column_checks = create
+            .columns
+            .iter()
+            .flat_map(|column| &column.options)
+            .filter_map(|option| match &option.option {
+                ColumnOption::Check(expr) => Some(expr),
+                _ => None,
+            });
+        let table_checks = create
+            .constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                TableConstraint::Check { expr, .. } => Some(expr.as_ref()),
+                _ => None,
+            });
+        for expr in column_checks.chain(table_checks) {
+            self.diagnostics
+                .extend(check_constraint_diagnostics(&table, expr));
+        }
+
+        self.check_single_primary_key(create);
+
         self.catalog.add_table(table);
     }
 
+    /// Process CREATE [UNIQUE] INDEX statement
+    ///
+    /// Plain column keys are checked against the table; expression keys
+    /// (e.g. `lower(email)`) are stored as written.
+    fn process_create_index(&mut self, create_index: &CreateIndex) {
+        let table_name = self.dialect.qualified_name(&create_index.table_name);
+        self.note_dependency(&table_name);
+        let Some(table) = self.catalog.get_table_mut(&table_name) else {
+            self.diagnostics.push(
+                Diagnostic::warning(
+                    DiagnosticKind::TableNotFound,
+                    format!(
+                        "CREATE INDEX references table '{}' which was not found in schema",
+                        table_name
+                    ),
+                )
+                .with_help("Ensure the CREATE TABLE statement appears before CREATE INDEX"),
+            );
+            return;
+        };
+
+        for key in &create_index.columns {
+            if let Expr::Identifier(ident) = &key.expr {
+                if !table.column_exists(&ident.value) {
+                    self.diagnostics.push(
+                        Diagnostic::warning(
+                            DiagnosticKind::ColumnNotFound,
+                            format!(
+                                "Indexed column '{}' not found in table '{}'",
+                                ident.value, table_name
+                            ),
+                        )
+                        .with_span(Span::from_sqlparser(&ident.span)),
+                    );
+                }
+            }
+        }
+
+        table.indexes.push(IndexDef {
+            name: create_index.name.as_ref().map(|n| n.to_string()),
+            columns: create_index
+                .columns
+                .iter()
+                .map(|key| match &key.expr {
+                    Expr::Identifier(ident) => ident.value.clone(),
+                    expr => expr.to_string(),
+                })
+                .collect(),
+            unique: create_index.unique,
+            predicate: create_index.predicate.as_ref().map(|p| p.to_string()),
+        });
+    }
+
+    /// Report a table that declares more than one PRIMARY KEY
+    ///
+    /// Each column-level `PRIMARY KEY` and each table-level `PRIMARY KEY (...)`
+    /// counts as one declaration, so a composite key is still a single one.
+    fn check_single_primary_key(&mut self, create: &sqlparser::ast::CreateTable) {
+        let column_pks = create.columns.iter().filter_map(|column| {
+            column
+                .options
+                .iter()
+                .any(|o| {
+                    matches!(
+                        o.option,
+                        ColumnOption::Unique {
+                            is_primary: true,
+                            ..
+                        }
+                    )
+                })
+                .then_some(&column.name)
+        });
+        let table_pks = create.constraints.iter().filter_map(|c| match c {
+            TableConstraint::PrimaryKey { columns, .. } => columns.first(),
+            _ => None,
+        });
+        let declarations: Vec<_> = column_pks.chain(table_pks).collect();
+
+        if let Some(second) = declarations.get(1) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    DiagnosticKind::MultiplePrimaryKeys,
+                    format!(
+                        "Table '{}' declares {} primary keys",
+                        self.dialect.qualified_name(&create.name),
+                        declarations.len()
+                    ),
+                )
+                .with_span(Span::from_sqlparser(&second.span))
+                .with_help(
+                    "A table can have only one primary key; use PRIMARY KEY (a, b) for a composite key",
+                ),
+            );
+        }
+    }
+
     /// Process CREATE VIEW statement
     fn process_create_view(
         &mut self,
@@ -168,7 +521,8 @@ impl SchemaBuilder {
         query: &sqlparser::ast::Query,
         materialized: bool,
     ) {
-        let qualified = object_name_to_qualified(name);
+        let qualified = self.dialect.qualified_name(name);
+        self.note_dependency(&qualified);
 
         // Determine column names: explicit column list or inferred from SELECT
         let column_names = if !columns.is_empty() {
@@ -177,16 +531,27 @@ impl SchemaBuilder {
             self.infer_view_columns(&query.body)
         };
 
+        let mut sources: Vec<QualifiedName> = Vec::new();
+        let _ = visit_relations(query, |relation| {
+            let source = self.dialect.qualified_name(relation);
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+
         let view = ViewDef {
             name: qualified,
             columns: column_names,
+            sources,
             materialized,
+            location: name.0.last().map(|id| self.location(&id.span)),
         };
         self.catalog.add_view(view);
     }
 
     /// Infer column names from a SELECT body for VIEW definition
-    fn infer_view_columns(&self, set_expr: &sqlparser::ast::SetExpr) -> Vec<String> {
+    fn infer_view_columns(&mut self, set_expr: &sqlparser::ast::SetExpr) -> Vec<String> {
         use sqlparser::ast::{Expr, SelectItem, SetExpr};
 
         let mut columns = Vec::new();
@@ -213,7 +578,8 @@ impl SchemaBuilder {
                     }
                     SelectItem::QualifiedWildcard(name, _) => {
                         // table.* - try to expand from the specified table
-                        let table_name = object_name_to_qualified(name);
+                        let table_name = self.dialect.qualified_name(name);
+                        self.note_dependency(&table_name);
                         if let Some(table_def) = self.catalog.get_table(&table_name) {
                             for col_name in table_def.columns.keys() {
                                 columns.push(col_name.clone());
@@ -233,13 +599,14 @@ impl SchemaBuilder {
 
     /// Expand wildcard columns from a table factor
     fn expand_wildcard_columns(
-        &self,
+        &mut self,
         factor: &sqlparser::ast::TableFactor,
         columns: &mut Vec<String>,
     ) {
         use sqlparser::ast::TableFactor;
         if let TableFactor::Table { name, .. } = factor {
-            let table_name = object_name_to_qualified(name);
+            let table_name = self.dialect.qualified_name(name);
+            self.note_dependency(&table_name);
             if let Some(table_def) = self.catalog.get_table(&table_name) {
                 for col_name in table_def.columns.keys() {
                     columns.push(col_name.clone());
@@ -272,7 +639,7 @@ impl SchemaBuilder {
             return;
         }
 
-        let table_name = object_name_to_qualified(name);
+        let table_name = self.dialect.qualified_name(name);
 
         // Check if table exists
         if !self.catalog.table_exists(&table_name) {
@@ -288,6 +655,7 @@ impl SchemaBuilder {
             );
             return;
         }
+        self.note_dependency(&table_name);
 
         for operation in operations {
             match operation {
@@ -295,6 +663,8 @@ impl SchemaBuilder {
                     let col_name = column_def.name.value.clone();
                     let data_type = SqlType::from_ast(&column_def.data_type);
                     let mut col = ColumnDef::new(&col_name, data_type);
+                    col.location = Some(self.location(&column_def.name.span));
+                    col.default = serial_default(&table_name, &column_def.data_type, &col_name);
 
                     // Process column options
                     // We need a temporary mutable table reference for check constraints
@@ -306,44 +676,77 @@ impl SchemaBuilder {
                             ColumnOption::Default(expr) => {
                                 col.default = Some(expr_to_default(expr));
                             }
-                            ColumnOption::Unique { is_primary, .. } => {
-                                if *is_primary {
-                                    col.is_primary_key = true;
-                                    col.nullable = false;
-                                }
+                            ColumnOption::Unique {
+                                is_primary: true, ..
+                            } => {
+                                col.is_primary_key = true;
+                                col.nullable = false;
                             }
                             ColumnOption::Generated {
                                 generated_as,
-                                generation_expr,
+                                generation_expr: None,
                                 ..
                             } => {
-                                if generation_expr.is_none() {
-                                    use sqlparser::ast::GeneratedAs;
-                                    let kind = match generated_as {
-                                        GeneratedAs::Always => IdentityKind::Always,
-                                        GeneratedAs::ByDefault => IdentityKind::ByDefault,
-                                        _ => continue,
-                                    };
-                                    col.identity = Some(kind);
-                                    col.nullable = false;
-                                }
+                                use sqlparser::ast::GeneratedAs;
+                                let kind = match generated_as {
+                                    GeneratedAs::Always => IdentityKind::Always,
+                                    GeneratedAs::ByDefault => IdentityKind::ByDefault,
+                                    _ => continue,
+                                };
+                                col.identity = Some(kind);
+                                col.nullable = false;
                             }
+                            ColumnOption::Generated {
+                                generation_expr: Some(_),
+                                ..
+                            } => col.is_generated = true,
                             _ => {}
                         }
                     }
 
+                    let foreign_keys: Vec<ForeignKeyDef> = column_def
+                        .options
+                        .iter()
+                        .filter_map(|option| self.inline_foreign_key(&col_name, option))
+                        .collect();
                     if let Some(table) = self.catalog.get_table_mut(&table_name) {
-                        // Collect check constraints from column options
+                        table.foreign_keys.extend(foreign_keys);
+                        // Collect check and unique constraints from column options
                         for option in &column_def.options {
-                            if let ColumnOption::Check(expr) = &option.option {
-                                let check = CheckConstraintDef {
-                                    name: option.name.as_ref().map(|n| n.value.clone()),
-                                    expression: expr.to_string(),
-                                };
-                                table.check_constraints.push(check);
+                            let name = option.name.as_ref().map(|n| n.value.clone());
+                            match &option.option {
+                                ColumnOption::Check(expr) => {
+                                    table.check_constraints.push(CheckConstraintDef {
+                                        name,
+                                        expression: expr.to_string(),
+                                    });
+                                }
+                                ColumnOption::Unique {
+                                    is_primary: false, ..
+                                } => {
+                                    table.unique_constraints.push(UniqueConstraintDef {
+                                        name,
+                                        columns: vec![col_name.clone()],
+                                    });
+                                }
+                                ColumnOption::Unique {
+                                    is_primary: true, ..
+                                } => {
+                                    table.primary_key.get_or_insert_with(|| PrimaryKeyDef {
+                                        name,
+                                        columns: vec![col_name.clone()],
+                                    });
+                                }
+                                _ => {}
                             }
                         }
                         table.columns.insert(col_name, col);
+                        for option in &column_def.options {
+                            if let ColumnOption::Check(expr) = &option.option {
+                                self.diagnostics
+                                    .extend(check_constraint_diagnostics(table, expr));
+                            }
+                        }
                     }
                 }
                 AlterTableOperation::DropColumn { column_name, .. } => {
@@ -355,22 +758,58 @@ impl SchemaBuilder {
                     old_column_name,
                     new_column_name,
                 } => {
-                    if let Some(table) = self.catalog.get_table_mut(&table_name) {
-                        if let Some(mut col) = table.columns.shift_remove(&old_column_name.value) {
-                            col.name = new_column_name.value.clone();
-                            table.columns.insert(new_column_name.value.clone(), col);
-                        }
+                    // Foreign keys referencing the column are renamed too
+                    let referencing: Vec<QualifiedName> = self
+                        .catalog
+                        .schemas
+                        .values()
+                        .flat_map(|schema| schema.tables.values())
+                        .filter(|table| {
+                            table
+                                .foreign_keys
+                                .iter()
+                                .any(|fk| fk.references_table.name == table_name.name)
+                        })
+                        .map(|table| table.name.clone())
+                        .collect();
+                    for name in &referencing {
+                        self.note_dependency(name);
+                    }
+                    let renamed = self.catalog.rename_column(
+                        &table_name,
+                        &old_column_name.value,
+                        &new_column_name.value,
+                    );
+                    let collides = self.catalog.get_table(&table_name).is_some_and(|table| {
+                        table.get_column(&old_column_name.value).is_some()
+                            && table.get_column(&new_column_name.value).is_some()
+                    });
+                    if renamed.is_none() && collides {
+                        self.diagnostics.push(
+                            Diagnostic::warning(
+                                DiagnosticKind::DuplicateTable,
+                                format!(
+                                    "Cannot rename column '{}' to '{}': table '{}' already has a column '{}'",
+                                    old_column_name.value,
+                                    new_column_name.value,
+                                    table_name,
+                                    new_column_name.value
+                                ),
+                            )
+                            .with_span(self.location(&new_column_name.span).span)
+                            .with_help("The database rejects this rename; the column keeps its old name"),
+                        );
                     }
                 }
                 AlterTableOperation::RenameTable {
                     table_name: new_name,
                 } => {
-                    let new_qualified = object_name_to_qualified(new_name);
-                    let schema_name = table_name
-                        .schema
-                        .as_ref()
+                    let new_qualified = self.dialect.qualified_name(new_name);
+                    let schema_name = self
+                        .catalog
+                        .resolve_schema(&table_name)
                         .unwrap_or(&self.catalog.default_schema)
-                        .clone();
+                        .to_string();
                     if let Some(schema) = self.catalog.schemas.get_mut(&schema_name) {
                         if let Some(mut table) = schema.tables.shift_remove(&table_name.name) {
                             table.name = new_qualified.clone();
@@ -405,7 +844,7 @@ impl SchemaBuilder {
                                 let fk = crate::schema::ForeignKeyDef {
                                     name: name.as_ref().map(|n| n.value.clone()),
                                     columns: columns.iter().map(|c| c.value.clone()).collect(),
-                                    references_table: object_name_to_qualified(foreign_table),
+                                    references_table: self.dialect.qualified_name(foreign_table),
                                     references_columns: referred_columns
                                         .iter()
                                         .map(|c| c.value.clone())
@@ -426,6 +865,8 @@ impl SchemaBuilder {
                                     expression: expr.to_string(),
                                 };
                                 table.check_constraints.push(check);
+                                self.diagnostics
+                                    .extend(check_constraint_diagnostics(table, expr));
                             }
                             _ => {}
                         }
@@ -440,7 +881,8 @@ impl SchemaBuilder {
 
     /// Process DROP TABLE statement
     fn process_drop_table(&mut self, name: &ObjectName) {
-        let table_name = object_name_to_qualified(name);
+        let table_name = self.dialect.qualified_name(name);
+        self.note_dependency(&table_name);
         self.catalog.drop_table(&table_name);
     }
 
@@ -450,21 +892,61 @@ impl SchemaBuilder {
         name: &ObjectName,
         representation: &UserDefinedTypeRepresentation,
     ) {
-        let qualified = object_name_to_qualified(name);
+        let qualified = self.dialect.qualified_name(name);
         match representation {
             UserDefinedTypeRepresentation::Enum { labels } => {
+                let enum_name = qualified.to_string();
+                if let Some(existing) = self.catalog.get_enum(&enum_name) {
+                    let owner = existing.location.as_ref().and_then(|l| l.file.clone());
+                    self.note_owner(owner);
+                }
                 let enum_def = EnumTypeDef {
-                    name: qualified.name,
+                    name: enum_name,
                     values: labels.iter().map(|l| l.value.clone()).collect(),
+                    location: name.0.last().map(|id| self.location(&id.span)),
                 };
                 self.catalog.add_enum(enum_def);
             }
-            _ => {
-                // Composite types and others - not yet supported
+            UserDefinedTypeRepresentation::Composite { attributes } => {
+                let composite_name = qualified.to_string();
+                if let Some(existing) = self.catalog.get_composite(&composite_name) {
+                    let owner = existing.location.as_ref().and_then(|l| l.file.clone());
+                    self.note_owner(owner);
+                }
+                let composite_def = CompositeTypeDef {
+                    name: composite_name,
+                    fields: attributes
+                        .iter()
+                        .map(|attr| CompositeFieldDef {
+                            name: attr.name.value.clone(),
+                            data_type: SqlType::from_ast(&attr.data_type),
+                        })
+                        .collect(),
+                    location: name.0.last().map(|id| self.location(&id.span)),
+                };
+                self.catalog.add_composite(composite_def);
             }
         }
     }
 
+    /// The foreign key of a column-level `REFERENCES` option
+    fn inline_foreign_key(&self, column: &str, option: &ColumnOptionDef) -> Option<ForeignKeyDef> {
+        let ColumnOption::ForeignKey {
+            foreign_table,
+            referred_columns,
+            ..
+        } = &option.option
+        else {
+            return None;
+        };
+        Some(ForeignKeyDef {
+            name: option.name.as_ref().map(|n| n.value.clone()),
+            columns: vec![column.to_string()],
+            references_table: self.dialect.qualified_name(foreign_table),
+            references_columns: referred_columns.iter().map(|c| c.value.clone()).collect(),
+        })
+    }
+
     /// Process a column option (NOT NULL, DEFAULT, PRIMARY KEY, etc.)
     fn process_column_option(
         &mut self,
@@ -472,6 +954,9 @@ impl SchemaBuilder {
         table: &mut TableDef,
         option: &ColumnOptionDef,
     ) {
+        if let Some(fk) = self.inline_foreign_key(&col.name, option) {
+            table.foreign_keys.push(fk);
+        }
         match &option.option {
             ColumnOption::Null => {
                 col.nullable = true;
@@ -482,11 +967,23 @@ impl SchemaBuilder {
             ColumnOption::Default(expr) => {
                 col.default = Some(expr_to_default(expr));
             }
-            ColumnOption::Unique { is_primary, .. } => {
-                if *is_primary {
-                    col.is_primary_key = true;
-                    col.nullable = false;
-                }
+            ColumnOption::Unique {
+                is_primary: true, ..
+            } => {
+                col.is_primary_key = true;
+                col.nullable = false;
+                table.primary_key.get_or_insert_with(|| PrimaryKeyDef {
+                    name: option.name.as_ref().map(|n| n.value.clone()),
+                    columns: vec![col.name.clone()],
+                });
+            }
+            ColumnOption::Unique {
+                is_primary: false, ..
+            } => {
+                table.unique_constraints.push(UniqueConstraintDef {
+                    name: option.name.as_ref().map(|n| n.value.clone()),
+                    columns: vec![col.name.clone()],
+                });
             }
             ColumnOption::Check(expr) => {
                 let check = CheckConstraintDef {
@@ -497,29 +994,33 @@ impl SchemaBuilder {
             }
             ColumnOption::Generated {
                 generated_as,
-                generation_expr,
+                generation_expr: None,
                 ..
             } => {
                 // IDENTITY columns (no generation expression = IDENTITY, not computed)
-                if generation_expr.is_none() {
-                    use sqlparser::ast::GeneratedAs;
-                    let kind = match generated_as {
-                        GeneratedAs::Always => IdentityKind::Always,
-                        GeneratedAs::ByDefault => IdentityKind::ByDefault,
-                        _ => return,
-                    };
-                    col.identity = Some(kind);
-                    col.nullable = false; // IDENTITY columns are implicitly NOT NULL
-                }
+                use sqlparser::ast::GeneratedAs;
+                let kind = match generated_as {
+                    GeneratedAs::Always => IdentityKind::Always,
+                    GeneratedAs::ByDefault => IdentityKind::ByDefault,
+                    _ => return,
+                };
+                col.identity = Some(kind);
+                col.nullable = false; // IDENTITY columns are implicitly NOT NULL
             }
-            ColumnOption::DialectSpecific(tokens) => {
-                // MySQL AUTO_INCREMENT / SQLite AUTOINCREMENT
-                if tokens
-                    .iter()
-                    .any(|t| matches!(t, Token::Word(w) if w.value == "AUTO_INCREMENT" || w.value == "AUTOINCREMENT"))
-                {
-                    col.nullable = false; // AUTO_INCREMENT/AUTOINCREMENT implies NOT NULL
-                }
+            ColumnOption::Generated {
+                generation_expr: Some(_),
+                ..
+            } => {
+                // Computed column: GENERATED ALWAYS AS (expr) [STORED | VIRTUAL]
+                col.is_generated = true;
+            }
+            // MySQL AUTO_INCREMENT / SQLite AUTOINCREMENT
+            ColumnOption::DialectSpecific(tokens)
+                if tokens.iter().any(|t| {
+                    matches!(t, Token::Word(w) if w.value == "AUTO_INCREMENT" || w.value == "AUTOINCREMENT")
+                }) =>
+            {
+                col.nullable = false; // AUTO_INCREMENT/AUTOINCREMENT implies NOT NULL
             }
             _ => {}
         }
@@ -552,7 +1053,7 @@ impl SchemaBuilder {
                 let fk = ForeignKeyDef {
                     name: name.as_ref().map(|n| n.value.clone()),
                     columns: columns.iter().map(|c| c.value.clone()).collect(),
-                    references_table: object_name_to_qualified(foreign_table),
+                    references_table: self.dialect.qualified_name(foreign_table),
                     references_columns: referred_columns.iter().map(|c| c.value.clone()).collect(),
                 };
                 table.foreign_keys.push(fk);
@@ -576,10 +1077,135 @@ impl SchemaBuilder {
     }
 
     /// Consume the builder and return the catalog
-    pub fn build(self) -> (Catalog, Vec<Diagnostic>) {
+    pub fn build(mut self) -> (Catalog, Vec<Diagnostic>) {
+        self.check_foreign_key_types();
+        self.check_foreign_key_cycles();
         (self.catalog, self.diagnostics)
     }
 
+    /// Report foreign key columns whose type can't be compared for equality
+    /// with the referenced column
+    ///
+    /// Runs once the whole schema is loaded, since a foreign key may
+    /// reference a table declared after it. Columns of custom types
+    /// (domains, types sqlsift doesn't model) are not compared.
+    fn check_foreign_key_types(&mut self) {
+        for schema in self.catalog.schemas.values() {
+            for table in schema.tables.values() {
+                for fk in &table.foreign_keys {
+                    let Some(referenced) = self.catalog.get_table(&fk.references_table) else {
+                        continue;
+                    };
+                    // `REFERENCES t` without columns points at the primary key
+                    let referenced_columns = if fk.references_columns.is_empty() {
+                        referenced
+                            .primary_key
+                            .as_ref()
+                            .map_or(&[][..], |pk| pk.columns.as_slice())
+                    } else {
+                        fk.references_columns.as_slice()
+                    };
+
+                    for (column, referenced_column) in fk.columns.iter().zip(referenced_columns) {
+                        let (Some(col), Some(ref_col)) = (
+                            table.get_column(column),
+                            referenced.get_column(referenced_column),
+                        ) else {
+                            continue;
+                        };
+                        let (ty, ref_ty) = (&col.data_type, &ref_col.data_type);
+                        if matches!(ty, SqlType::Custom(_) | SqlType::Unknown)
+                            || matches!(ref_ty, SqlType::Custom(_) | SqlType::Unknown)
+                            || equality_comparable(ty, ref_ty)
+                        {
+                            continue;
+                        }
+
+                        let mut diagnostic = Diagnostic::warning(
+                            DiagnosticKind::ForeignKeyTypeMismatch,
+                            format!(
+                                "Foreign key column '{}.{}' is {} but references '{}.{}' of type {}",
+                                table.name,
+                                column,
+                                ty.display_name(),
+                                referenced.name,
+                                referenced_column,
+                                ref_ty.display_name()
+                            ),
+                        )
+                        .with_help("Declare the foreign key column with the referenced column's type");
+                        if let Some(location) = &col.location {
+                            diagnostic = diagnostic.with_span(location.span);
+                        }
+                        self.diagnostics.push(diagnostic);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Report tables whose foreign keys reference each other in a cycle
+    ///
+    /// Such tables can't be filled one table at a time. Each group of
+    /// mutually reachable tables is reported once, with its shortest cycle.
+    /// A table referencing itself (a tree) is not a cycle here.
+    fn check_foreign_key_cycles(&mut self) {
+        let tables: Vec<&TableDef> = self
+            .catalog
+            .schemas
+            .values()
+            .flat_map(|schema| schema.tables.values())
+            .collect();
+        let edges: Vec<Vec<usize>> = tables
+            .iter()
+            .enumerate()
+            .map(|(i, table)| {
+                let mut targets: Vec<usize> = table
+                    .foreign_keys
+                    .iter()
+                    .filter_map(|fk| {
+                        let referenced = self.catalog.get_table(&fk.references_table)?;
+                        tables.iter().position(|t| t.name == referenced.name)
+                    })
+                    .filter(|&j| j != i)
+                    .collect();
+                targets.sort_unstable();
+                targets.dedup();
+                targets
+            })
+            .collect();
+        let reach: Vec<Vec<bool>> = (0..tables.len())
+            .map(|i| reachable_from(&edges, i))
+            .collect();
+
+        let mut reported = vec![false; tables.len()];
+        for start in 0..tables.len() {
+            if reported[start] || !reach[start][start] {
+                continue;
+            }
+            for (other, done) in reported.iter_mut().enumerate() {
+                *done |= reach[start][other] && reach[other][start];
+            }
+
+            let cycle: Vec<String> = shortest_cycle(&edges, start)
+                .into_iter()
+                .chain([start])
+                .map(|i| tables[i].name.to_string())
+                .collect();
+            let mut diagnostic = Diagnostic::info(
+                DiagnosticKind::ForeignKeyCycle,
+                format!("Foreign keys form a cycle: {}", cycle.join(" → ")),
+            )
+            .with_help(
+                "Rows can't be inserted one table at a time; make one of the foreign keys nullable or DEFERRABLE",
+            );
+            if let Some(location) = &tables[start].location {
+                diagnostic = diagnostic.with_span(location.span);
+            }
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
     /// Get a reference to the current catalog
     #[allow(dead_code)]
     pub fn catalog(&self) -> &Catalog {
@@ -593,14 +1219,140 @@ impl Default for SchemaBuilder {
     }
 }
 
-/// Convert sqlparser ObjectName to our QualifiedName
-fn object_name_to_qualified(name: &ObjectName) -> QualifiedName {
-    match name.0.as_slice() {
-        [table] => QualifiedName::new(&table.value),
-        [schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        [_catalog, schema, table] => QualifiedName::with_schema(&schema.value, &table.value),
-        _ => QualifiedName::new(name.to_string()),
+/// Warnings for a CHECK expression: column references that `table` lacks,
+/// and comparisons between two of its columns whose types don't match
+/// (`CHECK (starts_on < ends_at)` with a date and an integer column)
+fn check_constraint_diagnostics(table: &TableDef, expr: &Expr) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let _ = visit_expressions(expr, |e| {
+        if let Some(diag) = check_column_comparison(table, e) {
+            diagnostics.push(diag);
+        }
+        let column = match e {
+            Expr::Identifier(ident) => Some(ident),
+            Expr::CompoundIdentifier(idents) => idents.last(),
+            _ => None,
+        };
+        if let Some(ident) = column.filter(|ident| !table.column_exists(&ident.value)) {
+            diagnostics.push(
+                Diagnostic::warning(
+                    DiagnosticKind::ColumnNotFound,
+                    format!(
+                        "Column '{}' in CHECK constraint not found in table '{}'",
+                        ident.value, table.name
+                    ),
+                )
+                .with_span(Span::from_sqlparser(&ident.span)),
+            );
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    diagnostics
+}
+
+/// E0015 (as a warning) for a comparison in a CHECK expression between two
+/// columns of `table` whose types neither way cast implicitly
+fn check_column_comparison(table: &TableDef, expr: &Expr) -> Option<Diagnostic> {
+    let Expr::BinaryOp { left, op, right } = expr else {
+        return None;
+    };
+    if !matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+    ) {
+        return None;
+    }
+    fn column_type<'a>(
+        table: &'a TableDef,
+        expr: &'a Expr,
+    ) -> Option<(&'a sqlparser::ast::Ident, &'a SqlType)> {
+        let ident = match expr {
+            Expr::Identifier(ident) => ident,
+            Expr::CompoundIdentifier(idents) => idents.last()?,
+            _ => return None,
+        };
+        let column = table.get_column(&ident.value)?;
+        (column.data_type != SqlType::Unknown).then_some((ident, &column.data_type))
+    }
+    let ((left_ident, left_type), (right_ident, right_type)) =
+        (column_type(table, left)?, column_type(table, right)?);
+    if left_type.is_compatible_with(right_type) != TypeCompatibility::ExplicitCast
+        || right_type.is_compatible_with(left_type) != TypeCompatibility::ExplicitCast
+    {
+        return None;
+    }
+    Some(
+        Diagnostic::warning(
+            DiagnosticKind::ColumnTypeMismatch,
+            format!(
+                "Column type mismatch in CHECK constraint: {} is {} but {} is {}",
+                left_ident.value,
+                left_type.display_name(),
+                right_ident.value,
+                right_type.display_name()
+            ),
+        )
+        .with_span(Span::from_sqlparser(&left_ident.span))
+        .with_help("Check that the right columns are compared, or use an explicit CAST"),
+    )
+}
+
+/// Nodes reachable from `start` in one or more steps
+fn reachable_from(edges: &[Vec<usize>], start: usize) -> Vec<bool> {
+    let mut seen = vec![false; edges.len()];
+    let mut stack = edges[start].clone();
+    while let Some(node) = stack.pop() {
+        if !seen[node] {
+            seen[node] = true;
+            stack.extend(&edges[node]);
+        }
     }
+    seen
+}
+
+/// The nodes of a shortest path from `start` back to itself, beginning with
+/// `start` (empty if there is none)
+fn shortest_cycle(edges: &[Vec<usize>], start: usize) -> Vec<usize> {
+    let mut parent: Vec<Option<usize>> = vec![None; edges.len()];
+    let mut queue = std::collections::VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &edges[node] {
+            if next == start {
+                let mut path = vec![node];
+                while let Some(prev) = parent[*path.last().unwrap()] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return path;
+            }
+            if parent[next].is_none() {
+                parent[next] = Some(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// The implicit sequence default of a PostgreSQL `SERIAL` column
+fn serial_default(
+    table: &QualifiedName,
+    data_type: &sqlparser::ast::DataType,
+    column: &str,
+) -> Option<DefaultValue> {
+    let sqlparser::ast::DataType::Custom(name, _) = data_type else {
+        return None;
+    };
+    let is_serial = matches!(
+        name.to_string().to_lowercase().as_str(),
+        "serial" | "serial2" | "serial4" | "serial8" | "smallserial" | "bigserial"
+    );
+    is_serial.then(|| DefaultValue::NextVal(format!("nextval('{}_{}_seq')", table.name, column)))
 }
 
 /// Convert expression to DefaultValue
@@ -613,7 +1365,7 @@ fn expr_to_default(expr: &sqlparser::ast::Expr) -> DefaultValue {
         sqlparser::ast::Expr::Function(f) => {
             let func_name = f.name.to_string().to_lowercase();
             if func_name.contains("now") || func_name.contains("current_timestamp") {
-                DefaultValue::CurrentTimestamp
+                DefaultValue::CurrentTimestamp(f.to_string())
             } else if func_name.contains("nextval") {
                 DefaultValue::NextVal(f.to_string())
             } else {
@@ -624,6 +1376,91 @@ fn expr_to_default(expr: &sqlparser::ast::Expr) -> DefaultValue {
     }
 }
 
+/// Whether the types have an equality operator between them, which a
+/// foreign key needs
+///
+/// That is the case when they have a common type, except that a character
+/// type only compares with another character type: `text` casts to `uuid`
+/// as a literal, but a `text` column can't reference a `uuid` one.
+fn equality_comparable(a: &SqlType, b: &SqlType) -> bool {
+    a.is_character() == b.is_character() && SqlType::unify(&[a.clone(), b.clone()]).is_some()
+}
+
+/// An `EXCLUDE` constraint taken out of a statement
+struct ExclusionConstraint {
+    name: Option<String>,
+    /// The column of each element (`expr` for an expression), as
+    /// PostgreSQL's generated name lists them
+    columns: Vec<String>,
+}
+
+/// Remove the `[ADD] [CONSTRAINT name] EXCLUDE [USING method] (...) ...`
+/// elements from a statement's tokens, along with the comma separating them
+/// from the rest, and return what they were
+///
+/// `tokens` must not contain whitespace.
+fn take_exclusion_constraints(tokens: &mut Vec<TokenWithSpan>) -> Vec<ExclusionConstraint> {
+    let is_keyword = |token: &TokenWithSpan, keyword: Keyword| matches!(&token.token, Token::Word(w) if w.keyword == keyword);
+
+    let mut constraints = Vec::new();
+    while let Some(exclude) = tokens.iter().position(|t| is_keyword(t, Keyword::EXCLUDE)) {
+        let mut start = exclude;
+        let mut name = None;
+        if start >= 2 && is_keyword(&tokens[start - 2], Keyword::CONSTRAINT) {
+            if let Token::Word(word) = &tokens[start - 1].token {
+                name = Some(word.value.clone());
+            }
+            start -= 2;
+        }
+        if start >= 1 && is_keyword(&tokens[start - 1], Keyword::ADD) {
+            start -= 1;
+        }
+
+        let mut end = exclude + 1;
+        let mut depth = 0;
+        let mut in_element_list = true;
+        let mut columns = Vec::new();
+        while let Some(token) = tokens.get(end) {
+            // The first parenthesized list holds `column WITH operator` or
+            // `(expr) WITH operator` elements
+            if in_element_list
+                && depth == 1
+                && matches!(tokens[end - 1].token, Token::LParen | Token::Comma)
+            {
+                columns.push(match (&token.token, tokens.get(end + 1)) {
+                    (Token::Word(word), Some(next)) if is_keyword(next, Keyword::WITH) => {
+                        word.value.clone()
+                    }
+                    _ => "expr".to_string(),
+                });
+            }
+            match &token.token {
+                Token::LParen => depth += 1,
+                Token::RParen if depth == 0 => break,
+                Token::RParen => {
+                    depth -= 1;
+                    in_element_list &= depth > 0;
+                }
+                Token::Comma | Token::SemiColon | Token::EOF if depth == 0 => break,
+                _ => {}
+            }
+            end += 1;
+        }
+
+        // Take the separating comma before the element, or else the one after it
+        let range = if start > 0 && tokens[start - 1].token == Token::Comma {
+            start - 1..end
+        } else if tokens.get(end).is_some_and(|t| t.token == Token::Comma) {
+            start..end + 1
+        } else {
+            start..end
+        };
+        tokens.drain(range);
+        constraints.push(ExclusionConstraint { name, columns });
+    }
+    constraints
+}
+
 /// Split SQL text into individual statements by semicolons,
 /// respecting string literals and dollar-quoted strings.
 fn split_sql_statements(sql: &str) -> Vec<&str> {
@@ -777,6 +1614,47 @@ mod tests {
 
         let table = catalog.get_table(&QualifiedName::new("orders")).unwrap();
         assert_eq!(table.columns.len(), 3);
+        assert_eq!(table.foreign_keys.len(), 1);
+        let fk = &table.foreign_keys[0];
+        assert_eq!(fk.columns, vec!["user_id"]);
+        assert_eq!(fk.references_table, QualifiedName::new("users"));
+        assert_eq!(fk.references_columns, vec!["id"]);
+    }
+
+    #[test]
+    fn test_alter_add_column_constraints() {
+        let sql = r#"
+            CREATE TABLE users (id SERIAL PRIMARY KEY);
+            CREATE TABLE orders (id SERIAL PRIMARY KEY, code TEXT UNIQUE);
+            ALTER TABLE orders ADD COLUMN user_id INTEGER REFERENCES users(id);
+            ALTER TABLE orders ADD COLUMN external_id TEXT CONSTRAINT orders_external_key UNIQUE;
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let table = catalog.get_table(&QualifiedName::new("orders")).unwrap();
+        assert_eq!(table.foreign_keys.len(), 1);
+        let fk = &table.foreign_keys[0];
+        assert_eq!(fk.columns, vec!["user_id"]);
+        assert_eq!(fk.references_table, QualifiedName::new("users"));
+
+        let unique: Vec<(Option<&str>, &[String])> = table
+            .unique_constraints
+            .iter()
+            .map(|u| (u.name.as_deref(), u.columns.as_slice()))
+            .collect();
+        assert_eq!(
+            unique,
+            [
+                (None, &["code".to_string()][..]),
+                (
+                    Some("orders_external_key"),
+                    &["external_id".to_string()][..]
+                ),
+            ]
+        );
     }
 
     #[test]
@@ -971,6 +1849,168 @@ mod tests {
         assert!(warnings.is_empty(), "no warnings should be produced");
     }
 
+    #[test]
+    fn test_create_table_if_not_exists_keeps_existing() {
+        let sql = r#"
+            CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+            CREATE TABLE IF NOT EXISTS users (id INTEGER);
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, warnings) = builder.build();
+
+        let users = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        assert_eq!(users.column_names(), vec!["id", "name"]);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_duplicate_create_table_warns() {
+        let sql = "CREATE TABLE users (id INTEGER);\nCREATE TABLE users (id INTEGER, email TEXT);";
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, warnings) = builder.build();
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(warnings[0].kind, DiagnosticKind::DuplicateTable);
+        assert!(!warnings[0].is_error());
+        let span = warnings[0].span.unwrap();
+        assert_eq!((span.line, span.column), (2, 14));
+
+        // The later definition wins
+        let users = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        assert_eq!(users.column_names(), vec!["id", "email"]);
+    }
+
+    #[test]
+    fn test_foreign_key_type_mismatch() {
+        let sql = r#"
+            CREATE TABLE posts (
+                id SERIAL PRIMARY KEY,
+                user_id TEXT,
+                FOREIGN KEY (user_id) REFERENCES users(id)
+            );
+            CREATE TABLE users (id SERIAL PRIMARY KEY);
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (_, warnings) = builder.build();
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(warnings[0].kind, DiagnosticKind::ForeignKeyTypeMismatch);
+        assert!(!warnings[0].is_error());
+        assert!(warnings[0].message.contains("'posts.user_id' is text"));
+        assert_eq!(warnings[0].span.unwrap().line, 4);
+    }
+
+    #[test]
+    fn test_inline_foreign_key_type_mismatch() {
+        let sql = r#"
+            CREATE TABLE users (id SERIAL PRIMARY KEY, token UUID UNIQUE);
+            CREATE TABLE posts (
+                id SERIAL PRIMARY KEY,
+                user_id TEXT REFERENCES users(id),
+                user_token TEXT REFERENCES users(token)
+            );
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (_, warnings) = builder.build();
+
+        let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Foreign key column 'posts.user_id' is text but references 'users.id' of type integer",
+                "Foreign key column 'posts.user_token' is text but references 'users.token' of type uuid",
+            ]
+        );
+        assert!(warnings
+            .iter()
+            .all(|w| w.kind == DiagnosticKind::ForeignKeyTypeMismatch));
+    }
+
+    #[test]
+    fn test_foreign_key_compatible_types() {
+        let sql = r#"
+            CREATE TABLE users (id SERIAL PRIMARY KEY, code VARCHAR(20) UNIQUE);
+            CREATE TABLE posts (
+                id SERIAL PRIMARY KEY,
+                user_id BIGINT REFERENCES users,
+                user_code VARCHAR(50) REFERENCES users(code)
+            );
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (_, warnings) = builder.build();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    fn build_diagnostics(sql: &str) -> Vec<Diagnostic> {
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        builder.build().1
+    }
+
+    #[test]
+    fn test_foreign_key_cycle() {
+        let cycles = build_diagnostics(
+            "CREATE TABLE departments (id INTEGER PRIMARY KEY, manager_id INTEGER);
+            CREATE TABLE employees (id INTEGER PRIMARY KEY, department_id INTEGER REFERENCES departments(id));
+            ALTER TABLE departments ADD CONSTRAINT fk_manager FOREIGN KEY (manager_id) REFERENCES employees(id);",
+        );
+        assert_eq!(cycles.len(), 1, "{:?}", cycles);
+        assert_eq!(
+            cycles[0].message,
+            "Foreign keys form a cycle: departments → employees → departments"
+        );
+        assert_eq!(cycles[0].severity, crate::error::Severity::Info);
+        assert_eq!(cycles[0].code(), "W1010");
+        let span = cycles[0].span.unwrap();
+        assert_eq!((span.line, span.column), (1, 14));
+    }
+
+    #[test]
+    fn test_foreign_key_cycle_reported_once_per_group() {
+        let cycles = build_diagnostics(
+            "CREATE TABLE a (id INTEGER PRIMARY KEY, b_id INTEGER, c_id INTEGER);
+            CREATE TABLE b (id INTEGER PRIMARY KEY, a_id INTEGER REFERENCES a(id));
+            CREATE TABLE c (id INTEGER PRIMARY KEY, b_id INTEGER REFERENCES b(id));
+            ALTER TABLE a ADD FOREIGN KEY (c_id) REFERENCES c(id);
+            ALTER TABLE a ADD FOREIGN KEY (b_id) REFERENCES b(id);",
+        );
+        assert_eq!(cycles.len(), 1, "{:?}", cycles);
+        assert_eq!(cycles[0].message, "Foreign keys form a cycle: a → b → a");
+    }
+
+    #[test]
+    fn test_self_referencing_foreign_key_is_not_a_cycle() {
+        let cycles = build_diagnostics(
+            "CREATE TABLE categories (id INTEGER PRIMARY KEY, parent_id INTEGER REFERENCES categories(id));",
+        );
+        assert!(cycles.is_empty(), "{:?}", cycles);
+    }
+
+    #[test]
+    fn test_acyclic_foreign_keys() {
+        let cycles = build_diagnostics(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);
+            CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id));
+            CREATE TABLE comments (
+                id INTEGER PRIMARY KEY,
+                post_id INTEGER REFERENCES posts(id),
+                user_id INTEGER REFERENCES users(id)
+            );",
+        );
+        assert!(cycles.is_empty(), "{:?}", cycles);
+    }
+
     #[test]
     fn test_drop_table_then_alter_produces_no_warning() {
         // Simulates the Prisma migration pattern: drop old tables, create new ones,
@@ -1006,4 +2046,468 @@ mod tests {
         );
         assert!(warnings.is_empty(), "no warnings should be produced");
     }
+
+    #[test]
+    fn test_composite_primary_key_is_single_declaration() {
+        let sql = r#"
+            CREATE TABLE order_items (
+                order_id INTEGER,
+                item_id INTEGER,
+                PRIMARY KEY (order_id, item_id)
+            );
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, warnings) = builder.build();
+
+        let table = catalog
+            .get_table(&QualifiedName::new("order_items"))
+            .unwrap();
+        assert_eq!(
+            table.primary_key.as_ref().unwrap().columns,
+            vec!["order_id", "item_id"]
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_two_column_primary_keys() {
+        let sql = "CREATE TABLE t (a INTEGER PRIMARY KEY, b INTEGER PRIMARY KEY);";
+
+        let mut builder = SchemaBuilder::new();
+        let diags = builder.parse(sql).unwrap_err();
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].kind, DiagnosticKind::MultiplePrimaryKeys);
+        assert!(diags[0].message.contains("'t'"));
+        // Points at the second declaration
+        assert_eq!(diags[0].span.unwrap().column, 40);
+    }
+
+    #[test]
+    fn test_column_and_table_primary_keys() {
+        let sql = r#"
+            CREATE TABLE t (
+                id INTEGER PRIMARY KEY,
+                code TEXT,
+                PRIMARY KEY (code)
+            );
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        let diags = builder.parse(sql).unwrap_err();
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].kind, DiagnosticKind::MultiplePrimaryKeys);
+    }
+
+    #[test]
+    fn test_create_index_is_stored() {
+        let sql = r#"
+            CREATE TABLE events (id INTEGER PRIMARY KEY, user_id INTEGER, kind TEXT);
+            CREATE INDEX idx_events_user_kind ON events (user_id, kind);
+            CREATE INDEX ON events (lower(kind)) WHERE user_id IS NOT NULL;
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, warnings) = builder.build();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        let table = catalog.get_table(&QualifiedName::new("events")).unwrap();
+        assert_eq!(table.indexes.len(), 2);
+
+        let index = &table.indexes[0];
+        assert_eq!(index.name.as_deref(), Some("idx_events_user_kind"));
+        assert_eq!(index.columns, vec!["user_id", "kind"]);
+        assert!(!index.unique);
+        assert!(index.predicate.is_none());
+
+        let partial = &table.indexes[1];
+        assert!(partial.name.is_none());
+        assert_eq!(partial.columns, vec!["lower(kind)"]);
+        assert_eq!(partial.predicate.as_deref(), Some("user_id IS NOT NULL"));
+    }
+
+    #[test]
+    fn test_create_unique_index_flag() {
+        let sql = r#"
+            CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL);
+            CREATE UNIQUE INDEX users_email_key ON users (email);
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let table = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        assert_eq!(table.indexes.len(), 1);
+        assert!(table.indexes[0].unique);
+    }
+
+    #[test]
+    fn test_create_index_unknown_column_warns() {
+        let sql = r#"
+            CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL);
+            CREATE INDEX idx_users_name ON users (name);
+        "#;
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (_, warnings) = builder.build();
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, DiagnosticKind::ColumnNotFound);
+        assert_eq!(warnings[0].severity, crate::error::Severity::Warning);
+        assert!(warnings[0].message.contains("'name'"));
+    }
+
+    #[test]
+    fn test_serial_has_sequence_default() {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse("CREATE TABLE users (id SERIAL NOT NULL, seq BIGSERIAL, n INTEGER NOT NULL);")
+            .unwrap();
+        let (catalog, _) = builder.build();
+        let table = catalog.get_table(&QualifiedName::new("users")).unwrap();
+
+        let id = table.get_column("id").unwrap();
+        assert!(
+            matches!(&id.default, Some(DefaultValue::NextVal(s)) if s == "nextval('users_id_seq')")
+        );
+        assert!(!id.requires_value());
+        assert!(table.get_column("seq").unwrap().default.is_some());
+        assert!(table.get_column("n").unwrap().requires_value());
+    }
+
+    #[test]
+    fn test_check_constraint_valid_columns() {
+        let warnings = build_diagnostics(
+            "CREATE TABLE products (
+                id INTEGER,
+                price NUMERIC CHECK (price > 0),
+                sku TEXT CHECK (char_length(sku) = 8 AND sku <> ''),
+                created_on DATE CHECK (created_on <= CURRENT_DATE)
+            );
+            ALTER TABLE products ADD CONSTRAINT positive_id CHECK (products.id > 0);",
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_check_constraint_misspelled_column() {
+        let warnings = build_diagnostics(
+            "CREATE TABLE products (id INTEGER, price NUMERIC CHECK (pirce > 0));
+            ALTER TABLE products ADD CONSTRAINT c CHECK (idd > 0);
+            ALTER TABLE products ADD COLUMN qty INTEGER CHECK (quantity >= 0);",
+        );
+        let messages: Vec<&str> = warnings.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Column 'pirce' in CHECK constraint not found in table 'products'",
+                "Column 'idd' in CHECK constraint not found in table 'products'",
+                "Column 'quantity' in CHECK constraint not found in table 'products'",
+            ]
+        );
+        assert!(warnings
+            .iter()
+            .all(|d| d.kind == DiagnosticKind::ColumnNotFound && d.is_warning()));
+        let span = warnings[0].span.unwrap();
+        assert_eq!((span.line, span.column), (1, 57));
+    }
+
+    #[test]
+    fn test_check_constraint_multiple_columns() {
+        // A column CHECK may reference columns declared after it
+        let warnings = build_diagnostics(
+            "CREATE TABLE bookings (
+                starts_at TIMESTAMP CHECK (starts_at < ends_at),
+                ends_at TIMESTAMP,
+                CHECK (ends_at - starts_at < INTERVAL '1 day' OR staus = 'long')
+            );",
+        );
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].message.contains("'staus'"));
+    }
+
+    #[test]
+    fn test_check_constraint_comparable_columns() {
+        let warnings = build_diagnostics(
+            "CREATE TABLE events (
+                id INTEGER,
+                start_date DATE NOT NULL,
+                end_date DATE NOT NULL,
+                min_qty SMALLINT,
+                max_qty BIGINT,
+                CHECK (start_date < end_date),
+                CONSTRAINT qty_range CHECK (min_qty <= max_qty AND events.min_qty >= 0)
+            );",
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_check_constraint_incompatible_columns() {
+        let warnings = build_diagnostics(
+            "CREATE TABLE events (
+                id INTEGER,
+                label TEXT,
+                start_date DATE,
+                CHECK (start_date < end_date),
+                CHECK (id <> label)
+            );",
+        );
+        let messages: Vec<&str> = warnings.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Column 'end_date' in CHECK constraint not found in table 'events'",
+                "Column type mismatch in CHECK constraint: id is integer but label is text",
+            ]
+        );
+        assert_eq!(warnings[1].kind, DiagnosticKind::ColumnTypeMismatch);
+        assert!(warnings[1].is_warning());
+    }
+
+    #[test]
+    fn test_column_default_sql() {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse(
+                "CREATE TABLE events (
+                    id SERIAL,
+                    ref_id INTEGER GENERATED ALWAYS AS IDENTITY,
+                    created_at TIMESTAMP NOT NULL DEFAULT now(),
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    note TEXT DEFAULT NULL,
+                    label TEXT NOT NULL DEFAULT lower('X'),
+                    body TEXT NOT NULL
+                );",
+            )
+            .unwrap();
+        let (catalog, _) = builder.build();
+        let table = catalog.get_table(&QualifiedName::new("events")).unwrap();
+        let default_sql = |name: &str| table.get_column(name).unwrap().default_sql();
+
+        assert_eq!(
+            default_sql("id").as_deref(),
+            Some("nextval('events_id_seq')")
+        );
+        assert_eq!(
+            default_sql("ref_id").as_deref(),
+            Some("GENERATED ALWAYS AS IDENTITY")
+        );
+        assert_eq!(default_sql("created_at").as_deref(), Some("now()"));
+        assert_eq!(default_sql("attempts").as_deref(), Some("0"));
+        assert_eq!(default_sql("note").as_deref(), Some("NULL"));
+        assert_eq!(default_sql("label").as_deref(), Some("lower('X')"));
+        assert_eq!(default_sql("body"), None);
+        assert!(!table.get_column("created_at").unwrap().requires_value());
+        assert!(table.get_column("body").unwrap().requires_value());
+    }
+
+    #[test]
+    fn test_source_locations() {
+        let sql = "CREATE TABLE users (\n    id INTEGER PRIMARY KEY,\n    email TEXT\n);\nALTER TABLE users ADD COLUMN name TEXT;";
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse_file("db/schema.sql", sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let table = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        let location = table.location.as_ref().unwrap();
+        assert_eq!(location.file, Some(PathBuf::from("db/schema.sql")));
+        assert_eq!((location.span.line, location.span.column), (1, 14));
+
+        let email = table
+            .get_column("email")
+            .unwrap()
+            .location
+            .as_ref()
+            .unwrap();
+        assert_eq!((email.span.line, email.span.column), (3, 5));
+        let name = table.get_column("name").unwrap().location.as_ref().unwrap();
+        assert_eq!((name.span.line, name.span.column), (5, 30));
+    }
+
+    #[test]
+    fn test_replace_file() {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse_file("users.sql", "CREATE TABLE users (id INTEGER, name TEXT);")
+            .unwrap();
+        builder
+            .parse_file(
+                "orders.sql",
+                "CREATE TABLE orders (id INTEGER, user_id INTEGER REFERENCES users(id));",
+            )
+            .unwrap();
+        let (catalog, _) = builder.build();
+        assert!(catalog.interdependent_files.is_empty());
+
+        // orders.sql isn't parsed again
+        let mut builder = SchemaBuilder::from_catalog(catalog, SqlDialect::default());
+        builder
+            .replace_file("users.sql", "CREATE TABLE users (id INTEGER, email TEXT);")
+            .unwrap();
+        let (catalog, diagnostics) = builder.build();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        let users = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        assert!(users.column_exists("email"));
+        assert!(!users.column_exists("name"));
+        let orders = catalog.get_table(&QualifiedName::new("orders")).unwrap();
+        assert_eq!(orders.foreign_keys.len(), 1);
+        assert!(catalog.interdependent_files.is_empty());
+    }
+
+    #[test]
+    fn test_replace_file_removes_types_and_views() {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse_file(
+                "types.sql",
+                "CREATE TYPE mood AS ENUM ('ok');\nCREATE VIEW one AS SELECT 1 AS x;",
+            )
+            .unwrap();
+        builder.replace_file("types.sql", "").unwrap();
+        let (catalog, _) = builder.build();
+        assert!(catalog.get_enum("mood").is_none());
+        assert!(catalog.get_view(&QualifiedName::new("one")).is_none());
+    }
+
+    #[test]
+    fn test_cross_file_statements_are_interdependent() {
+        for other in [
+            "ALTER TABLE users ADD COLUMN email TEXT;",
+            "CREATE INDEX users_name ON users (name);",
+            "CREATE VIEW people AS SELECT * FROM users;",
+            "DROP TABLE users;",
+        ] {
+            let mut builder = SchemaBuilder::new();
+            builder
+                .parse_file("users.sql", "CREATE TABLE users (id INTEGER, name TEXT);")
+                .unwrap();
+            builder.parse_file("other.sql", other).unwrap();
+            let (catalog, _) = builder.build();
+            assert!(
+                catalog.is_interdependent(std::path::Path::new("users.sql")),
+                "{}",
+                other
+            );
+            assert!(
+                catalog.is_interdependent(std::path::Path::new("other.sql")),
+                "{}",
+                other
+            );
+        }
+    }
+
+    #[test]
+    fn test_source_locations_view_and_enum() {
+        let sql = "CREATE TYPE mood AS ENUM ('ok', 'sad');
+CREATE VIEW app.recent AS SELECT 1 AS one;";
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse_file("db/schema.sql", sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let mood = catalog.get_enum("mood").unwrap().location.as_ref().unwrap();
+        assert_eq!(
+            (mood.span.line, mood.span.column, mood.span.length),
+            (1, 13, 4)
+        );
+        let view = catalog
+            .get_view(&QualifiedName::with_schema("app", "recent"))
+            .unwrap();
+        let location = view.location.as_ref().unwrap();
+        assert_eq!(location.file, Some(PathBuf::from("db/schema.sql")));
+        assert_eq!((location.span.line, location.span.column), (2, 17));
+    }
+
+    #[test]
+    fn test_source_locations_when_parsing_individually() {
+        // The unsupported statement forces statement-by-statement parsing
+        let sql = "CREATE FUNCTION f() RETURNS trigger AS $$ BEGIN END $$ LANGUAGE plpgsql;\n\n  CREATE TABLE users (id INTEGER);";
+
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        let (catalog, _) = builder.build();
+
+        let table = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        let location = table.location.as_ref().unwrap();
+        assert_eq!(location.file, None);
+        assert_eq!((location.span.line, location.span.column), (3, 16));
+        let id = table.get_column("id").unwrap().location.as_ref().unwrap();
+        assert_eq!((id.span.line, id.span.column), (3, 23));
+    }
+
+    #[test]
+    fn test_load_json_schema() {
+        let json = r#"{
+            "schemas": [
+                {
+                    "name": "public",
+                    "tables": [
+                        {
+                            "name": "users",
+                            "columns": [
+                                { "name": "id", "type": "integer", "nullable": false, "primary_key": true },
+                                { "name": "email", "type": "varchar(255)" },
+                                { "name": "tags", "type": "text[]" },
+                                { "name": "created_at", "type": "timestamptz", "nullable": false, "default": "now()" }
+                            ]
+                        }
+                    ]
+                },
+                {
+                    "name": "audit",
+                    "tables": [
+                        { "name": "Log", "columns": [{ "name": "id", "type": "bigint" }] }
+                    ]
+                }
+            ]
+        }"#;
+        let mut builder = SchemaBuilder::new();
+        builder.load_json(json).unwrap();
+        let (catalog, _) = builder.build();
+
+        let users = catalog.get_table(&QualifiedName::new("users")).unwrap();
+        assert_eq!(
+            users.column_names(),
+            vec!["id", "email", "tags", "created_at"]
+        );
+        let id = users.get_column("id").unwrap();
+        assert!(id.is_primary_key && !id.nullable);
+        assert_eq!(users.primary_key.as_ref().unwrap().columns, vec!["id"]);
+        let email = users.get_column("email").unwrap();
+        assert!(email.nullable);
+        assert_eq!(email.data_type, SqlType::Varchar { length: Some(255) });
+        assert_eq!(
+            users.get_column("tags").unwrap().data_type,
+            SqlType::Array(Box::new(SqlType::Text))
+        );
+        assert!(!users.get_column("created_at").unwrap().requires_value());
+
+        let log = catalog
+            .get_table(&QualifiedName::with_schema("audit", "Log"))
+            .unwrap();
+        assert_eq!(log.get_column("id").unwrap().data_type, SqlType::BigInt);
+    }
+
+    #[test]
+    fn test_load_json_errors() {
+        let mut builder = SchemaBuilder::new();
+        let errors = builder.load_json("{ \"schemas\": [").unwrap_err();
+        assert_eq!(errors[0].kind, DiagnosticKind::ParseError);
+
+        let json = r#"{"schemas": [{"name": "public", "tables": [
+            {"name": "t", "columns": [{"name": "c", "type": "integer integer"}]}
+        ]}]}"#;
+        let errors = builder.load_json(json).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("'integer integer'"));
+    }
 }
@@ -0,0 +1,171 @@
+//! On-disk cache of a built catalog
+//!
+//! Rebuilding the catalog from DDL on every CLI run or LSP start is slow for
+//! large schemas. The cache stores the built catalog together with a key
+//! derived from the schema files and dialect, and is only reused while the
+//! key matches. The warnings the build produced are stored with it, so a
+//! cached run reports the same schema diagnostics as a fresh one.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dialect::SqlDialect;
+use crate::error::Diagnostic;
+
+use super::catalog::Catalog;
+
+/// Layout of the cached data; bump whenever `Catalog` or anything it
+/// contains changes shape, so caches written before the change are rebuilt
+const CACHE_FORMAT: u32 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    /// Version of sqlsift that wrote the cache; other versions ignore it
+    version: String,
+    key: u64,
+    catalog: Catalog,
+    /// Diagnostics from building the catalog
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Catalog {
+    /// Cache key for a catalog built from `files` (path and contents) with `dialect`
+    ///
+    /// The key changes whenever a file is added, removed, renamed or edited,
+    /// and with the cache format.
+    pub fn cache_key(dialect: SqlDialect, files: &[(&Path, &str)]) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&CACHE_FORMAT.to_le_bytes());
+        hasher.write(dialect.to_string().as_bytes());
+        for (path, contents) in files {
+            hasher.write(path.to_string_lossy().as_bytes());
+            hasher.write(contents.as_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// Load a catalog cached under `key`, with the diagnostics from building it
+    ///
+    /// Returns `None` when the cache is missing, unreadable, written by
+    /// another version of sqlsift, or built from different schema files.
+    pub fn load_cached(path: &Path, key: u64) -> Option<(Catalog, Vec<Diagnostic>)> {
+        let contents = fs::read_to_string(path).ok()?;
+        let cache: CacheFile = serde_json::from_str(&contents).ok()?;
+        (cache.version == env!("CARGO_PKG_VERSION") && cache.key == key)
+            .then_some((cache.catalog, cache.diagnostics))
+    }
+
+    /// Write the catalog and the diagnostics from building it to `path` for
+    /// [`load_cached`](Self::load_cached)
+    ///
+    /// Only cache a catalog whose schema files all parsed; the parse errors
+    /// aren't stored.
+    pub fn save_cache(&self, path: &Path, key: u64, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        let cache = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            key,
+            catalog: self.clone(),
+            diagnostics: diagnostics.to_vec(),
+        };
+        let json = serde_json::to_string(&cache)?;
+        fs::write(path, json)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust releases
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Hash `bytes`, followed by a separator so that field boundaries count
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter().chain(&[0xff]) {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{QualifiedName, SchemaBuilder};
+
+    fn cache_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("sqlsift-cache-{}-{name}.json", std::process::id()))
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let schema = "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);";
+        let mut builder = SchemaBuilder::new();
+        builder.parse(schema).unwrap();
+        builder.parse("CREATE TABLE users (id INTEGER);").unwrap();
+        let (catalog, diagnostics) = builder.build();
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+
+        let path = cache_path("round-trip");
+        let key = Catalog::cache_key(SqlDialect::PostgreSQL, &[(Path::new("schema.sql"), schema)]);
+        catalog.save_cache(&path, key, &diagnostics).unwrap();
+
+        let (loaded, cached_diagnostics) =
+            Catalog::load_cached(&path, key).expect("cache should load");
+        let users = loaded.get_table(&QualifiedName::new("users")).unwrap();
+        assert_eq!(users.column_names(), vec!["id"]);
+        assert_eq!(cached_diagnostics.len(), 1);
+        assert_eq!(cached_diagnostics[0].kind, diagnostics[0].kind);
+        assert_eq!(cached_diagnostics[0].message, diagnostics[0].message);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_changed_schema() {
+        let path = cache_path("invalidate");
+        let schema_path = Path::new("schema.sql");
+        let key = Catalog::cache_key(
+            SqlDialect::PostgreSQL,
+            &[(schema_path, "CREATE TABLE a (id INT);")],
+        );
+        Catalog::new().save_cache(&path, key, &[]).unwrap();
+
+        let edited = Catalog::cache_key(
+            SqlDialect::PostgreSQL,
+            &[(schema_path, "CREATE TABLE a (id BIGINT);")],
+        );
+        let other_dialect = Catalog::cache_key(
+            SqlDialect::MySQL,
+            &[(schema_path, "CREATE TABLE a (id INT);")],
+        );
+        assert_ne!(key, edited);
+        assert_ne!(key, other_dialect);
+        assert!(Catalog::load_cached(&path, edited).is_none());
+        assert!(Catalog::load_cached(&path, key).is_some());
+
+        let _ = fs::remove_file(&path);
+        assert!(Catalog::load_cached(&path, key).is_none());
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_file_boundaries() {
+        let a = Catalog::cache_key(
+            SqlDialect::PostgreSQL,
+            &[(Path::new("a.sql"), "x"), (Path::new("b.sql"), "y")],
+        );
+        let b = Catalog::cache_key(
+            SqlDialect::PostgreSQL,
+            &[(Path::new("a.sql"), "xb.sql"), (Path::new(""), "y")],
+        );
+        assert_ne!(a, b);
+    }
+}
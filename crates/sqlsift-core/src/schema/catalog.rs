@@ -1,8 +1,11 @@
 //! Schema catalog - stores table and column definitions
 
+use std::path::{Path, PathBuf};
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::error::Span;
 use crate::types::SqlType;
 
 /// Schema catalog - holds all table/view information
@@ -14,6 +17,19 @@ pub struct Catalog {
     pub default_schema: String,
     /// Enum type definitions (name -> EnumTypeDef)
     pub enums: IndexMap<String, EnumTypeDef>,
+    /// Composite type definitions (name -> CompositeTypeDef)
+    #[serde(default)]
+    pub composites: IndexMap<String, CompositeTypeDef>,
+    /// Schemas searched in order for unqualified names (PostgreSQL `search_path`).
+    /// `default_schema` is always tried last.
+    #[serde(default)]
+    pub search_path: Vec<String>,
+    /// Schema files with statements that change or read objects declared in
+    /// another file (ALTER TABLE, CREATE INDEX, `SELECT *` views, ...), and
+    /// the files declaring those objects. Such files can't be swapped out
+    /// with [`remove_file`](Self::remove_file) on their own.
+    #[serde(default)]
+    pub interdependent_files: Vec<PathBuf>,
 }
 
 impl Catalog {
@@ -22,6 +38,9 @@ impl Catalog {
             schemas: IndexMap::new(),
             default_schema: "public".to_string(),
             enums: IndexMap::new(),
+            composites: IndexMap::new(),
+            search_path: Vec::new(),
+            interdependent_files: Vec::new(),
         };
         // Create default schema
         catalog.schemas.insert(
@@ -63,22 +82,72 @@ impl Catalog {
         schema.tables.insert(table.name.name.clone(), table);
     }
 
+    /// Set the ordered list of schemas consulted for unqualified names
+    pub fn set_search_path(&mut self, schemas: Vec<String>) {
+        self.search_path = schemas;
+    }
+
+    /// Schemas to search for a name, in lookup order
+    fn lookup_schemas<'a>(&'a self, name: &'a QualifiedName) -> Vec<&'a str> {
+        if let Some(schema) = &name.schema {
+            return vec![schema.as_str()];
+        }
+        let mut schemas: Vec<&str> = self.search_path.iter().map(String::as_str).collect();
+        if !schemas.contains(&self.default_schema.as_str()) {
+            schemas.push(&self.default_schema);
+        }
+        schemas
+    }
+
+    /// The schema a table or view name resolves to
+    pub fn resolve_schema(&self, name: &QualifiedName) -> Option<&str> {
+        self.schemas_containing(name).into_iter().next()
+    }
+
+    /// Every search_path schema holding a table or view with this (unqualified) name
+    ///
+    /// More than one entry means the name is ambiguous without a schema qualifier.
+    pub fn schemas_containing(&self, name: &QualifiedName) -> Vec<&str> {
+        self.lookup_schemas(name)
+            .into_iter()
+            .filter_map(|schema_name| {
+                let schema = self.schemas.get(schema_name)?;
+                (schema.tables.contains_key(&name.name) || schema.views.contains_key(&name.name))
+                    .then_some(schema.name.as_str())
+            })
+            .collect()
+    }
+
     /// Look up a table by name
+    ///
+    /// Unqualified names are searched through `search_path`, then `default_schema`.
     pub fn get_table(&self, name: &QualifiedName) -> Option<&TableDef> {
-        let schema_name = name.schema.as_ref().unwrap_or(&self.default_schema);
-        self.schemas
-            .get(schema_name)
-            .and_then(|s| s.tables.get(&name.name))
+        self.lookup_schemas(name)
+            .into_iter()
+            .find_map(|schema_name| self.schemas.get(schema_name)?.tables.get(&name.name))
     }
 
     /// Look up a table by name (mutable)
     pub fn get_table_mut(&mut self, name: &QualifiedName) -> Option<&mut TableDef> {
-        let schema_name = name.schema.as_ref().unwrap_or(&self.default_schema).clone();
+        let schema_name = self.table_schema(name)?.to_string();
         self.schemas
             .get_mut(&schema_name)
             .and_then(|s| s.tables.get_mut(&name.name))
     }
 
+    /// The schema holding a table (not a view) with this name
+    fn table_schema(&self, name: &QualifiedName) -> Option<&str> {
+        self.lookup_schemas(name)
+            .into_iter()
+            .find_map(|schema_name| {
+                let schema = self.schemas.get(schema_name)?;
+                schema
+                    .tables
+                    .contains_key(&name.name)
+                    .then_some(schema.name.as_str())
+            })
+    }
+
     /// Check if a table exists
     pub fn table_exists(&self, name: &QualifiedName) -> bool {
         self.get_table(name).is_some()
@@ -86,27 +155,179 @@ impl Catalog {
 
     /// Add an enum type to the catalog
     pub fn add_enum(&mut self, enum_def: EnumTypeDef) {
-        self.enums.insert(enum_def.name.clone(), enum_def);
+        let key = self.type_key(&enum_def.name).to_string();
+        self.enums.insert(key, enum_def);
     }
 
-    /// Get an enum type by name
+    /// Get an enum type by name, schema-qualified outside the default schema
     pub fn get_enum(&self, name: &str) -> Option<&EnumTypeDef> {
-        self.enums.get(name)
+        self.enums.get(self.type_key(name))
     }
 
     /// Check if an enum type exists
     pub fn enum_exists(&self, name: &str) -> bool {
-        self.enums.contains_key(name)
+        self.get_enum(name).is_some()
+    }
+
+    /// Enum and composite types of the default schema are keyed without it
+    fn type_key<'n>(&self, name: &'n str) -> &'n str {
+        name.strip_prefix(self.default_schema.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+            .unwrap_or(name)
+    }
+
+    /// Add a composite type to the catalog
+    pub fn add_composite(&mut self, composite_def: CompositeTypeDef) {
+        let key = self.type_key(&composite_def.name).to_string();
+        self.composites.insert(key, composite_def);
+    }
+
+    /// Get a composite type by name, schema-qualified outside the default schema
+    pub fn get_composite(&self, name: &str) -> Option<&CompositeTypeDef> {
+        self.composites.get(self.type_key(name))
+    }
+
+    /// The composite type a column type refers to, if any
+    pub fn composite_type(&self, ty: &SqlType) -> Option<&CompositeTypeDef> {
+        let SqlType::Custom(name) = ty else {
+            return None;
+        };
+        self.get_composite(name)
+            .or_else(|| self.get_composite(&name.to_lowercase()))
     }
 
     /// Drop a table from the catalog
     pub fn drop_table(&mut self, name: &QualifiedName) {
-        let schema_name = name.schema.as_ref().unwrap_or(&self.default_schema).clone();
+        let Some(schema_name) = self.table_schema(name).map(str::to_string) else {
+            return;
+        };
         if let Some(schema) = self.schemas.get_mut(&schema_name) {
             schema.tables.shift_remove(&name.name);
         }
     }
 
+    /// Remove the tables, views and types declared in `file`
+    ///
+    /// Only objects whose location names `file` are removed. Whatever other
+    /// files did to them goes too, and changes `file` made to other files'
+    /// objects stay, so this is only exact for files not listed in
+    /// `interdependent_files`.
+    pub fn remove_file(&mut self, file: &Path) {
+        let declared_in = |location: &Option<SourceLocation>| {
+            location
+                .as_ref()
+                .and_then(|l| l.file.as_deref())
+                .is_some_and(|f| f == file)
+        };
+        for schema in self.schemas.values_mut() {
+            schema
+                .tables
+                .retain(|_, table| !declared_in(&table.location));
+            schema.views.retain(|_, view| !declared_in(&view.location));
+        }
+        self.enums.retain(|_, def| !declared_in(&def.location));
+        self.composites.retain(|_, def| !declared_in(&def.location));
+    }
+
+    /// Whether `file` depends on, or is depended on by, another schema file
+    pub fn is_interdependent(&self, file: &Path) -> bool {
+        self.interdependent_files.iter().any(|f| f == file)
+    }
+
+    /// Rename a column of a table, along with everything that names it
+    ///
+    /// The table's primary key, unique constraints, indexes and foreign keys
+    /// are updated, as are foreign keys anywhere in the catalog that
+    /// reference the column. View queries aren't kept, so views can't be
+    /// updated; the views that read from the table are returned for review
+    /// instead. Returns `None` if the table or column doesn't exist, or the
+    /// table already has another column named `new`.
+    pub fn rename_column(
+        &mut self,
+        table: &QualifiedName,
+        old: &str,
+        new: &str,
+    ) -> Option<Vec<QualifiedName>> {
+        let schema_name = self.table_schema(table)?.to_string();
+        let position = self.schemas[&schema_name].tables[&table.name]
+            .columns
+            .keys()
+            .position(|name| name.eq_ignore_ascii_case(old))?;
+        if self.schemas[&schema_name].tables[&table.name]
+            .columns
+            .keys()
+            .enumerate()
+            .any(|(i, name)| i != position && name.eq_ignore_ascii_case(new))
+        {
+            return None;
+        }
+
+        // Foreign keys referencing the table, as (schema, table, index)
+        let references: Vec<(String, String, usize)> = self
+            .schemas
+            .iter()
+            .flat_map(|(schema_key, schema)| {
+                schema.tables.iter().flat_map(move |(table_key, def)| {
+                    def.foreign_keys
+                        .iter()
+                        .enumerate()
+                        .map(move |(idx, fk)| (schema_key, table_key, idx, fk))
+                })
+            })
+            .filter(|(_, _, _, fk)| {
+                fk.references_table.name == table.name
+                    && self.table_schema(&fk.references_table) == Some(schema_name.as_str())
+            })
+            .map(|(schema_key, table_key, idx, _)| (schema_key.clone(), table_key.clone(), idx))
+            .collect();
+
+        let target = self
+            .schemas
+            .get_mut(&schema_name)?
+            .tables
+            .get_mut(&table.name)?;
+        let (_, mut column) = target.columns.shift_remove_index(position)?;
+        column.name = new.to_string();
+        target
+            .columns
+            .shift_insert(position, new.to_string(), column);
+        if let Some(pk) = &mut target.primary_key {
+            rename_in(&mut pk.columns, old, new);
+        }
+        for unique in &mut target.unique_constraints {
+            rename_in(&mut unique.columns, old, new);
+        }
+        for index in &mut target.indexes {
+            rename_in(&mut index.columns, old, new);
+        }
+        for fk in &mut target.foreign_keys {
+            rename_in(&mut fk.columns, old, new);
+        }
+
+        for (schema_key, table_key, idx) in references {
+            let fk = &mut self.schemas[&schema_key].tables[&table_key].foreign_keys[idx];
+            rename_in(&mut fk.references_columns, old, new);
+        }
+
+        Some(
+            self.schemas
+                .iter()
+                .flat_map(|(schema_name, schema)| {
+                    schema
+                        .views
+                        .iter()
+                        .filter(|(_, view)| {
+                            view.sources.iter().any(|source| {
+                                source.name == table.name
+                                    && self.table_schema(source) == Some(schema_name.as_str())
+                            })
+                        })
+                        .map(move |(name, _)| QualifiedName::with_schema(schema_name, name))
+                })
+                .collect(),
+        )
+    }
+
     /// Add a view to the catalog
     pub fn add_view(&mut self, view: ViewDef) {
         let schema_name = view
@@ -120,10 +341,9 @@ impl Catalog {
 
     /// Look up a view by name
     pub fn get_view(&self, name: &QualifiedName) -> Option<&ViewDef> {
-        let schema_name = name.schema.as_ref().unwrap_or(&self.default_schema);
-        self.schemas
-            .get(schema_name)
-            .and_then(|s| s.views.get(&name.name))
+        self.lookup_schemas(name)
+            .into_iter()
+            .find_map(|schema_name| self.schemas.get(schema_name)?.views.get(&name.name))
     }
 
     /// Check if a view exists
@@ -163,6 +383,13 @@ impl Catalog {
     }
 }
 
+/// Replace a column name in a column list
+fn rename_in(columns: &mut [String], old: &str, new: &str) {
+    for column in columns.iter_mut().filter(|c| c.eq_ignore_ascii_case(old)) {
+        *column = new.to_string();
+    }
+}
+
 /// A database schema (namespace)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Schema {
@@ -213,6 +440,39 @@ impl std::fmt::Display for QualifiedName {
     }
 }
 
+/// Fully-qualified reference to a column (schema.table.column)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ColumnRef {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+}
+
+impl ColumnRef {
+    pub fn new(
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        column: impl Into<String>,
+    ) -> Self {
+        Self {
+            schema: schema.into(),
+            table: table.into(),
+            column: column.into(),
+        }
+    }
+
+    /// The table (or view) this column belongs to
+    pub fn table_name(&self) -> QualifiedName {
+        QualifiedName::with_schema(&self.schema, &self.table)
+    }
+}
+
+impl std::fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.schema, self.table, self.column)
+    }
+}
+
 /// Table definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableDef {
@@ -222,6 +482,14 @@ pub struct TableDef {
     pub foreign_keys: Vec<ForeignKeyDef>,
     pub unique_constraints: Vec<UniqueConstraintDef>,
     pub check_constraints: Vec<CheckConstraintDef>,
+    /// Names of `EXCLUDE` constraints, which are only tracked by name
+    #[serde(default)]
+    pub exclusion_constraints: Vec<String>,
+    #[serde(default)]
+    pub indexes: Vec<IndexDef>,
+    /// Where the table name appears in its CREATE TABLE
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
 }
 
 impl TableDef {
@@ -233,6 +501,9 @@ impl TableDef {
             foreign_keys: Vec::new(),
             unique_constraints: Vec::new(),
             check_constraints: Vec::new(),
+            exclusion_constraints: Vec::new(),
+            indexes: Vec::new(),
+            location: None,
         }
     }
 
@@ -254,6 +525,71 @@ impl TableDef {
     pub fn column_names(&self) -> Vec<&str> {
         self.columns.keys().map(|s| s.as_str()).collect()
     }
+
+    /// Whether an index, primary key or unique constraint leads with the
+    /// column, so that lookups by it alone can use an index
+    pub fn has_index_on(&self, column: &str) -> bool {
+        let leads = |columns: &[String]| {
+            columns
+                .first()
+                .is_some_and(|c| c.eq_ignore_ascii_case(column))
+        };
+        self.get_column(column).is_some_and(|c| c.is_primary_key)
+            || self
+                .primary_key
+                .as_ref()
+                .is_some_and(|pk| leads(&pk.columns))
+            || self.unique_constraints.iter().any(|u| leads(&u.columns))
+            || self.indexes.iter().any(|i| leads(&i.columns))
+    }
+
+    /// Column sets that identify at most one row: the primary key, unique
+    /// constraints and non-partial unique indexes
+    pub fn unique_keys(&self) -> Vec<Vec<&str>> {
+        fn names(columns: &[String]) -> Vec<&str> {
+            columns.iter().map(String::as_str).collect()
+        }
+        let primary_key: Vec<&str> = match &self.primary_key {
+            Some(pk) => names(&pk.columns),
+            None => self
+                .columns
+                .values()
+                .filter(|c| c.is_primary_key)
+                .map(|c| c.name.as_str())
+                .collect(),
+        };
+        let uniques = self.unique_constraints.iter().map(|u| names(&u.columns));
+        let indexes = self
+            .indexes
+            .iter()
+            .filter(|i| i.unique && i.predicate.is_none())
+            .map(|i| names(&i.columns));
+        std::iter::once(primary_key)
+            .filter(|pk| !pk.is_empty())
+            .chain(uniques)
+            .chain(indexes)
+            .collect()
+    }
+
+    /// Names of the primary key and unique constraints, as `ON CONFLICT ON
+    /// CONSTRAINT` refers to them
+    ///
+    /// Unnamed constraints get PostgreSQL's generated names: `users_pkey` for
+    /// the primary key and `users_email_key` for a unique constraint on `email`.
+    pub fn unique_constraint_names(&self) -> Vec<String> {
+        let table = &self.name.name;
+        let primary_key = self
+            .primary_key
+            .as_ref()
+            .map(|pk| pk.name.clone().unwrap_or_else(|| format!("{}_pkey", table)));
+        let uniques = self.unique_constraints.iter().map(|unique| {
+            unique
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}_{}_key", table, unique.columns.join("_")))
+        });
+        primary_key.into_iter().chain(uniques).collect()
+    }
 }
 
 /// Column definition
@@ -265,6 +601,12 @@ pub struct ColumnDef {
     pub default: Option<DefaultValue>,
     pub is_primary_key: bool,
     pub identity: Option<IdentityKind>,
+    /// Computed column (`GENERATED ALWAYS AS (expr) STORED`); not writable
+    #[serde(default)]
+    pub is_generated: bool,
+    /// Where the column is declared (CREATE TABLE or ALTER TABLE ADD COLUMN)
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
 }
 
 impl ColumnDef {
@@ -276,6 +618,8 @@ impl ColumnDef {
             default: None,
             is_primary_key: false,
             identity: None,
+            is_generated: false,
+            location: None,
         }
     }
 
@@ -289,6 +633,24 @@ impl ColumnDef {
         self
     }
 
+    /// SQL text of the value the column takes when an INSERT omits it:
+    /// its DEFAULT expression, or the identity clause that generates it
+    pub fn default_sql(&self) -> Option<String> {
+        if let Some(default) = &self.default {
+            return Some(default.to_string());
+        }
+        self.identity.as_ref().map(|kind| match kind {
+            IdentityKind::Always => "GENERATED ALWAYS AS IDENTITY".to_string(),
+            IdentityKind::ByDefault => "GENERATED BY DEFAULT AS IDENTITY".to_string(),
+        })
+    }
+
+    /// Whether an INSERT must supply a value: NOT NULL with no default,
+    /// identity or generation expression to fall back on
+    pub fn requires_value(&self) -> bool {
+        !self.nullable && self.default.is_none() && self.identity.is_none() && !self.is_generated
+    }
+
     pub fn primary_key(mut self) -> Self {
         self.is_primary_key = true;
         self.nullable = false;
@@ -296,16 +658,38 @@ impl ColumnDef {
     }
 }
 
+/// Declaration site of a schema object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Schema file, if the SQL was read from one
+    pub file: Option<PathBuf>,
+    /// Span of the declared name within that file
+    pub span: Span,
+}
+
 /// Default value for a column
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DefaultValue {
     Literal(String),
     Expression(String),
-    CurrentTimestamp,
+    /// `now()`, `CURRENT_TIMESTAMP` and similar, as written
+    CurrentTimestamp(String),
     Null,
     NextVal(String), // For sequences/SERIAL
 }
 
+impl std::fmt::Display for DefaultValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultValue::Literal(sql)
+            | DefaultValue::Expression(sql)
+            | DefaultValue::CurrentTimestamp(sql)
+            | DefaultValue::NextVal(sql) => f.write_str(sql),
+            DefaultValue::Null => f.write_str("NULL"),
+        }
+    }
+}
+
 /// Primary key constraint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrimaryKeyDef {
@@ -336,11 +720,52 @@ pub struct CheckConstraintDef {
     pub expression: String,
 }
 
+/// Index definition (CREATE [UNIQUE] INDEX)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexDef {
+    pub name: Option<String>,
+    /// Indexed columns or expressions, in key order
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// Partial index predicate (`WHERE ...`)
+    pub predicate: Option<String>,
+}
+
 /// Enum type definition (CREATE TYPE ... AS ENUM)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumTypeDef {
     pub name: String,
     pub values: Vec<String>,
+    /// Where the type name appears in its CREATE TYPE
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+}
+
+/// Composite type definition (CREATE TYPE ... AS (...))
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeTypeDef {
+    pub name: String,
+    /// Fields in declaration order
+    pub fields: Vec<CompositeFieldDef>,
+    /// Where the type name appears in its CREATE TYPE
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+}
+
+impl CompositeTypeDef {
+    /// Get a field by name (case-insensitive)
+    pub fn get_field(&self, name: &str) -> Option<&CompositeFieldDef> {
+        self.fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A field of a composite type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeFieldDef {
+    pub name: String,
+    pub data_type: SqlType,
 }
 
 /// Identity column kind (GENERATED ... AS IDENTITY)
@@ -355,7 +780,13 @@ pub enum IdentityKind {
 pub struct ViewDef {
     pub name: QualifiedName,
     pub columns: Vec<String>,
+    /// Tables and views the query reads from, as written
+    #[serde(default)]
+    pub sources: Vec<QualifiedName>,
     pub materialized: bool,
+    /// Where the view name appears in its CREATE VIEW
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
 }
 
 #[cfg(test)]
@@ -382,4 +813,70 @@ mod tests {
         assert!(catalog.table_exists(&QualifiedName::new("users")));
         assert!(catalog.table_exists(&QualifiedName::with_schema("public", "users")));
     }
+
+    #[test]
+    fn test_catalog_search_path() {
+        let mut catalog = Catalog::new();
+        catalog.add_table(TableDef::new(QualifiedName::with_schema("app", "users")));
+        catalog.add_table(TableDef::new(QualifiedName::new("users")));
+        catalog.add_table(TableDef::new(QualifiedName::with_schema("app", "events")));
+
+        let users = QualifiedName::new("users");
+        assert_eq!(catalog.resolve_schema(&users), Some("public"));
+        assert!(!catalog.table_exists(&QualifiedName::new("events")));
+
+        catalog.set_search_path(vec!["app".to_string()]);
+        assert_eq!(catalog.resolve_schema(&users), Some("app"));
+        assert!(catalog.table_exists(&QualifiedName::new("events")));
+
+        // Qualified names ignore the search path
+        let public_users = QualifiedName::with_schema("public", "users");
+        assert_eq!(catalog.resolve_schema(&public_users), Some("public"));
+
+        catalog.drop_table(&users);
+        assert_eq!(catalog.resolve_schema(&users), Some("public"));
+    }
+
+    #[test]
+    fn test_rename_column_updates_references() {
+        let mut builder = crate::schema::SchemaBuilder::new();
+        builder
+            .parse(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT);
+                CREATE TABLE orders (id INTEGER, user_id INTEGER REFERENCES users (id));
+                CREATE VIEW user_ids AS SELECT id AS uid FROM users;
+                CREATE VIEW order_ids AS SELECT id FROM orders;
+                CREATE VIEW order_users AS SELECT o.id FROM orders o JOIN users u ON u.id = o.user_id;",
+            )
+            .unwrap();
+        let (mut catalog, _) = builder.build();
+        let users = QualifiedName::new("users");
+
+        let views = catalog.rename_column(&users, "id", "user_id").unwrap();
+        assert_eq!(
+            views,
+            vec![
+                QualifiedName::with_schema("public", "user_ids"),
+                QualifiedName::with_schema("public", "order_users"),
+            ]
+        );
+
+        let table = catalog.get_table(&users).unwrap();
+        assert_eq!(table.column_names(), vec!["user_id", "email", "name"]);
+        assert_eq!(table.get_column("user_id").unwrap().name, "user_id");
+        let orders = catalog.get_table(&QualifiedName::new("orders")).unwrap();
+        assert_eq!(orders.foreign_keys[0].references_columns, vec!["user_id"]);
+        // The referencing column itself keeps its name
+        assert_eq!(orders.foreign_keys[0].columns, vec!["user_id"]);
+
+        assert!(catalog.rename_column(&users, "missing", "x").is_none());
+        // Renaming onto another column's name, also from the last column
+        assert!(catalog.rename_column(&users, "name", "email").is_none());
+        assert!(catalog.rename_column(&users, "user_id", "NAME").is_none());
+        let table = catalog.get_table(&users).unwrap();
+        assert_eq!(table.column_names(), vec!["user_id", "email", "name"]);
+        assert!(catalog
+            .rename_column(&QualifiedName::new("missing"), "id", "x")
+            .is_none());
+    }
 }
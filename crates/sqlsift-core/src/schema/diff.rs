@@ -0,0 +1,525 @@
+//! Structural differences between two catalogs
+//!
+//! Used to review a migration: build one catalog from the schema before it and
+//! one from the schema after, and list what changed. Only tables and their
+//! columns are compared; views, types and constraints are not.
+//!
+//! [`Catalog::destructive_changes`] picks out the changes that can lose data
+//! or fail on a table that already has rows (W1004).
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Diagnostic, DiagnosticKind};
+use crate::types::SqlType;
+
+use super::catalog::{Catalog, QualifiedName, TableDef};
+
+/// A change from one catalog to another, as reported by [`Catalog::diff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum SchemaChange {
+    TableAdded {
+        table: QualifiedName,
+    },
+    TableRemoved {
+        table: QualifiedName,
+    },
+    ColumnAdded {
+        table: QualifiedName,
+        column: String,
+        data_type: SqlType,
+    },
+    ColumnRemoved {
+        table: QualifiedName,
+        column: String,
+        data_type: SqlType,
+    },
+    /// A column replaced by one of the same type at the same position
+    ///
+    /// This is a guess: the schemas alone can't tell a rename from a column
+    /// dropped and another added in its place, so it counts as destructive.
+    ColumnRenamed {
+        table: QualifiedName,
+        from: String,
+        to: String,
+    },
+    ColumnTypeChanged {
+        table: QualifiedName,
+        column: String,
+        from: SqlType,
+        to: SqlType,
+    },
+}
+
+impl SchemaChange {
+    /// Whether the change may drop data (a removed table or column, or a
+    /// column that may have been dropped rather than renamed)
+    pub fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            SchemaChange::TableRemoved { .. }
+                | SchemaChange::ColumnRemoved { .. }
+                | SchemaChange::ColumnRenamed { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaChange::TableAdded { table } => write!(f, "added table {}", table),
+            SchemaChange::TableRemoved { table } => write!(f, "removed table {}", table),
+            SchemaChange::ColumnAdded {
+                table,
+                column,
+                data_type,
+            } => write!(
+                f,
+                "added column {}.{} ({})",
+                table,
+                column,
+                data_type.display_name()
+            ),
+            SchemaChange::ColumnRemoved {
+                table,
+                column,
+                data_type,
+            } => write!(
+                f,
+                "removed column {}.{} ({})",
+                table,
+                column,
+                data_type.display_name()
+            ),
+            SchemaChange::ColumnRenamed { table, from, to } => {
+                write!(f, "possibly renamed column {}.{} to {}", table, from, to)
+            }
+            SchemaChange::ColumnTypeChanged {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "changed type of {}.{} from {} to {}",
+                table,
+                column,
+                from.display_name(),
+                to.display_name()
+            ),
+        }
+    }
+}
+
+impl Catalog {
+    /// The changes that turn this catalog into `other`
+    ///
+    /// Tables are matched by schema and name, columns by name. A column that
+    /// disappears while one of the same type appears at the same position is
+    /// reported as a possible rename rather than a removal and an addition;
+    /// [`SchemaChange::is_destructive`] still treats it as a removal.
+    pub fn diff(&self, other: &Catalog) -> Vec<SchemaChange> {
+        let mut changes = Vec::new();
+        for (name, old) in self.qualified_tables() {
+            match other.table_in_schema(&name) {
+                Some(new) => diff_columns(&name, old, new, &mut changes),
+                None => changes.push(SchemaChange::TableRemoved { table: name }),
+            }
+        }
+        for (name, _) in other.qualified_tables() {
+            if self.table_in_schema(&name).is_none() {
+                changes.push(SchemaChange::TableAdded { table: name });
+            }
+        }
+        changes
+    }
+
+    /// W1004: the changes to `other` that can lose data or fail on existing rows
+    ///
+    /// Dropped tables and columns, type changes that narrow a column
+    /// (`varchar(100)` to `varchar(10)`, `bigint` to `integer`) or move it to
+    /// a type with no common type (`text` to `integer`), and NOT NULL columns
+    /// without a default added to an existing table, which fail unless the
+    /// table is empty. A column that [`Catalog::diff`] takes for a rename may
+    /// just as well have been dropped, so it is reported too.
+    pub fn destructive_changes(&self, other: &Catalog) -> Vec<Diagnostic> {
+        self.diff(other)
+            .into_iter()
+            .filter_map(|change| {
+                let (message, help) = match &change {
+                    SchemaChange::TableRemoved { table } => (
+                        format!("Table '{}' is dropped", table),
+                        "Its rows are deleted with it",
+                    ),
+                    SchemaChange::ColumnRemoved { table, column, .. } => (
+                        format!("Column '{}' of table '{}' is dropped", column, table),
+                        "Its values are deleted with it",
+                    ),
+                    SchemaChange::ColumnRenamed { table, from, to } => (
+                        format!(
+                            "Column '{}' of table '{}' is possibly dropped: it was replaced by '{}'",
+                            from, table, to
+                        ),
+                        "If this is a rename, use ALTER TABLE ... RENAME COLUMN to keep its values; otherwise they are deleted",
+                    ),
+                    SchemaChange::ColumnTypeChanged {
+                        table,
+                        column,
+                        from,
+                        to,
+                    } if needs_conversion(from, to) => (
+                        format!(
+                            "Column '{}' of table '{}' changes from {} to {}, which existing values may not convert to",
+                            column,
+                            table,
+                            from.display_name(),
+                            to.display_name()
+                        ),
+                        "Existing values that can't be converted make the migration fail",
+                    ),
+                    SchemaChange::ColumnTypeChanged {
+                        table,
+                        column,
+                        from,
+                        to,
+                    } if narrows(from, to) => (
+                        format!(
+                            "Column '{}' of table '{}' narrows from {} to {}",
+                            column,
+                            table,
+                            from.display_name(),
+                            to.display_name()
+                        ),
+                        "Existing values that don't fit the new type make the migration fail",
+                    ),
+                    SchemaChange::ColumnAdded { table, column, .. }
+                        if other
+                            .table_in_schema(table)
+                            .and_then(|def| def.get_column(column))
+                            .is_some_and(|col| col.requires_value()) =>
+                    {
+                        (
+                            format!(
+                                "NOT NULL column '{}' is added to table '{}' without a default",
+                                column, table
+                            ),
+                            "Existing rows have no value for it, so this fails unless the table is empty; add a DEFAULT",
+                        )
+                    }
+                    _ => return None,
+                };
+                Some(
+                    Diagnostic::warning(DiagnosticKind::DestructiveMigration, message)
+                        .with_help(help),
+                )
+            })
+            .collect()
+    }
+
+    /// Every table with its schema-qualified name
+    fn qualified_tables(&self) -> impl Iterator<Item = (QualifiedName, &TableDef)> {
+        self.schemas.iter().flat_map(|(schema_name, schema)| {
+            schema
+                .tables
+                .iter()
+                .map(move |(name, table)| (QualifiedName::with_schema(schema_name, name), table))
+        })
+    }
+
+    /// Look up a table by schema-qualified name, without the search path
+    fn table_in_schema(&self, name: &QualifiedName) -> Option<&TableDef> {
+        self.schemas
+            .get(name.schema.as_deref()?)?
+            .tables
+            .get(&name.name)
+    }
+}
+
+/// Whether changing a column from `from` to `to` has to convert each value
+/// into an unrelated type: types with no common type, or text parsed into
+/// anything else (`text` to `uuid`)
+fn needs_conversion(from: &SqlType, to: &SqlType) -> bool {
+    (from.is_character() && !to.is_character())
+        || SqlType::unify(&[from.clone(), to.clone()]).is_none()
+}
+
+/// Whether changing a column from `from` to `to` can truncate or reject values
+fn narrows(from: &SqlType, to: &SqlType) -> bool {
+    match (from, to) {
+        (
+            SqlType::Decimal {
+                precision: from_precision,
+                scale: from_scale,
+            },
+            SqlType::Decimal {
+                precision: to_precision,
+                scale: to_scale,
+            },
+        ) => match (from_precision, to_precision) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(from_precision), Some(to_precision)) => {
+                let (from_scale, to_scale) = (from_scale.unwrap_or(0), to_scale.unwrap_or(0));
+                to_scale < from_scale
+                    || to_precision.saturating_sub(to_scale)
+                        < from_precision.saturating_sub(from_scale)
+            }
+        },
+        // Otherwise a change is safe when the new type is the common type of both
+        _ => SqlType::unify(&[from.clone(), to.clone()]).is_some_and(|common| common != *to),
+    }
+}
+
+fn diff_columns(
+    table: &QualifiedName,
+    old: &TableDef,
+    new: &TableDef,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let mut added: Vec<(usize, &str, &SqlType)> = new
+        .columns
+        .values()
+        .enumerate()
+        .filter(|(_, col)| !old.column_exists(&col.name))
+        .map(|(idx, col)| (idx, col.name.as_str(), &col.data_type))
+        .collect();
+
+    for (idx, col) in old.columns.values().enumerate() {
+        match new.get_column(&col.name) {
+            Some(new_col) if new_col.data_type != col.data_type => {
+                changes.push(SchemaChange::ColumnTypeChanged {
+                    table: table.clone(),
+                    column: col.name.clone(),
+                    from: col.data_type.clone(),
+                    to: new_col.data_type.clone(),
+                });
+            }
+            Some(_) => {}
+            None => {
+                let renamed = added
+                    .iter()
+                    .position(|&(i, _, ty)| i == idx && *ty == col.data_type);
+                match renamed {
+                    Some(pos) => {
+                        let (_, to, _) = added.remove(pos);
+                        changes.push(SchemaChange::ColumnRenamed {
+                            table: table.clone(),
+                            from: col.name.clone(),
+                            to: to.to_string(),
+                        });
+                    }
+                    None => changes.push(SchemaChange::ColumnRemoved {
+                        table: table.clone(),
+                        column: col.name.clone(),
+                        data_type: col.data_type.clone(),
+                    }),
+                }
+            }
+        }
+    }
+
+    changes.extend(
+        added
+            .into_iter()
+            .map(|(_, column, data_type)| SchemaChange::ColumnAdded {
+                table: table.clone(),
+                column: column.to_string(),
+                data_type: data_type.clone(),
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaBuilder;
+
+    fn catalog(sql: &str) -> Catalog {
+        let mut builder = SchemaBuilder::new();
+        builder.parse(sql).unwrap();
+        builder.build().0
+    }
+
+    fn users() -> QualifiedName {
+        QualifiedName::with_schema("public", "users")
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let sql = "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);";
+        assert!(catalog(sql).diff(&catalog(sql)).is_empty());
+    }
+
+    #[test]
+    fn test_diff_dropped_column() {
+        let before = catalog("CREATE TABLE users (id INTEGER, name TEXT, email TEXT);");
+        let after = catalog("CREATE TABLE users (id INTEGER, name TEXT);");
+        let changes = before.diff(&after);
+        assert_eq!(
+            changes,
+            vec![SchemaChange::ColumnRemoved {
+                table: users(),
+                column: "email".to_string(),
+                data_type: SqlType::Text,
+            }]
+        );
+        assert!(changes[0].is_destructive());
+        assert_eq!(
+            changes[0].to_string(),
+            "removed column public.users.email (text)"
+        );
+    }
+
+    #[test]
+    fn test_diff_added_table() {
+        let before = catalog("CREATE TABLE users (id INTEGER);");
+        let after = catalog(
+            "CREATE TABLE users (id INTEGER); CREATE TABLE orders (id INTEGER, user_id INTEGER);",
+        );
+        let changes = before.diff(&after);
+        assert_eq!(
+            changes,
+            vec![SchemaChange::TableAdded {
+                table: QualifiedName::with_schema("public", "orders"),
+            }]
+        );
+        assert!(!changes[0].is_destructive());
+        assert!(after.diff(&before)[0].is_destructive());
+    }
+
+    #[test]
+    fn test_diff_column_type_change() {
+        let before = catalog("CREATE TABLE users (id INTEGER, name TEXT);");
+        let after = catalog("CREATE TABLE users (id BIGINT, name TEXT);");
+        let changes = before.diff(&after);
+        assert_eq!(
+            changes,
+            vec![SchemaChange::ColumnTypeChanged {
+                table: users(),
+                column: "id".to_string(),
+                from: SqlType::Integer,
+                to: SqlType::BigInt,
+            }]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "changed type of public.users.id from integer to bigint"
+        );
+    }
+
+    #[test]
+    fn test_diff_renamed_and_added_columns() {
+        let before = catalog("CREATE TABLE users (id INTEGER, mail TEXT);");
+        let after = catalog("CREATE TABLE users (id INTEGER, email TEXT, age INTEGER);");
+        let changes = before.diff(&after);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::ColumnRenamed {
+                    table: users(),
+                    from: "mail".to_string(),
+                    to: "email".to_string(),
+                },
+                SchemaChange::ColumnAdded {
+                    table: users(),
+                    column: "age".to_string(),
+                    data_type: SqlType::Integer,
+                },
+            ]
+        );
+        assert!(changes[0].is_destructive());
+        assert!(!changes[1].is_destructive());
+        assert_eq!(
+            changes[0].to_string(),
+            "possibly renamed column public.users.mail to email"
+        );
+    }
+
+    #[test]
+    fn test_destructive_dropped_column() {
+        let before = catalog("CREATE TABLE users (id INTEGER, email TEXT);");
+        let after = catalog("CREATE TABLE users (id INTEGER);");
+        let warnings = before.destructive_changes(&after);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(warnings[0].kind, DiagnosticKind::DestructiveMigration);
+        assert_eq!(
+            warnings[0].message,
+            "Column 'email' of table 'public.users' is dropped"
+        );
+    }
+
+    #[test]
+    fn test_destructive_type_narrowing() {
+        let before = catalog(
+            "CREATE TABLE users (id BIGINT, name VARCHAR(100), score NUMERIC(10, 2), bio VARCHAR(10));",
+        );
+        let after = catalog(
+            "CREATE TABLE users (id INTEGER, name VARCHAR(10), score NUMERIC(12, 2), bio TEXT);",
+        );
+        let messages: Vec<String> = before
+            .destructive_changes(&after)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Column 'id' of table 'public.users' narrows from bigint to integer",
+                "Column 'name' of table 'public.users' narrows from varchar(100) to varchar(10)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_destructive_incompatible_type_change() {
+        let before = catalog("CREATE TABLE users (id TEXT, token TEXT, active BOOLEAN);");
+        let after = catalog("CREATE TABLE users (id INTEGER, token UUID, active INTEGER);");
+        let messages: Vec<String> = before
+            .destructive_changes(&after)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "Column 'id' of table 'public.users' changes from text to integer, which existing values may not convert to",
+                "Column 'token' of table 'public.users' changes from text to uuid, which existing values may not convert to",
+                "Column 'active' of table 'public.users' changes from boolean to integer, which existing values may not convert to",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_destructive_possible_rename() {
+        let before = catalog("CREATE TABLE users (id INTEGER, notes TEXT);");
+        let after = catalog("CREATE TABLE users (id INTEGER, bio TEXT);");
+        let warnings = before.destructive_changes(&after);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(
+            warnings[0].message,
+            "Column 'notes' of table 'public.users' is possibly dropped: it was replaced by 'bio'"
+        );
+    }
+
+    #[test]
+    fn test_destructive_not_null_column_without_default() {
+        let before = catalog("CREATE TABLE users (id INTEGER);");
+        let after = catalog(
+            "CREATE TABLE users (id INTEGER, name TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'new');",
+        );
+        let warnings = before.destructive_changes(&after);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].message.contains("'name'"));
+    }
+
+    #[test]
+    fn test_additive_change_is_not_destructive() {
+        let before = catalog("CREATE TABLE users (id INTEGER, name VARCHAR(50));");
+        let after = catalog(
+            "CREATE TABLE users (id BIGINT, name VARCHAR(100), email TEXT);
+            CREATE TABLE orders (id INTEGER NOT NULL, user_id INTEGER NOT NULL);",
+        );
+        assert!(!before.diff(&after).is_empty());
+        assert!(before.destructive_changes(&after).is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+//! JSON schema dumps, for [`SchemaBuilder::load_json`](super::SchemaBuilder::load_json)
+
+use serde::Deserialize;
+
+use crate::dialect::SqlDialect;
+use crate::error::{Diagnostic, DiagnosticKind, Span};
+use crate::types::SqlType;
+
+use super::catalog::{ColumnDef, DefaultValue, PrimaryKeyDef, QualifiedName, TableDef};
+
+#[derive(Debug, Deserialize)]
+struct JsonDump {
+    schemas: Vec<JsonSchema>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonSchema {
+    name: String,
+    #[serde(default)]
+    tables: Vec<JsonTable>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonTable {
+    name: String,
+    columns: Vec<JsonColumn>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonColumn {
+    name: String,
+    #[serde(rename = "type")]
+    data_type: String,
+    #[serde(default = "default_nullable")]
+    nullable: bool,
+    #[serde(default)]
+    primary_key: bool,
+    #[serde(default)]
+    default: Option<String>,
+}
+
+fn default_nullable() -> bool {
+    true
+}
+
+/// Tables described by a JSON schema dump
+///
+/// Tables in `default_schema` are left unqualified, as they would be when
+/// declared in DDL without a schema.
+pub(crate) fn tables_from_json(
+    json: &str,
+    dialect: SqlDialect,
+    default_schema: &str,
+) -> Result<Vec<TableDef>, Vec<Diagnostic>> {
+    let dump: JsonDump = serde_json::from_str(json).map_err(|e| {
+        vec![Diagnostic::error(
+            DiagnosticKind::ParseError,
+            format!("Invalid JSON schema: {e}"),
+        )
+        .with_span(Span::with_location(e.line(), e.column(), 1))]
+    })?;
+
+    let mut tables = Vec::new();
+    let mut errors = Vec::new();
+    for schema in dump.schemas {
+        for json_table in schema.tables {
+            let name = if schema.name == default_schema {
+                QualifiedName::new(&json_table.name)
+            } else {
+                QualifiedName::with_schema(&schema.name, &json_table.name)
+            };
+            let mut table = TableDef::new(name);
+            let mut primary_key = Vec::new();
+
+            for json_column in json_table.columns {
+                let Some(data_type) = SqlType::parse(&json_column.data_type, dialect) else {
+                    errors.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        format!(
+                            "Unrecognized type '{}' for column '{}.{}'",
+                            json_column.data_type, table.name, json_column.name
+                        ),
+                    ));
+                    continue;
+                };
+                let mut column = ColumnDef::new(&json_column.name, data_type);
+                column.nullable = json_column.nullable;
+                column.default = json_column.default.map(DefaultValue::Expression);
+                if json_column.primary_key {
+                    column = column.primary_key();
+                    primary_key.push(json_column.name.clone());
+                }
+                table.columns.insert(json_column.name, column);
+            }
+
+            if !primary_key.is_empty() {
+                table.primary_key = Some(PrimaryKeyDef {
+                    name: None,
+                    columns: primary_key,
+                });
+            }
+            tables.push(table);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(tables)
+    } else {
+        Err(errors)
+    }
+}
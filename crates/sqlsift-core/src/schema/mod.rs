@@ -1,10 +1,15 @@
 //! Schema management module
 
 mod builder;
+mod cache;
 mod catalog;
+mod diff;
+mod json;
 
 pub use builder::SchemaBuilder;
 pub use catalog::{
-    Catalog, CheckConstraintDef, ColumnDef, DefaultValue, EnumTypeDef, ForeignKeyDef, IdentityKind,
-    PrimaryKeyDef, QualifiedName, Schema, TableDef, UniqueConstraintDef, ViewDef,
+    Catalog, CheckConstraintDef, ColumnDef, ColumnRef, CompositeFieldDef, CompositeTypeDef,
+    DefaultValue, EnumTypeDef, ForeignKeyDef, IdentityKind, IndexDef, PrimaryKeyDef, QualifiedName,
+    Schema, SourceLocation, TableDef, UniqueConstraintDef, ViewDef,
 };
+pub use diff::SchemaChange;
@@ -2,6 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use sqlparser::ast::DataType;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::Token;
+
+use crate::dialect::SqlDialect;
 
 /// Internal representation of SQL types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -64,6 +68,14 @@ pub enum SqlType {
 }
 
 impl SqlType {
+    /// Parse a type name as written in CREATE TABLE, e.g. `varchar(255)` or `integer[]`
+    pub fn parse(type_name: &str, dialect: SqlDialect) -> Option<Self> {
+        let dialect = dialect.parser_dialect();
+        let mut parser = Parser::new(dialect.as_ref()).try_with_sql(type_name).ok()?;
+        let data_type = parser.parse_data_type().ok()?;
+        (parser.peek_token().token == Token::EOF).then(|| Self::from_ast(&data_type))
+    }
+
     /// Convert from sqlparser's DataType to our internal SqlType
     pub fn from_ast(data_type: &DataType) -> Self {
         match data_type {
@@ -214,6 +226,101 @@ impl SqlType {
         }
     }
 
+    /// The common type of a list of values, e.g. the columns of a UNION or
+    /// the branches of a CASE
+    ///
+    /// `Unknown` entries (NULL, or anything not inferred) take on the type of
+    /// the others. Numbers widen along integer → bigint → numeric → floating
+    /// point, character types widen to the longest (or to `text`), and other
+    /// types unify when one implicitly casts to the other. Returns `None` for
+    /// an empty list or when two of the types have no common type.
+    pub fn unify(types: &[SqlType]) -> Option<SqlType> {
+        let (first, rest) = types.split_first()?;
+        rest.iter()
+            .try_fold(first.clone(), |acc, ty| acc.unify_with(ty))
+    }
+
+    fn unify_with(&self, other: &SqlType) -> Option<SqlType> {
+        use SqlType::*;
+        if self == other {
+            return Some(self.clone());
+        }
+        match (self, other) {
+            (Unknown, ty) | (ty, Unknown) => Some(ty.clone()),
+            (Array(a), Array(b)) => Some(Array(Box::new(a.unify_with(b)?))),
+            (Decimal { .. }, Decimal { .. }) => Some(Decimal {
+                precision: None,
+                scale: None,
+            }),
+            (a, b) if a.numeric_rank().is_some() && b.numeric_rank().is_some() => {
+                Some(if a.numeric_rank() > b.numeric_rank() {
+                    a.clone()
+                } else {
+                    b.clone()
+                })
+            }
+            (Text, Char { .. } | Varchar { .. }) | (Char { .. } | Varchar { .. }, Text) => {
+                Some(Text)
+            }
+            (Char { length: a }, Char { length: b }) => Some(Char {
+                length: max_length(*a, *b),
+            }),
+            (
+                Char { length: a } | Varchar { length: a },
+                Char { length: b } | Varchar { length: b },
+            ) => Some(Varchar {
+                length: max_length(*a, *b),
+            }),
+            (
+                Timestamp {
+                    precision: p1,
+                    with_timezone: tz1,
+                },
+                Timestamp {
+                    precision: p2,
+                    with_timezone: tz2,
+                },
+            ) => Some(Timestamp {
+                precision: if p1 == p2 { *p1 } else { None },
+                with_timezone: *tz1 || *tz2,
+            }),
+            (Date, ts @ Timestamp { .. }) | (ts @ Timestamp { .. }, Date) => Some(ts.clone()),
+            (a, b) => {
+                if a.is_compatible_with(b) != TypeCompatibility::ExplicitCast {
+                    Some(b.clone())
+                } else if b.is_compatible_with(a) != TypeCompatibility::ExplicitCast {
+                    Some(a.clone())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Position of a numeric type in the widening order
+    fn numeric_rank(&self) -> Option<u8> {
+        use SqlType::*;
+        match self {
+            TinyInt => Some(0),
+            SmallInt => Some(1),
+            MediumInt => Some(2),
+            Integer => Some(3),
+            BigInt => Some(4),
+            Decimal { .. } => Some(5),
+            Real => Some(6),
+            DoublePrecision => Some(7),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a character type (`char`, `varchar` or `text`)
+    pub fn is_character(&self) -> bool {
+        matches!(
+            self,
+            SqlType::Char { .. } | SqlType::Varchar { .. } | SqlType::Text
+        )
+    }
+
     /// Get a human-readable name for this type
     pub fn display_name(&self) -> String {
         match self {
@@ -262,6 +369,11 @@ impl SqlType {
     }
 }
 
+/// The longer of two character lengths; unbounded if either is
+fn max_length(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    Some(a?.max(b?))
+}
+
 /// Extract character length from CharacterLength if present
 fn extract_char_length(info: Option<&sqlparser::ast::CharacterLength>) -> Option<u64> {
     info.map(|i| match i {
@@ -318,4 +430,72 @@ mod tests {
             TypeCompatibility::ExplicitCast
         );
     }
+
+    #[test]
+    fn test_unify_numeric_widening() {
+        use SqlType::*;
+        assert_eq!(SqlType::unify(&[SmallInt, Integer]), Some(Integer));
+        assert_eq!(SqlType::unify(&[Integer, BigInt, SmallInt]), Some(BigInt));
+        let numeric = Decimal {
+            precision: Some(10),
+            scale: Some(2),
+        };
+        assert_eq!(
+            SqlType::unify(&[BigInt, numeric.clone()]),
+            Some(numeric.clone())
+        );
+        assert_eq!(SqlType::unify(&[numeric, Real]), Some(Real));
+        assert_eq!(
+            SqlType::unify(&[Integer, DoublePrecision]),
+            Some(DoublePrecision)
+        );
+    }
+
+    #[test]
+    fn test_unify_text() {
+        use SqlType::*;
+        assert_eq!(SqlType::unify(&[Text, Text]), Some(Text));
+        assert_eq!(
+            SqlType::unify(&[Varchar { length: Some(10) }, Char { length: Some(20) }]),
+            Some(Varchar { length: Some(20) })
+        );
+        assert_eq!(
+            SqlType::unify(&[Varchar { length: Some(10) }, Varchar { length: None }]),
+            Some(Varchar { length: None })
+        );
+        assert_eq!(
+            SqlType::unify(&[Varchar { length: Some(10) }, Text]),
+            Some(Text)
+        );
+    }
+
+    #[test]
+    fn test_unify_absorbs_null() {
+        use SqlType::*;
+        assert_eq!(SqlType::unify(&[Unknown, Integer, Unknown]), Some(Integer));
+        assert_eq!(SqlType::unify(&[Unknown]), Some(Unknown));
+        assert_eq!(SqlType::unify(&[]), None);
+    }
+
+    #[test]
+    fn test_unify_incompatible() {
+        use SqlType::*;
+        assert_eq!(SqlType::unify(&[Text, Integer]), None);
+        assert_eq!(SqlType::unify(&[Integer, Unknown, Boolean]), None);
+        // Implicit casts still unify
+        assert_eq!(SqlType::unify(&[Text, Uuid]), Some(Uuid));
+        assert_eq!(
+            SqlType::unify(&[
+                Date,
+                Timestamp {
+                    precision: None,
+                    with_timezone: false
+                }
+            ]),
+            Some(Timestamp {
+                precision: None,
+                with_timezone: false
+            })
+        );
+    }
 }
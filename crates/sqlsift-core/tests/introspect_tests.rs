@@ -0,0 +1,143 @@
+// Tests for building a catalog from database introspection rows
+#![cfg(feature = "postgres")]
+
+use std::convert::Infallible;
+
+use sqlsift_core::analyzer::Analyzer;
+use sqlsift_core::error::DiagnosticKind;
+use sqlsift_core::introspect::{
+    build_catalog, ColumnRow, ConstraintKind, ConstraintRow, EnumRow, IntrospectionSource,
+};
+use sqlsift_core::schema::{IdentityKind, QualifiedName};
+use sqlsift_core::types::SqlType;
+
+/// Canned rows standing in for a database
+struct MockSource;
+
+fn column(table: &str, name: &str, data_type: &str, nullable: bool) -> ColumnRow {
+    ColumnRow {
+        schema: "public".to_string(),
+        table: table.to_string(),
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable,
+        default: None,
+        identity: None,
+        is_generated: false,
+    }
+}
+
+impl IntrospectionSource for MockSource {
+    type Error = Infallible;
+
+    fn columns(&mut self) -> Result<Vec<ColumnRow>, Infallible> {
+        Ok(vec![
+            ColumnRow {
+                identity: Some(IdentityKind::Always),
+                ..column("users", "id", "integer", false)
+            },
+            column("users", "email", "character varying(255)", false),
+            column("users", "mood", "mood", true),
+            ColumnRow {
+                default: Some("now()".to_string()),
+                ..column("users", "created_at", "timestamp with time zone", false)
+            },
+            column("orders", "id", "bigint", false),
+            column("orders", "user_id", "integer", false),
+            column("orders", "total", "numeric(10,2)", true),
+        ])
+    }
+
+    fn constraints(&mut self) -> Result<Vec<ConstraintRow>, Infallible> {
+        Ok(vec![
+            ConstraintRow {
+                schema: "public".to_string(),
+                table: "users".to_string(),
+                name: "users_pkey".to_string(),
+                kind: ConstraintKind::PrimaryKey,
+                columns: vec!["id".to_string()],
+                references: None,
+            },
+            ConstraintRow {
+                schema: "public".to_string(),
+                table: "orders".to_string(),
+                name: "orders_user_id_fkey".to_string(),
+                kind: ConstraintKind::ForeignKey,
+                columns: vec!["user_id".to_string()],
+                references: Some((
+                    "public".to_string(),
+                    "users".to_string(),
+                    vec!["id".to_string()],
+                )),
+            },
+        ])
+    }
+
+    fn enums(&mut self) -> Result<Vec<EnumRow>, Infallible> {
+        Ok(vec![
+            EnumRow {
+                schema: "public".to_string(),
+                name: "mood".to_string(),
+                values: vec!["happy".to_string(), "sad".to_string()],
+            },
+            EnumRow {
+                schema: "billing".to_string(),
+                name: "mood".to_string(),
+                values: vec!["paid".to_string(), "overdue".to_string()],
+            },
+        ])
+    }
+}
+
+#[test]
+fn test_build_catalog_from_introspection() {
+    let catalog = build_catalog(&mut MockSource).unwrap();
+
+    let users = catalog.get_table(&QualifiedName::new("users")).unwrap();
+    assert_eq!(
+        users.column_names(),
+        vec!["id", "email", "mood", "created_at"]
+    );
+    assert!(users.get_column("id").unwrap().is_primary_key);
+    assert_eq!(
+        users.primary_key.as_ref().unwrap().columns,
+        vec!["id".to_string()]
+    );
+    assert_eq!(
+        users.get_column("email").unwrap().data_type,
+        SqlType::Varchar { length: Some(255) }
+    );
+    assert_eq!(
+        users.get_column("mood").unwrap().data_type,
+        SqlType::Custom("mood".to_string())
+    );
+    assert!(!users.get_column("created_at").unwrap().requires_value());
+    assert_eq!(
+        catalog.get_enum("mood").unwrap().values,
+        vec!["happy", "sad"]
+    );
+    // Same-named enums of other schemas stay apart
+    assert_eq!(
+        catalog.get_enum("billing.mood").unwrap().values,
+        vec!["paid", "overdue"]
+    );
+
+    let orders = catalog.get_table(&QualifiedName::new("orders")).unwrap();
+    let fk = &orders.foreign_keys[0];
+    assert_eq!(fk.references_table, QualifiedName::new("users"));
+    assert_eq!(fk.references_columns, vec!["id".to_string()]);
+}
+
+#[test]
+fn test_analyze_against_introspected_catalog() {
+    let catalog = build_catalog(&mut MockSource).unwrap();
+    let mut analyzer = Analyzer::new(&catalog);
+
+    let diagnostics =
+        analyzer.analyze("SELECT u.email, o.total FROM users u JOIN orders o ON o.user_id = u.id");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    let diagnostics = analyzer.analyze("SELECT o.amount FROM orders o");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::ColumnNotFound);
+}
@@ -0,0 +1,526 @@
+//! Context-aware completion for SQL documents
+//!
+//! The document is tokenized (not parsed) so that incomplete statements such
+//! as `SELECT u.` still produce useful candidates.
+
+use sqlparser::dialect::GenericDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Location, Token, TokenWithSpan, Tokenizer};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation, InsertTextFormat};
+
+use sqlsift_core::schema::{Catalog, ForeignKeyDef, QualifiedName, TableDef, ViewDef};
+
+/// Completion candidates at a 0-indexed `line` / `character` position
+///
+/// - after `FROM` / `JOIN` (or a `,` in a FROM list): table and view names;
+///   after `JOIN`, also `table alias ON ...` snippets for tables with a
+///   foreign key to or from a table already in scope
+/// - after `alias.`: that table's columns
+/// - inside a SELECT list, a WHERE or JOIN ... ON condition, or an UPDATE's
+///   SET list: columns of the tables in the statement's scope
+///
+/// Anywhere else, and when the text can't be tokenized, every table, view and
+/// column in the catalog is offered.
+pub fn complete_at(
+    text: &str,
+    line: usize,
+    character: usize,
+    catalog: &Catalog,
+) -> Vec<CompletionItem> {
+    let Ok(tokens) = Tokenizer::new(&GenericDialect {}, text).tokenize_with_location() else {
+        return catalog_items(catalog);
+    };
+    let tokens: Vec<TokenWithSpan> = tokens
+        .into_iter()
+        .filter(|t| !matches!(t.token, Token::Whitespace(_)))
+        .collect();
+
+    let cursor = Location::new(line as u64 + 1, character as u64 + 1);
+    let mut before = tokens.iter().take_while(|t| t.span.end <= cursor).count();
+
+    // The word being typed is not part of the context
+    if let Some(t) = before.checked_sub(1).map(|i| &tokens[i]) {
+        if matches!(t.token, Token::Word(_)) && t.span.end == cursor {
+            before -= 1;
+        }
+    }
+
+    // Limit the scope to the statement around the cursor
+    let start = tokens[..before]
+        .iter()
+        .rposition(|t| t.token == Token::SemiColon)
+        .map_or(0, |i| i + 1);
+    let end = tokens[before..]
+        .iter()
+        .position(|t| t.token == Token::SemiColon)
+        .map_or(tokens.len(), |i| before + i);
+    let statement = &tokens[start..end];
+    let context = &tokens[start..before];
+
+    match context {
+        [.., qualifier, TokenWithSpan {
+            token: Token::Period,
+            ..
+        }] => {
+            let Token::Word(word) = &qualifier.token else {
+                return Vec::new();
+            };
+            let scope = from_scope(statement);
+            let table = scope
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&word.value))
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| QualifiedName::new(&word.value));
+            relation_columns(catalog, &table)
+        }
+        [.., last] if is_keyword(last, &[Keyword::JOIN]) => {
+            let mut items = join_items(catalog, &from_scope(context));
+            items.extend(relation_items(catalog));
+            items
+        }
+        [.., last] if is_keyword(last, &[Keyword::FROM]) => relation_items(catalog),
+        _ => match clause_keyword(context) {
+            Some(Keyword::FROM) if context.last().is_some_and(|t| t.token == Token::Comma) => {
+                relation_items(catalog)
+            }
+            Some(Keyword::SELECT | Keyword::WHERE | Keyword::ON | Keyword::SET) => {
+                let scope = from_scope(statement);
+                if scope.is_empty() {
+                    return catalog_items(catalog);
+                }
+                scope
+                    .iter()
+                    .flat_map(|(_, table)| relation_columns(catalog, table))
+                    .collect()
+            }
+            _ => catalog_items(catalog),
+        },
+    }
+}
+
+/// Every table, view and column in the catalog
+pub fn catalog_items(catalog: &Catalog) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+    for schema in catalog.schemas.values() {
+        for table in schema.tables.values() {
+            items.push(table_item(table));
+            items.extend(column_items(table));
+        }
+        items.extend(schema.views.values().map(view_item));
+    }
+    items
+}
+
+/// Table and view names
+fn relation_items(catalog: &Catalog) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+    for schema in catalog.schemas.values() {
+        items.extend(schema.tables.values().map(table_item));
+        items.extend(schema.views.values().map(view_item));
+    }
+    items
+}
+
+/// `table alias ON ...` snippets joining a table to one in `scope` along a
+/// foreign key, in either direction
+fn join_items(catalog: &Catalog, scope: &[(String, QualifiedName)]) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+    for (key, name) in scope {
+        let Some(scoped) = catalog.get_table(name) else {
+            continue;
+        };
+        for schema in catalog.schemas.values() {
+            for table in schema.tables.values() {
+                let alias = join_alias(&table.name.name, scope);
+                // table.fk_columns -> scoped
+                for fk in &table.foreign_keys {
+                    if references(catalog, fk, scoped) {
+                        let pairs = fk_column_pairs(fk, scoped);
+                        items.push(join_item(table, &alias, &pairs, key));
+                    }
+                }
+                // scoped.fk_columns -> table
+                for fk in &scoped.foreign_keys {
+                    if references(catalog, fk, table) {
+                        let pairs: Vec<(&str, &str)> = fk_column_pairs(fk, table)
+                            .into_iter()
+                            .map(|(column, referenced)| (referenced, column))
+                            .collect();
+                        items.push(join_item(table, &alias, &pairs, key));
+                    }
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Whether a foreign key points at `table`
+fn references(catalog: &Catalog, fk: &ForeignKeyDef, table: &TableDef) -> bool {
+    catalog
+        .get_table(&fk.references_table)
+        .is_some_and(|referenced| referenced.name == table.name)
+}
+
+/// (column, referenced column) pairs of a foreign key; `REFERENCES t`
+/// without columns points at the primary key
+fn fk_column_pairs<'a>(fk: &'a ForeignKeyDef, referenced: &'a TableDef) -> Vec<(&'a str, &'a str)> {
+    let referenced_columns = if fk.references_columns.is_empty() {
+        referenced
+            .primary_key
+            .as_ref()
+            .map_or(&[][..], |pk| pk.columns.as_slice())
+    } else {
+        fk.references_columns.as_slice()
+    };
+    fk.columns
+        .iter()
+        .zip(referenced_columns)
+        .map(|(c, r)| (c.as_str(), r.as_str()))
+        .collect()
+}
+
+/// `table alias ON alias.a = key.b [AND ...]`, for `(a, b)` column pairs
+fn join_item(table: &TableDef, alias: &str, pairs: &[(&str, &str)], key: &str) -> CompletionItem {
+    let condition = pairs
+        .iter()
+        .map(|(column, other)| format!("{alias}.{column} = {key}.{other}"))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let text = format!("{} {} ON {}", table.name.name, alias, condition);
+    CompletionItem {
+        label: text.clone(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some("join on foreign key".to_string()),
+        insert_text: Some(format!("{text}$0")),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    }
+}
+
+/// An alias for a joined table that doesn't clash with the scope: its
+/// initial, else its name, else its name with a number
+fn join_alias(table_name: &str, scope: &[(String, QualifiedName)]) -> String {
+    let taken = |alias: &str| scope.iter().any(|(key, _)| key.eq_ignore_ascii_case(alias));
+    let initial: String = table_name
+        .chars()
+        .take(1)
+        .flat_map(char::to_lowercase)
+        .collect();
+    std::iter::once(initial)
+        .chain(std::iter::once(table_name.to_string()))
+        .chain((2..).map(|n| format!("{table_name}{n}")))
+        .find(|alias| !alias.is_empty() && !taken(alias))
+        .unwrap_or_default()
+}
+
+/// Columns of one table or view (empty if it is unknown)
+fn relation_columns(catalog: &Catalog, name: &QualifiedName) -> Vec<CompletionItem> {
+    if let Some(table) = catalog.get_table(name) {
+        return column_items(table);
+    }
+    catalog
+        .get_view(name)
+        .map(|view| {
+            view.columns
+                .iter()
+                .map(|col| CompletionItem {
+                    label: col.clone(),
+                    kind: Some(CompletionItemKind::FIELD),
+                    detail: Some(view.name.name.clone()),
+                    ..Default::default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn table_item(table: &TableDef) -> CompletionItem {
+    let cols: Vec<String> = table
+        .columns
+        .values()
+        .map(|c| format!("{} ({})", c.name, c.data_type.display_name()))
+        .collect();
+    CompletionItem {
+        label: table.name.name.clone(),
+        kind: Some(CompletionItemKind::CLASS),
+        detail: Some("table".to_string()),
+        documentation: if cols.is_empty() {
+            None
+        } else {
+            Some(Documentation::String(cols.join(", ")))
+        },
+        ..Default::default()
+    }
+}
+
+fn column_items(table: &TableDef) -> Vec<CompletionItem> {
+    table
+        .columns
+        .values()
+        .map(|col| {
+            let nullable = if col.nullable { "nullable" } else { "not null" };
+            CompletionItem {
+                label: col.name.clone(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(format!(
+                    "{} ({}) — {}",
+                    col.data_type.display_name(),
+                    nullable,
+                    table.name.name
+                )),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn view_item(view: &ViewDef) -> CompletionItem {
+    let kind = if view.materialized {
+        "materialized view"
+    } else {
+        "view"
+    };
+    CompletionItem {
+        label: view.name.name.clone(),
+        kind: Some(CompletionItemKind::INTERFACE),
+        detail: Some(kind.to_string()),
+        documentation: if view.columns.is_empty() {
+            None
+        } else {
+            Some(Documentation::String(view.columns.join(", ")))
+        },
+        ..Default::default()
+    }
+}
+
+/// Tables referenced in FROM / JOIN / UPDATE, keyed by alias (or table name)
+fn from_scope(statement: &[TokenWithSpan]) -> Vec<(String, QualifiedName)> {
+    let mut scope = Vec::new();
+    let mut in_from = false;
+    let mut i = 0;
+    while i < statement.len() {
+        let token = &statement[i];
+        let starts_relation = is_keyword(token, &[Keyword::FROM, Keyword::JOIN, Keyword::UPDATE])
+            || (in_from && token.token == Token::Comma);
+        if is_keyword(token, &[Keyword::FROM]) {
+            in_from = true;
+        } else if clause_keywords().iter().any(|k| is_keyword(token, &[*k])) {
+            in_from = false;
+        }
+        i += 1;
+        if !starts_relation {
+            continue;
+        }
+
+        // name [. name] [AS] [alias]
+        let mut parts = Vec::new();
+        while let Some(Token::Word(word)) = statement.get(i).map(|t| &t.token) {
+            if word.keyword != Keyword::NoKeyword && word.quote_style.is_none() && parts.is_empty()
+            {
+                break;
+            }
+            parts.push(word.value.clone());
+            i += 1;
+            if statement.get(i).map(|t| &t.token) != Some(&Token::Period) {
+                break;
+            }
+            i += 1;
+        }
+        let table = match parts.as_slice() {
+            [name] => QualifiedName::new(name),
+            [.., schema, name] => QualifiedName::with_schema(schema, name),
+            [] => continue,
+        };
+
+        if statement
+            .get(i)
+            .is_some_and(|t| is_keyword(t, &[Keyword::AS]))
+        {
+            i += 1;
+        }
+        let alias = match statement.get(i).map(|t| &t.token) {
+            Some(Token::Word(word)) if word.keyword == Keyword::NoKeyword => {
+                i += 1;
+                word.value.clone()
+            }
+            _ => table.name.clone(),
+        };
+        scope.push((alias, table));
+    }
+    scope
+}
+
+/// The most recent clause-starting keyword before the cursor
+fn clause_keyword(context: &[TokenWithSpan]) -> Option<Keyword> {
+    context.iter().rev().find_map(|t| match &t.token {
+        Token::Word(word) if clause_keywords().contains(&word.keyword) => Some(word.keyword),
+        _ => None,
+    })
+}
+
+fn clause_keywords() -> &'static [Keyword] {
+    &[
+        Keyword::SELECT,
+        Keyword::FROM,
+        Keyword::JOIN,
+        Keyword::ON,
+        Keyword::WHERE,
+        Keyword::GROUP,
+        Keyword::HAVING,
+        Keyword::ORDER,
+        Keyword::LIMIT,
+        Keyword::SET,
+        Keyword::VALUES,
+        Keyword::INTO,
+        Keyword::UPDATE,
+        Keyword::RETURNING,
+    ]
+}
+
+fn is_keyword(token: &TokenWithSpan, keywords: &[Keyword]) -> bool {
+    matches!(&token.token, Token::Word(word) if keywords.contains(&word.keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlsift_core::schema::SchemaBuilder;
+
+    fn catalog() -> Catalog {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse(
+                "CREATE TABLE users (id INTEGER NOT NULL, name TEXT);\n\
+                 CREATE TABLE orders (id INTEGER NOT NULL, user_id INTEGER, total NUMERIC);\n\
+                 CREATE VIEW active_users AS SELECT id, name FROM users;",
+            )
+            .unwrap();
+        builder.build().0
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tables_after_from() {
+        let catalog = catalog();
+        let items = complete_at("SELECT id FROM ", 0, 15, &catalog);
+        assert_eq!(labels(&items), vec!["users", "orders", "active_users"]);
+
+        // Partially typed name and JOIN
+        let items = complete_at("SELECT * FROM users u JOIN ord", 0, 30, &catalog);
+        assert_eq!(labels(&items), vec!["users", "orders", "active_users"]);
+    }
+
+    #[test]
+    fn test_join_snippets_from_foreign_keys() {
+        let mut builder = SchemaBuilder::new();
+        builder
+            .parse(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);\n\
+                 CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id));\n\
+                 CREATE TABLE tags (id INTEGER PRIMARY KEY);",
+            )
+            .unwrap();
+        let catalog = builder.build().0;
+
+        let items = complete_at("SELECT * FROM users u JOIN ", 0, 27, &catalog);
+        let snippet = &items[0];
+        assert_eq!(snippet.label, "orders o ON o.user_id = u.id");
+        assert_eq!(
+            snippet.insert_text.as_deref(),
+            Some("orders o ON o.user_id = u.id$0")
+        );
+        assert_eq!(snippet.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert_eq!(snippet.kind, Some(CompletionItemKind::SNIPPET));
+        assert_eq!(
+            labels(&items[1..]),
+            vec!["users", "orders", "tags"],
+            "plain table names follow the snippets"
+        );
+
+        // The other direction, with an alias that would clash
+        let items = complete_at("SELECT * FROM orders u JOIN ", 0, 28, &catalog);
+        assert_eq!(items[0].label, "users users ON users.id = u.user_id");
+
+        // Nothing to join on without a table in scope
+        let items = complete_at("SELECT * FROM tags t JOIN ", 0, 26, &catalog);
+        assert_eq!(labels(&items), vec!["users", "orders", "tags"]);
+    }
+
+    #[test]
+    fn test_columns_after_alias_dot() {
+        let catalog = catalog();
+        let text = "SELECT o.\nFROM users u JOIN orders o ON o.user_id = u.id";
+        let items = complete_at(text, 0, 9, &catalog);
+        assert_eq!(labels(&items), vec!["id", "user_id", "total"]);
+        assert!(items
+            .iter()
+            .all(|i| i.kind == Some(CompletionItemKind::FIELD)));
+
+        // A table name works as its own qualifier
+        let items = complete_at("SELECT users.na FROM users", 0, 15, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_columns_in_select_list() {
+        let catalog = catalog();
+        let items = complete_at("SELECT  FROM active_users", 0, 7, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_columns_in_where_and_on() {
+        let catalog = catalog();
+        let items = complete_at("SELECT * FROM orders WHERE ", 0, 27, &catalog);
+        assert_eq!(labels(&items), vec!["id", "user_id", "total"]);
+
+        let items = complete_at(
+            "SELECT * FROM orders WHERE total > 1 AND us",
+            0,
+            43,
+            &catalog,
+        );
+        assert_eq!(labels(&items), vec!["id", "user_id", "total"]);
+
+        let items = complete_at("DELETE FROM users WHERE ", 0, 24, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name"]);
+
+        let items = complete_at("SELECT * FROM users u JOIN orders o ON ", 0, 39, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name", "id", "user_id", "total"]);
+    }
+
+    #[test]
+    fn test_columns_in_update() {
+        let catalog = catalog();
+        let items = complete_at("UPDATE orders SET ", 0, 18, &catalog);
+        assert_eq!(labels(&items), vec!["id", "user_id", "total"]);
+
+        let items = complete_at("UPDATE users SET name = 'x' WHERE ", 0, 34, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name"]);
+
+        // UPDATE ... FROM brings the joined table into scope
+        let text = "UPDATE orders o SET total = 0 FROM users u WHERE ";
+        let items = complete_at(text, 0, 49, &catalog);
+        assert_eq!(labels(&items), vec!["id", "user_id", "total", "id", "name"]);
+    }
+
+    #[test]
+    fn test_scope_is_current_statement() {
+        let catalog = catalog();
+        let text = "SELECT id FROM orders;\nSELECT  FROM users";
+        let items = complete_at(text, 1, 7, &catalog);
+        assert_eq!(labels(&items), vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_fallback_to_catalog() {
+        let catalog = catalog();
+        let items = complete_at("", 0, 0, &catalog);
+        assert_eq!(items.len(), catalog_items(&catalog).len());
+        assert_eq!(items.len(), 8);
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
+use sqlsift_core::RuleLevel;
 
 /// Configuration for sqlsift (loaded from sqlsift.toml)
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -20,23 +22,153 @@ pub struct Config {
     #[serde(default)]
     pub disable: Vec<String>,
 
+    #[serde(default)]
+    pub enable: Vec<String>,
+
+    #[serde(default)]
+    pub severity: HashMap<String, RuleLevel>,
+
+    #[serde(default)]
+    pub type_checking: Option<bool>,
+
+    #[serde(default)]
+    pub plpgsql: Option<bool>,
+
+    #[serde(default)]
+    pub search_path: Vec<String>,
+
     pub schema_dir: Option<String>,
+
+    #[serde(default)]
+    pub cache: Option<String>,
 }
 
 impl Config {
-    /// Find and load sqlsift.toml from the given root directory or its parents
-    pub fn find_from_root(root: &Path) -> Option<Self> {
+    /// Load configuration from an explicit path, skipping discovery
+    ///
+    /// A file with a `[tool.sqlsift]` table (e.g. `pyproject.toml`) is read
+    /// from that table; any other file is read as a standalone config.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if let Some(config) = Self::from_tool_table(&contents) {
+            return Ok(config);
+        }
+        toml::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Find and load configuration from the given root directory or its parents
+    ///
+    /// In each directory `sqlsift.toml` wins; otherwise a `[tool.sqlsift]`
+    /// table in `pyproject.toml` is used.
+    pub fn find_from_root(root: &Path) -> Result<Option<Self>, String> {
         let mut current = root.to_path_buf();
         loop {
             let config_path = current.join("sqlsift.toml");
             if config_path.exists() {
-                let contents = std::fs::read_to_string(&config_path).ok()?;
-                return toml::from_str(&contents).ok();
+                return Self::load_from(&config_path).map(Some);
+            }
+            let shared_path = current.join("pyproject.toml");
+            if let Some(config) = std::fs::read_to_string(&shared_path)
+                .ok()
+                .and_then(|contents| Self::from_tool_table(&contents))
+            {
+                return Ok(Some(config));
             }
             if !current.pop() {
                 break;
             }
         }
-        None
+        Ok(None)
+    }
+
+    /// Parse the `[tool.sqlsift]` table of a shared TOML file, if present
+    pub fn from_tool_table(contents: &str) -> Option<Self> {
+        let mut document: toml::Table = toml::from_str(contents).ok()?;
+        let tool = document.remove("tool")?;
+        tool.get("sqlsift")?.clone().try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_tool_table() {
+        let config = Config::from_tool_table(
+            r#"
+            [project]
+            name = "app"
+
+            [tool.sqlsift]
+            schema = ["db/schema.sql"]
+            dialect = "mysql"
+            enable = ["W1002"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.schema, vec!["db/schema.sql"]);
+        assert_eq!(config.dialect.as_deref(), Some("mysql"));
+        assert_eq!(config.enable, vec!["W1002"]);
+
+        assert!(Config::from_tool_table("[tool.black]\nline-length = 88").is_none());
+    }
+
+    #[test]
+    fn test_severity_table() {
+        let config: Config = toml::from_str(
+            "schema = [\"schema.sql\"]\n\n[severity]\nE0003 = \"warning\"\nambiguous-column = \"off\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.severity["E0003"], RuleLevel::Warning);
+        assert_eq!(config.severity["ambiguous-column"], RuleLevel::Off);
+
+        assert!(toml::from_str::<Config>("[severity]\nE0003 = \"fatal\"\n").is_err());
+    }
+
+    #[test]
+    fn test_load_from_explicit_path() {
+        let dir = std::env::temp_dir().join(format!("sqlsift-lsp-load-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("ci")).unwrap();
+        std::fs::write(dir.join("sqlsift.toml"), "schema = [\"own.sql\"]\n").unwrap();
+        std::fs::write(dir.join("ci/lint.toml"), "schema = [\"ci.sql\"]\n").unwrap();
+        std::fs::write(
+            dir.join("ci/shared.toml"),
+            "[tool.sqlsift]\nschema = [\"shared.sql\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from(&dir.join("ci/lint.toml")).unwrap();
+        assert_eq!(config.schema, vec!["ci.sql"]);
+        let config = Config::load_from(&dir.join("ci/shared.toml")).unwrap();
+        assert_eq!(config.schema, vec!["shared.sql"]);
+        assert!(Config::load_from(&dir.join("missing.toml")).is_err());
+
+        std::fs::write(dir.join("ci/broken.toml"), "schema = [\"ci.sql\"\n").unwrap();
+        let error = Config::load_from(&dir.join("ci/broken.toml")).unwrap_err();
+        assert!(error.starts_with("Failed to parse"), "{error}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_from_root_prefers_sqlsift_toml() {
+        let dir = std::env::temp_dir().join(format!("sqlsift-lsp-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("pyproject.toml"),
+            "[tool.sqlsift]\nschema = [\"shared.sql\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::find_from_root(&dir).unwrap().unwrap();
+        assert_eq!(config.schema, vec!["shared.sql"]);
+
+        std::fs::write(dir.join("sqlsift.toml"), "schema = [\"own.sql\"]\n").unwrap();
+        let config = Config::find_from_root(&dir).unwrap().unwrap();
+        assert_eq!(config.schema, vec!["own.sql"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
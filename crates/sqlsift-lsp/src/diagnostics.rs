@@ -1,9 +1,21 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use tower_lsp::lsp_types::{self, NumberOrString, Position, Range};
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    self, CodeAction, CodeActionKind, CodeActionOrCommand, DiagnosticRelatedInformation,
+    NumberOrString, Position, Range, TextEdit, Url, WorkspaceEdit,
+};
 
+use sqlsift_core::schema::SourceLocation;
 use sqlsift_core::{Diagnostic, Severity, Span};
 
+/// A diagnostic's suggestion, carried in `lsp_types::Diagnostic::data`
+#[derive(Debug, Serialize, Deserialize)]
+struct QuickFix {
+    range: Range,
+    replacement: String,
+}
+
 /// Convert sqlsift diagnostics to LSP diagnostics, filtering disabled rules
 pub fn to_lsp_diagnostics(
     diagnostics: &[Diagnostic],
@@ -23,10 +35,68 @@ fn to_lsp_diagnostic(diag: &Diagnostic) -> lsp_types::Diagnostic {
         code: Some(NumberOrString::String(diag.code().to_string())),
         source: Some("sqlsift".to_string()),
         message: format_message(diag),
+        data: diag.suggestion.as_ref().and_then(|suggestion| {
+            serde_json::to_value(QuickFix {
+                range: span_to_range(Some(&suggestion.span)),
+                replacement: suggestion.replacement.clone(),
+            })
+            .ok()
+        }),
+        related_information: related_information(diag),
         ..Default::default()
     }
 }
 
+/// Schema locations attached to a diagnostic; only those in a file can be linked
+fn related_information(diag: &Diagnostic) -> Option<Vec<DiagnosticRelatedInformation>> {
+    let related: Vec<_> = diag
+        .related
+        .iter()
+        .filter_map(|related| {
+            Some(DiagnosticRelatedInformation {
+                location: to_lsp_location(&related.location)?,
+                message: related.message.clone(),
+            })
+        })
+        .collect();
+    (!related.is_empty()).then_some(related)
+}
+
+/// Convert a schema source location to an LSP location (needs a file)
+pub fn to_lsp_location(location: &SourceLocation) -> Option<lsp_types::Location> {
+    let uri = Url::from_file_path(location.file.as_ref()?).ok()?;
+    let span = &location.span;
+    let start = Position::new(
+        span.line.saturating_sub(1) as u32,
+        span.column.saturating_sub(1) as u32,
+    );
+    let end = Position::new(start.line, start.character + span.length as u32);
+    Some(lsp_types::Location::new(uri, Range::new(start, end)))
+}
+
+/// Quick-fix code actions for the diagnostics that carry a suggestion
+pub fn quick_fixes(uri: &Url, diagnostics: &[lsp_types::Diagnostic]) -> Vec<CodeActionOrCommand> {
+    diagnostics
+        .iter()
+        .filter(|d| d.source.as_deref() == Some("sqlsift"))
+        .filter_map(|diag| {
+            let fix: QuickFix = serde_json::from_value(diag.data.clone()?).ok()?;
+            let edit = TextEdit::new(fix.range, fix.replacement.clone());
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with '{}'", fix.replacement),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                is_preferred: Some(true),
+                ..Default::default()
+            }))
+        })
+        .collect()
+}
+
 /// Convert Span (1-indexed) to LSP Range (0-indexed)
 fn span_to_range(span: Option<&Span>) -> Range {
     match span {
@@ -134,4 +204,79 @@ mod tests {
             Some(NumberOrString::String("E0003".to_string()))
         );
     }
+
+    #[test]
+    fn test_quick_fix_replaces_suggestion_span() {
+        let diag = Diagnostic::error(DiagnosticKind::ColumnNotFound, "Column 'naem' not found")
+            .with_span(Span::with_location(1, 8, 4))
+            .with_help("Did you mean 'name'?")
+            .with_suggestion(Span::with_location(1, 8, 4), "name");
+        let lsp_diags = to_lsp_diagnostics(&[diag], &HashSet::new());
+        let uri = Url::parse("file:///queries/users.sql").unwrap();
+
+        let actions = quick_fixes(&uri, &lsp_diags);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.title, "Replace with 'name'");
+        assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let edits = &changes[&uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "name");
+        assert_eq!(
+            edits[0].range,
+            Range::new(Position::new(0, 7), Position::new(0, 11))
+        );
+    }
+
+    #[test]
+    fn test_no_quick_fix_without_suggestion() {
+        let diag = Diagnostic::error(DiagnosticKind::ColumnNotFound, "Column 'x' not found")
+            .with_span(Span::with_location(1, 8, 1));
+        let lsp_diags = to_lsp_diagnostics(&[diag], &HashSet::new());
+        assert!(lsp_diags[0].data.is_none());
+
+        let uri = Url::parse("file:///queries/users.sql").unwrap();
+        assert!(quick_fixes(&uri, &lsp_diags).is_empty());
+    }
+
+    #[test]
+    fn test_related_information_for_ambiguous_column() {
+        let location = |file: Option<&str>, line| SourceLocation {
+            file: file.map(std::path::PathBuf::from),
+            span: Span::with_location(line, 5, 2),
+        };
+        let diag = Diagnostic::error(DiagnosticKind::AmbiguousColumn, "Column 'id' is ambiguous")
+            .with_span(Span::with_location(1, 8, 2))
+            .with_related(
+                "'id' found in users",
+                location(Some("/schema/users.sql"), 2),
+            )
+            .with_related(
+                "'id' found in orders",
+                location(Some("/schema/orders.sql"), 3),
+            )
+            .with_related("'id' found in tmp", location(None, 1));
+        let lsp_diags = to_lsp_diagnostics(&[diag], &HashSet::new());
+
+        let related = lsp_diags[0].related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 2, "entries without a file are dropped");
+        assert_eq!(related[0].message, "'id' found in users");
+        assert_eq!(
+            related[0].location.uri,
+            Url::parse("file:///schema/users.sql").unwrap()
+        );
+        assert_eq!(
+            related[0].location.range,
+            Range::new(Position::new(1, 4), Position::new(1, 6))
+        );
+        assert_eq!(related[1].message, "'id' found in orders");
+
+        let plain = Diagnostic::error(DiagnosticKind::AmbiguousColumn, "Column 'id' is ambiguous");
+        let lsp_diags = to_lsp_diagnostics(&[plain], &HashSet::new());
+        assert!(lsp_diags[0].related_information.is_none());
+    }
 }
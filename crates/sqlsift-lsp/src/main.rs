@@ -1,7 +1,9 @@
+mod completion;
 mod config;
 mod diagnostics;
 mod server;
 mod state;
+mod symbols;
 
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::EnvFilter;
@@ -1,12 +1,18 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use tokio::sync::RwLock;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
-use crate::diagnostics::to_lsp_diagnostics;
+use crate::completion::complete_at;
+use crate::diagnostics::{quick_fixes, to_lsp_diagnostics};
 use crate::state::ServerState;
+use crate::symbols::document_symbols;
+
+/// How long typing has to pause before a changed document is analyzed
+const ANALYSIS_DELAY: Duration = Duration::from_millis(150);
 
 pub struct Backend {
     client: Client,
@@ -21,9 +27,12 @@ impl Backend {
         }
     }
 
-    /// Analyze a document and publish diagnostics
+    /// Analyze a document and publish diagnostics, unless the client pulls them
     async fn publish_diagnostics_for(&self, uri: Url, text: &str) {
         let state = self.state.read().await;
+        if state.pull_diagnostics {
+            return;
+        }
         let diagnostics = state.analyze_document(text);
         let lsp_diagnostics = to_lsp_diagnostics(&diagnostics, &state.disabled_rules);
         self.client
@@ -31,10 +40,41 @@ impl Backend {
             .await;
     }
 
+    /// Analyze `uri` once edits pause, unless an edit after `generation` arrives first
+    ///
+    /// Returns `None` when superseded. Analysis runs under the same read lock
+    /// as the generation check, so a newer edit can't slip in between; the
+    /// lock is handed back with the diagnostics so they can be published
+    /// before a `did_close` gets to clear them.
+    async fn debounced_diagnostics(
+        state: Arc<RwLock<ServerState>>,
+        uri: &Url,
+        generation: u64,
+    ) -> Option<(OwnedRwLockReadGuard<ServerState>, Vec<Diagnostic>)> {
+        tokio::time::sleep(ANALYSIS_DELAY).await;
+        let state = state.read_owned().await;
+        if !state.is_current(uri, generation) {
+            return None;
+        }
+        let text = state.open_documents.get(uri)?;
+        let diagnostics = to_lsp_diagnostics(&state.analyze_document(text), &state.disabled_rules);
+        Some((state, diagnostics))
+    }
+
     /// Re-analyze all open documents and publish diagnostics
+    ///
+    /// A client that pulls diagnostics is asked to pull them again instead.
     async fn reanalyze_all_open_documents(&self) {
         let uris_and_texts: Vec<(Url, String)> = {
             let state = self.state.read().await;
+            if state.pull_diagnostics {
+                let refresh = state.diagnostic_refresh;
+                drop(state);
+                if refresh {
+                    let _ = self.client.workspace_diagnostic_refresh().await;
+                }
+                return;
+            }
             state
                 .open_documents
                 .iter()
@@ -51,11 +91,25 @@ impl Backend {
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        {
+            let mut state = self.state.write().await;
+            state.pull_diagnostics = pulls_diagnostics(&params.capabilities);
+            state.diagnostic_refresh = params
+                .capabilities
+                .workspace
+                .as_ref()
+                .and_then(|w| w.diagnostic.as_ref())
+                .and_then(|d| d.refresh_support)
+                .unwrap_or(false);
+        }
+
         // Store workspace root for config loading
         if let Some(root_uri) = params.root_uri {
             if let Ok(path) = root_uri.to_file_path() {
-                let mut state = self.state.write().await;
-                state.load_config(&path);
+                let result = self.state.write().await.load_config(&path);
+                if let Err(error) = result {
+                    self.client.log_message(MessageType::WARNING, error).await;
+                }
             }
         }
 
@@ -72,6 +126,18 @@ impl LanguageServer for Backend {
                     },
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some("sqlsift".to_string()),
+                        // Results depend on the schema files as well
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: false,
+                        ..Default::default()
+                    },
+                )),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec![".".to_string(), " ".to_string()]),
                     ..Default::default()
@@ -125,14 +191,30 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri.clone();
         // FULL sync: first content change contains the entire document
         if let Some(change) = params.content_changes.into_iter().next() {
-            let text = change.text;
-
-            {
+            let generation = {
                 let mut state = self.state.write().await;
-                state.open_documents.insert(uri.clone(), text.clone());
-            }
+                state.open_documents.insert(uri.clone(), change.text);
+                if state.pull_diagnostics {
+                    return;
+                }
+                state.next_generation(&uri)
+            };
 
-            self.publish_diagnostics_for(uri, &text).await;
+            // Analyze in the background so the next keystroke isn't held up
+            let version = params.text_document.version;
+            let client = self.client.clone();
+            let state = Arc::clone(&self.state);
+            tokio::spawn(async move {
+                // `_state` holds the lock until published, so a did_close
+                // can't clear the diagnostics first and have them come back
+                if let Some((_state, diagnostics)) =
+                    Self::debounced_diagnostics(state, &uri, generation).await
+                {
+                    client
+                        .publish_diagnostics(uri, diagnostics, Some(version))
+                        .await;
+                }
+            });
         }
     }
 
@@ -140,18 +222,16 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri.clone();
 
         // Check if saved file is a schema file
-        let is_schema = if let Ok(path) = uri.to_file_path() {
-            let state = self.state.read().await;
-            state.is_schema_file(&path)
-        } else {
-            false
+        let schema_path = match uri.to_file_path() {
+            Ok(path) if self.state.read().await.is_schema_file(&path) => Some(path),
+            _ => None,
         };
 
-        if is_schema {
-            // Rebuild catalog and re-analyze all open documents
+        if let Some(path) = schema_path {
+            // Update the catalog and re-analyze all open documents
             let errors = {
                 let mut state = self.state.write().await;
-                state.rebuild_catalog()
+                state.update_schema_file(&path)
             };
 
             for error in errors {
@@ -172,13 +252,17 @@ impl LanguageServer for Backend {
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri.clone();
 
-        {
+        let pull = {
             let mut state = self.state.write().await;
             state.open_documents.remove(&uri);
-        }
+            state.edit_generations.remove(&uri);
+            state.pull_diagnostics
+        };
 
         // Clear diagnostics for closed document
-        self.client.publish_diagnostics(uri, vec![], None).await;
+        if !pull {
+            self.client.publish_diagnostics(uri, vec![], None).await;
+        }
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -209,9 +293,87 @@ impl LanguageServer for Backend {
         }
     }
 
-    async fn completion(&self, _params: CompletionParams) -> Result<Option<CompletionResponse>> {
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let state = self.state.read().await;
+        let text = match state.open_documents.get(uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let (line, character) = (position.line as usize, position.character as usize);
+        let word = match word_at_position(text, line, character) {
+            Some(w) => w,
+            None => return Ok(None),
+        };
+
+        Ok(state
+            .definition_location(text, line, character, &word)
+            .map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let actions = quick_fixes(&params.text_document.uri, &params.context.diagnostics);
+        if actions.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(actions))
+        }
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let state = self.state.read().await;
+        let text = match state.open_documents.get(&params.text_document.uri) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        Ok(Some(DocumentSymbolResponse::Nested(document_symbols(
+            text,
+            state.dialect,
+        ))))
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let state = self.state.read().await;
+        let report = match state.open_documents.get(&params.text_document.uri) {
+            Some(text) => state.diagnostic_report(text),
+            None => FullDocumentDiagnosticReport::default(),
+        };
+
+        Ok(DocumentDiagnosticReportResult::Report(
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: report,
+            }),
+        ))
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
         let state = self.state.read().await;
-        let items = state.completion_items();
+        let items = match state.open_documents.get(uri) {
+            Some(text) => complete_at(
+                text,
+                position.line as usize,
+                position.character as usize,
+                &state.catalog,
+            ),
+            None => state.completion_items(),
+        };
 
         if items.is_empty() {
             Ok(None)
@@ -221,6 +383,14 @@ impl LanguageServer for Backend {
     }
 }
 
+/// Whether the client pulls diagnostics with `textDocument/diagnostic`
+fn pulls_diagnostics(capabilities: &ClientCapabilities) -> bool {
+    capabilities
+        .text_document
+        .as_ref()
+        .is_some_and(|t| t.diagnostic.is_some())
+}
+
 /// Extract the SQL identifier at the given line/character position
 fn word_at_position(text: &str, line: usize, character: usize) -> Option<String> {
     let target_line = text.lines().nth(line)?;
@@ -251,6 +421,67 @@ fn is_ident_char(b: u8) -> bool {
 mod tests {
     use super::*;
 
+    /// Record an edit like `did_change` does and start its analysis
+    async fn edit(
+        state: &Arc<RwLock<ServerState>>,
+        uri: &Url,
+        text: &str,
+    ) -> tokio::task::JoinHandle<Option<Vec<Diagnostic>>> {
+        let generation = {
+            let mut state = state.write().await;
+            state.open_documents.insert(uri.clone(), text.to_string());
+            state.next_generation(uri)
+        };
+        let (state, uri) = (Arc::clone(state), uri.clone());
+        tokio::spawn(async move {
+            Backend::debounced_diagnostics(state, &uri, generation)
+                .await
+                .map(|(_, diagnostics)| diagnostics)
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rapid_changes_analyze_latest_only() {
+        let uri = Url::parse("file:///tmp/query.sql").unwrap();
+        let state = Arc::new(RwLock::new(ServerState::new()));
+
+        let first = edit(&state, &uri, "SELECT 1").await;
+        tokio::time::sleep(ANALYSIS_DELAY / 3).await;
+        let second = edit(&state, &uri, "SELECT id FROM users").await;
+
+        assert!(first.await.unwrap().is_none());
+        // The published diagnostics are for the second text
+        let diagnostics = second.await.unwrap().unwrap();
+        assert!(diagnostics.iter().any(|d| d.message.contains("users")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_closed_document_not_analyzed() {
+        let uri = Url::parse("file:///tmp/query.sql").unwrap();
+        let state = Arc::new(RwLock::new(ServerState::new()));
+
+        let pending = edit(&state, &uri, "SELECT id FROM users").await;
+        {
+            let mut state = state.write().await;
+            state.open_documents.remove(&uri);
+            state.edit_generations.remove(&uri);
+        }
+        assert!(pending.await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pulls_diagnostics() {
+        assert!(!pulls_diagnostics(&ClientCapabilities::default()));
+        let capabilities = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                diagnostic: Some(DiagnosticClientCapabilities::default()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(pulls_diagnostics(&capabilities));
+    }
+
     #[test]
     fn test_word_at_position_middle() {
         let text = "SELECT name FROM users";
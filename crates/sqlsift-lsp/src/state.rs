@@ -4,17 +4,37 @@ use std::path::{Path, PathBuf};
 use tower_lsp::lsp_types::{self, Url};
 
 use sqlsift_core::schema::{Catalog, QualifiedName, SchemaBuilder};
-use sqlsift_core::{Analyzer, Diagnostic, SqlDialect};
+use sqlsift_core::{
+    apply_rule_levels, Analyzer, Diagnostic, DiagnosticKind, RuleLevel, SqlDialect,
+};
 
+use crate::completion;
 use crate::config::Config;
+use crate::diagnostics::{to_lsp_diagnostics, to_lsp_location};
 
 pub struct ServerState {
     pub catalog: Catalog,
     pub dialect: SqlDialect,
     pub disabled_rules: HashSet<String>,
+    pub enabled_rules: HashSet<DiagnosticKind>,
+    /// Severity overrides from the `[severity]` config table
+    pub rule_levels: HashMap<DiagnosticKind, RuleLevel>,
+    pub type_checking: bool,
+    pub plpgsql: bool,
+    pub search_path: Vec<String>,
     pub open_documents: HashMap<Url, String>,
+    /// Edits seen per open document; a pending analysis started for an
+    /// older count is stale
+    pub edit_generations: HashMap<Url, u64>,
     pub schema_files: Vec<PathBuf>,
     pub workspace_root: Option<PathBuf>,
+    /// File caching the built catalog across server restarts
+    pub cache_file: Option<PathBuf>,
+    /// The client pulls diagnostics with `textDocument/diagnostic`, so none
+    /// are pushed
+    pub pull_diagnostics: bool,
+    /// The client accepts `workspace/diagnostic/refresh` requests
+    pub diagnostic_refresh: bool,
 }
 
 impl ServerState {
@@ -23,17 +43,29 @@ impl ServerState {
             catalog: Catalog::default(),
             dialect: SqlDialect::default(),
             disabled_rules: HashSet::new(),
+            enabled_rules: HashSet::new(),
+            rule_levels: HashMap::new(),
+            type_checking: true,
+            plpgsql: false,
+            search_path: Vec::new(),
             open_documents: HashMap::new(),
+            edit_generations: HashMap::new(),
             schema_files: Vec::new(),
             workspace_root: None,
+            cache_file: None,
+            pull_diagnostics: false,
+            diagnostic_refresh: false,
         }
     }
 
     /// Load configuration from sqlsift.toml and set up state
-    pub fn load_config(&mut self, workspace_root: &Path) {
+    ///
+    /// A config file that can't be read or parsed is left out, and the
+    /// error returned.
+    pub fn load_config(&mut self, workspace_root: &Path) -> Result<(), String> {
         self.workspace_root = Some(workspace_root.to_path_buf());
 
-        if let Some(config) = Config::find_from_root(workspace_root) {
+        if let Some(config) = Config::find_from_root(workspace_root)? {
             // Resolve dialect
             if let Some(dialect_str) = &config.dialect {
                 if let Ok(d) = dialect_str.parse() {
@@ -44,45 +76,159 @@ impl ServerState {
             // Set disabled rules
             self.disabled_rules = config.disable.iter().cloned().collect();
 
+            // Set enabled opt-in rules (unknown codes are ignored)
+            self.enabled_rules = config
+                .enable
+                .iter()
+                .filter_map(|code| DiagnosticKind::from_code(code))
+                .collect();
+            self.rule_levels = config
+                .severity
+                .iter()
+                .filter_map(|(code, level)| Some((DiagnosticKind::from_code(code)?, *level)))
+                .collect();
+            self.type_checking = config.type_checking.unwrap_or(true);
+            self.plpgsql = config.plpgsql.unwrap_or(false);
+            self.search_path = config.search_path.clone();
+
             // Resolve schema files
             self.schema_files = resolve_schema_files(&config, workspace_root);
+            self.cache_file = config.cache.as_ref().map(|c| workspace_root.join(c));
         }
+        Ok(())
     }
 
     /// Rebuild the catalog from schema files
+    ///
+    /// With a cache file configured, a catalog cached from the same schema
+    /// files is reused instead.
     pub fn rebuild_catalog(&mut self) -> Vec<String> {
-        let mut builder = SchemaBuilder::with_dialect(self.dialect);
         let mut errors = Vec::new();
-
+        let mut sources = Vec::new();
         for schema_file in &self.schema_files {
             match std::fs::read_to_string(schema_file) {
-                Ok(content) => {
-                    if let Err(diags) = builder.parse(&content) {
-                        for d in diags {
-                            errors.push(format!("{}: {}", schema_file.display(), d.message));
-                        }
-                    }
-                }
+                Ok(content) => sources.push((schema_file.as_path(), content)),
                 Err(e) => {
                     errors.push(format!("Failed to read {}: {}", schema_file.display(), e));
                 }
             }
         }
+        let cache_key = Catalog::cache_key(
+            self.dialect,
+            &sources
+                .iter()
+                .map(|(path, content)| (*path, content.as_str()))
+                .collect::<Vec<_>>(),
+        );
+
+        let cached = self
+            .cache_file
+            .as_deref()
+            .and_then(|path| Catalog::load_cached(path, cache_key));
+        let (mut catalog, schema_diags) = if let Some(cached) = cached {
+            cached
+        } else {
+            let mut builder = SchemaBuilder::with_dialect(self.dialect);
+            let mut parsed = true;
+            for (schema_file, content) in &sources {
+                let result = if schema_file.extension().is_some_and(|ext| ext == "json") {
+                    builder.load_json(content)
+                } else {
+                    builder.parse_file(schema_file, content)
+                };
+                if let Err(diags) = result {
+                    parsed = false;
+                    for d in diags {
+                        errors.push(format!("{}: {}", schema_file.display(), d.message));
+                    }
+                }
+            }
 
-        let (catalog, schema_diags) = builder.build();
+            let (catalog, schema_diags) = builder.build();
+            // A file that failed to parse would be skipped on every cached start
+            if let Some(path) = self.cache_file.as_ref().filter(|_| parsed) {
+                if let Err(e) = catalog.save_cache(path, cache_key, &schema_diags) {
+                    errors.push(format!("Failed to write {}: {}", path.display(), e));
+                }
+            }
+            (catalog, schema_diags)
+        };
+        for d in schema_diags {
+            errors.push(format!("Schema warning: {}", d.message));
+        }
+
+        catalog.set_search_path(self.search_path.clone());
         self.catalog = catalog;
+        errors
+    }
 
+    /// Pick up an edit to one schema file
+    ///
+    /// Only `path` is parsed again; the other files' tables stay as they are.
+    /// Files that alter, index or `SELECT *` from another file's tables (or
+    /// are altered by one) fall back to [`rebuild_catalog`](Self::rebuild_catalog),
+    /// as do JSON schema files. The cache file isn't updated; it is rewritten
+    /// on the next full rebuild.
+    pub fn update_schema_file(&mut self, path: &Path) -> Vec<String> {
+        if path.extension().is_some_and(|ext| ext == "json") || self.catalog.is_interdependent(path)
+        {
+            return self.rebuild_catalog();
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return self.rebuild_catalog();
+        };
+
+        let mut errors = Vec::new();
+        let mut builder =
+            SchemaBuilder::from_catalog(std::mem::take(&mut self.catalog), self.dialect);
+        if let Err(diags) = builder.replace_file(path, &content) {
+            for d in diags {
+                errors.push(format!("{}: {}", path.display(), d.message));
+            }
+        }
+        let (mut catalog, schema_diags) = builder.build();
+        // The new contents may have introduced a dependency on another file
+        if catalog.is_interdependent(path) {
+            return self.rebuild_catalog();
+        }
         for d in schema_diags {
             errors.push(format!("Schema warning: {}", d.message));
         }
 
+        catalog.set_search_path(self.search_path.clone());
+        self.catalog = catalog;
         errors
     }
 
     /// Analyze a SQL document and return diagnostics
     pub fn analyze_document(&self, text: &str) -> Vec<Diagnostic> {
         let mut analyzer = Analyzer::with_dialect(&self.catalog, self.dialect);
-        analyzer.analyze(text)
+        for kind in &self.enabled_rules {
+            analyzer.enable_rule(*kind);
+        }
+        analyzer.set_type_checking(self.type_checking);
+        analyzer.set_plpgsql_analysis(self.plpgsql);
+        apply_rule_levels(analyzer.analyze(text), &self.rule_levels)
+    }
+
+    /// Analyze a SQL document for a `textDocument/diagnostic` pull request
+    pub fn diagnostic_report(&self, text: &str) -> lsp_types::FullDocumentDiagnosticReport {
+        lsp_types::FullDocumentDiagnosticReport {
+            result_id: None,
+            items: to_lsp_diagnostics(&self.analyze_document(text), &self.disabled_rules),
+        }
+    }
+
+    /// Record an edit to `uri`, returning its generation
+    pub fn next_generation(&mut self, uri: &Url) -> u64 {
+        let generation = self.edit_generations.entry(uri.clone()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    /// Whether `generation` is still the latest edit of an open document
+    pub fn is_current(&self, uri: &Url, generation: u64) -> bool {
+        self.edit_generations.get(uri) == Some(&generation)
     }
 
     /// Check if a file path is one of the schema files
@@ -90,6 +236,37 @@ impl ServerState {
         self.schema_files.iter().any(|p| p == path)
     }
 
+    /// Find where the table or column under the cursor is declared
+    ///
+    /// `line` / `character` are 0-indexed and `word` is the identifier at that
+    /// position. Column references are resolved through the query's FROM
+    /// scope; anything else is looked up as a table name.
+    pub fn definition_location(
+        &self,
+        text: &str,
+        line: usize,
+        character: usize,
+        word: &str,
+    ) -> Option<lsp_types::Location> {
+        let analyzer = Analyzer::with_dialect(&self.catalog, self.dialect);
+        let column_location = analyzer
+            .resolve_column(text, line + 1, character + 1)
+            .and_then(|origin| {
+                self.catalog
+                    .get_table(&origin.table_name())?
+                    .get_column(&origin.column)?
+                    .location
+                    .as_ref()
+            });
+        let location = column_location.or_else(|| {
+            self.catalog
+                .get_table(&QualifiedName::new(word))?
+                .location
+                .as_ref()
+        })?;
+        to_lsp_location(location)
+    }
+
     /// Get hover information for a word (table, view, or column name)
     pub fn hover_info(&self, word: &str) -> Option<String> {
         let name = QualifiedName::new(word);
@@ -97,17 +274,39 @@ impl ServerState {
         // Check tables
         if let Some(table) = self.catalog.get_table(&name) {
             let mut md = format!("**{}** (table)\n\n", table.name.name);
-            md.push_str("| Column | Type | Nullable |\n");
-            md.push_str("|--------|------|----------|\n");
+            md.push_str("| Column | Type | Nullable | Default |\n");
+            md.push_str("|--------|------|----------|---------|\n");
             for col in table.columns.values() {
                 let nullable = if col.nullable { "NULL" } else { "NOT NULL" };
+                let default = col
+                    .default_sql()
+                    .map(|sql| format!("`{}`", sql))
+                    .unwrap_or_default();
                 md.push_str(&format!(
-                    "| {} | {} | {} |\n",
+                    "| {} | {} | {} | {} |\n",
                     col.name,
                     col.data_type.display_name(),
-                    nullable
+                    nullable,
+                    default
                 ));
             }
+            if !table.indexes.is_empty() {
+                md.push_str("\nIndexes:\n");
+                for index in &table.indexes {
+                    let name = index.name.as_deref().unwrap_or("(unnamed)");
+                    let unique = if index.unique { " (UNIQUE)" } else { "" };
+                    md.push_str(&format!(
+                        "- {}{} on ({})",
+                        name,
+                        unique,
+                        index.columns.join(", ")
+                    ));
+                    if let Some(predicate) = &index.predicate {
+                        md.push_str(&format!(" WHERE {}", predicate));
+                    }
+                    md.push('\n');
+                }
+            }
             return Some(md);
         }
 
@@ -131,13 +330,17 @@ impl ServerState {
             for table in schema.tables.values() {
                 if let Some(col) = table.get_column(word) {
                     let nullable = if col.nullable { "nullable" } else { "not null" };
-                    matches.push(format!(
+                    let mut md = format!(
                         "**{}** — {} ({})\n\nTable: {}",
                         col.name,
                         col.data_type.display_name(),
                         nullable,
                         table.name.name
-                    ));
+                    );
+                    if let Some(default) = col.default_sql() {
+                        md.push_str(&format!("\n\nDefault: `{}`", default));
+                    }
+                    matches.push(md);
                 }
             }
         }
@@ -151,67 +354,7 @@ impl ServerState {
 
     /// Get completion items from the schema catalog
     pub fn completion_items(&self) -> Vec<lsp_types::CompletionItem> {
-        let mut items = Vec::new();
-
-        for schema in self.catalog.schemas.values() {
-            // Tables
-            for table in schema.tables.values() {
-                let cols: Vec<String> = table
-                    .columns
-                    .values()
-                    .map(|c| format!("{} ({})", c.name, c.data_type.display_name()))
-                    .collect();
-                items.push(lsp_types::CompletionItem {
-                    label: table.name.name.clone(),
-                    kind: Some(lsp_types::CompletionItemKind::CLASS),
-                    detail: Some("table".to_string()),
-                    documentation: if cols.is_empty() {
-                        None
-                    } else {
-                        Some(lsp_types::Documentation::String(cols.join(", ")))
-                    },
-                    ..Default::default()
-                });
-
-                // Columns from this table
-                for col in table.columns.values() {
-                    let nullable = if col.nullable { "nullable" } else { "not null" };
-                    items.push(lsp_types::CompletionItem {
-                        label: col.name.clone(),
-                        kind: Some(lsp_types::CompletionItemKind::FIELD),
-                        detail: Some(format!(
-                            "{} ({}) — {}",
-                            col.data_type.display_name(),
-                            nullable,
-                            table.name.name
-                        )),
-                        ..Default::default()
-                    });
-                }
-            }
-
-            // Views
-            for view in schema.views.values() {
-                let kind = if view.materialized {
-                    "materialized view"
-                } else {
-                    "view"
-                };
-                items.push(lsp_types::CompletionItem {
-                    label: view.name.name.clone(),
-                    kind: Some(lsp_types::CompletionItemKind::INTERFACE),
-                    detail: Some(kind.to_string()),
-                    documentation: if view.columns.is_empty() {
-                        None
-                    } else {
-                        Some(lsp_types::Documentation::String(view.columns.join(", ")))
-                    },
-                    ..Default::default()
-                });
-            }
-        }
-
-        items
+        completion::catalog_items(&self.catalog)
     }
 }
 
@@ -295,6 +438,60 @@ mod tests {
         assert_eq!(diagnostics[0].code(), "E0002");
     }
 
+    #[test]
+    fn test_analyze_document_enabled_rules() {
+        let mut state = state_with_schema("CREATE TABLE users (id INTEGER, name TEXT);");
+        assert!(state.analyze_document("SELECT * FROM users").is_empty());
+
+        state.enabled_rules.insert(DiagnosticKind::SelectStar);
+        let diagnostics = state.analyze_document("SELECT * FROM users");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "W1002");
+    }
+
+    #[test]
+    fn test_diagnostic_report_bad_column() {
+        let mut state = state_with_schema("CREATE TABLE users (id INTEGER, name TEXT);");
+        let report = state.diagnostic_report("SELECT id,\n       nmae FROM users");
+        assert_eq!(report.items.len(), 1);
+        let item = &report.items[0];
+        assert_eq!(
+            item.code,
+            Some(lsp_types::NumberOrString::String("E0002".to_string()))
+        );
+        assert_eq!(item.severity, Some(lsp_types::DiagnosticSeverity::ERROR));
+        assert_eq!(item.range.start, lsp_types::Position::new(1, 7));
+
+        state.disabled_rules.insert("E0002".to_string());
+        assert!(state
+            .diagnostic_report("SELECT nmae FROM users")
+            .items
+            .is_empty());
+    }
+
+    #[test]
+    fn test_analyze_document_severity_overrides() {
+        let mut state = state_with_schema(
+            "CREATE TABLE users (id INTEGER, name TEXT);
+             CREATE TABLE orders (id INTEGER, user_id INTEGER);",
+        );
+        state
+            .rule_levels
+            .insert(DiagnosticKind::TypeMismatch, RuleLevel::Warning);
+        state
+            .rule_levels
+            .insert(DiagnosticKind::AmbiguousColumn, RuleLevel::Off);
+
+        let diagnostics = state.analyze_document("SELECT name FROM users WHERE id = 'one'");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "E0003");
+        assert_eq!(diagnostics[0].severity, sqlsift_core::Severity::Warning);
+
+        assert!(state
+            .analyze_document("SELECT id FROM users, orders WHERE users.id = orders.id")
+            .is_empty());
+    }
+
     #[test]
     fn test_is_schema_file() {
         let mut state = ServerState::new();
@@ -303,6 +500,103 @@ mod tests {
         assert!(!state.is_schema_file(Path::new("/tmp/other.sql")));
     }
 
+    /// A state loading `files` (name and contents) from a fresh directory
+    fn state_with_schema_files(test: &str, files: &[(&str, &str)]) -> ServerState {
+        let dir = std::env::temp_dir().join(format!("sqlsift-lsp-{}-{test}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut state = ServerState::new();
+        for (name, contents) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, contents).unwrap();
+            state.schema_files.push(path);
+        }
+        assert!(state.rebuild_catalog().is_empty());
+        state
+    }
+
+    #[test]
+    fn test_update_schema_file_reparses_only_that_file() {
+        let mut state = state_with_schema_files(
+            "incremental",
+            &[
+                ("users.sql", "CREATE TABLE users (id INTEGER, name TEXT);"),
+                ("orders.sql", "CREATE TABLE orders (id INTEGER);"),
+            ],
+        );
+        let (users, orders) = (state.schema_files[0].clone(), state.schema_files[1].clone());
+
+        // orders.sql changes on disk too, but only users.sql was saved
+        std::fs::write(&users, "CREATE TABLE users (id INTEGER, email TEXT);").unwrap();
+        std::fs::write(&orders, "CREATE TABLE orders (id INTEGER, total NUMERIC);").unwrap();
+        assert!(state.update_schema_file(&users).is_empty());
+
+        assert!(state.analyze_document("SELECT email FROM users").is_empty());
+        assert_eq!(
+            state.analyze_document("SELECT name FROM users")[0].code(),
+            "E0002"
+        );
+        assert_eq!(
+            state.analyze_document("SELECT total FROM orders")[0].code(),
+            "E0002"
+        );
+
+        std::fs::remove_dir_all(users.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_update_schema_file_falls_back_to_rebuild() {
+        let mut state = state_with_schema_files(
+            "rebuild",
+            &[
+                ("users.sql", "CREATE TABLE users (id INTEGER);"),
+                ("alter.sql", "ALTER TABLE users ADD COLUMN name TEXT;"),
+            ],
+        );
+        let users = state.schema_files[0].clone();
+
+        // Re-parsing users.sql alone would lose the column alter.sql adds
+        std::fs::write(&users, "CREATE TABLE users (id INTEGER, email TEXT);").unwrap();
+        assert!(state.update_schema_file(&users).is_empty());
+        assert!(state
+            .analyze_document("SELECT name, email FROM users")
+            .is_empty());
+
+        std::fs::remove_dir_all(users.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_catalog_cache_keeps_warnings_and_skips_parse_errors() {
+        let dir = std::env::temp_dir().join(format!("sqlsift-lsp-{}-cache", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let schema = dir.join("schema.sql");
+        let cache = dir.join("catalog.json");
+        let _ = std::fs::remove_file(&cache);
+        let mut state = ServerState::new();
+        state.schema_files.push(schema.clone());
+        state.cache_file = Some(cache.clone());
+
+        std::fs::write(
+            &schema,
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, code TEXT PRIMARY KEY);",
+        )
+        .unwrap();
+        assert!(!state.rebuild_catalog().is_empty());
+        assert!(!cache.exists(), "a schema with parse errors was cached");
+
+        // Schema warnings are reported on a cache hit too
+        std::fs::write(
+            &schema,
+            "CREATE TABLE users (id INTEGER); CREATE TABLE users (id BIGINT);",
+        )
+        .unwrap();
+        let errors = state.rebuild_catalog();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(cache.exists());
+        assert_eq!(state.rebuild_catalog(), errors);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_new_state_defaults() {
         let state = ServerState::new();
@@ -323,6 +617,42 @@ mod tests {
         assert!(hover.contains("| age | integer | NULL |"));
     }
 
+    #[test]
+    fn test_hover_info_defaults() {
+        let state = state_with_schema(
+            "CREATE TABLE users (id SERIAL, created_at TIMESTAMP DEFAULT now(), score INTEGER DEFAULT 0, name TEXT);",
+        );
+        let hover = state.hover_info("users").unwrap();
+        assert!(hover.contains("| Column | Type | Nullable | Default |"));
+        assert!(
+            hover.contains("| id | integer | NULL | `nextval('users_id_seq')` |"),
+            "{hover}"
+        );
+        assert!(hover.contains("| score | integer | NULL | `0` |"));
+        assert!(hover.contains("| name | text | NULL |  |"));
+
+        let hover = state.hover_info("created_at").unwrap();
+        assert!(hover.ends_with("Default: `now()`"), "{hover}");
+        assert!(!state.hover_info("name").unwrap().contains("Default:"));
+    }
+
+    #[test]
+    fn test_hover_info_table_indexes() {
+        let state = state_with_schema(
+            "CREATE TABLE users (id INTEGER NOT NULL, email TEXT, org_id INTEGER);\n\
+             CREATE UNIQUE INDEX idx_email ON users (email);\n\
+             CREATE INDEX idx_org ON users (org_id, id) WHERE email IS NOT NULL;",
+        );
+        let hover = state.hover_info("users").unwrap();
+        let indexes = hover.split("Indexes:").nth(1).expect("Indexes section");
+        assert!(indexes.contains("- idx_email (UNIQUE) on (email)"));
+        assert!(indexes.contains("- idx_org on (org_id, id) WHERE email IS NOT NULL"));
+
+        // No section without indexes
+        let state = state_with_schema("CREATE TABLE users (id INTEGER);");
+        assert!(!state.hover_info("users").unwrap().contains("Indexes:"));
+    }
+
     #[test]
     fn test_hover_info_view() {
         let state = state_with_schema(
@@ -400,4 +730,62 @@ mod tests {
         let items = state.completion_items();
         assert!(items.is_empty());
     }
+
+    fn state_with_schema_file(path: &str, schema_sql: &str) -> ServerState {
+        let mut state = ServerState::new();
+        let mut builder = SchemaBuilder::new();
+        builder.parse_file(path, schema_sql).unwrap();
+        let (catalog, _) = builder.build();
+        state.catalog = catalog;
+        state
+    }
+
+    #[test]
+    fn test_definition_location_table() {
+        let state = state_with_schema_file(
+            "/project/schema.sql",
+            "CREATE TABLE users (id INTEGER);\nCREATE TABLE orders (id INTEGER, user_id INTEGER);",
+        );
+        let text = "SELECT id FROM orders";
+        let location = state.definition_location(text, 0, 16, "orders").unwrap();
+
+        assert_eq!(
+            location.uri,
+            Url::from_file_path("/project/schema.sql").unwrap()
+        );
+        assert_eq!(location.range.start, lsp_types::Position::new(1, 13));
+        assert_eq!(location.range.end, lsp_types::Position::new(1, 19));
+    }
+
+    #[test]
+    fn test_definition_location_column() {
+        let state = state_with_schema_file(
+            "/project/schema.sql",
+            "CREATE TABLE users (\n    id INTEGER,\n    name TEXT\n);\n\
+             CREATE TABLE orders (id INTEGER, user_id INTEGER);",
+        );
+        // `o.id` resolves to orders.id, not users.id
+        let text = "SELECT o.id, u.name FROM orders o JOIN users u ON u.id = o.user_id";
+        let location = state.definition_location(text, 0, 9, "id").unwrap();
+        assert_eq!(location.range.start, lsp_types::Position::new(4, 21));
+
+        let location = state.definition_location(text, 0, 15, "name").unwrap();
+        assert_eq!(location.range.start, lsp_types::Position::new(2, 4));
+        assert_eq!(location.range.end, lsp_types::Position::new(2, 8));
+    }
+
+    #[test]
+    fn test_definition_location_unknown() {
+        let state =
+            state_with_schema_file("/project/schema.sql", "CREATE TABLE users (id INTEGER);");
+        assert!(state
+            .definition_location("SELECT 1 FROM missing", 0, 15, "missing")
+            .is_none());
+
+        // Without a schema file there is nowhere to jump to
+        let state = state_with_schema("CREATE TABLE users (id INTEGER);");
+        assert!(state
+            .definition_location("SELECT id FROM users", 0, 16, "users")
+            .is_none());
+    }
 }
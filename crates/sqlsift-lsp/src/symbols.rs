@@ -0,0 +1,168 @@
+//! Document outline for schema files
+//!
+//! The document is run through the schema builder on its own, and every
+//! table, view and enum it declares becomes a symbol at its source location.
+
+use tower_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
+
+use sqlsift_core::schema::{SchemaBuilder, SourceLocation};
+use sqlsift_core::SqlDialect;
+
+/// Tables (with their columns), views and enums declared in `text`, in
+/// source order
+pub fn document_symbols(text: &str, dialect: SqlDialect) -> Vec<DocumentSymbol> {
+    let mut builder = SchemaBuilder::with_dialect(dialect);
+    // Statements that fail to parse are skipped; the rest still get symbols
+    let _ = builder.parse(text);
+    let (catalog, _) = builder.build();
+
+    let mut symbols = Vec::new();
+    for schema in catalog.schemas.values() {
+        for table in schema.tables.values() {
+            let Some(name_range) = table.location.as_ref().map(to_range) else {
+                continue;
+            };
+            let columns: Vec<DocumentSymbol> = table
+                .columns
+                .values()
+                .filter_map(|col| {
+                    let range = to_range(col.location.as_ref()?);
+                    Some(symbol(
+                        col.name.clone(),
+                        Some(col.data_type.display_name()),
+                        SymbolKind::FIELD,
+                        range,
+                        range,
+                        None,
+                    ))
+                })
+                .collect();
+            // Stretch the table over its columns so that they nest inside it
+            let end = columns
+                .iter()
+                .map(|c| c.range.end)
+                .fold(name_range.end, Position::max);
+            symbols.push(symbol(
+                table.name.to_string(),
+                Some("table".to_string()),
+                SymbolKind::STRUCT,
+                Range::new(name_range.start, end),
+                name_range,
+                Some(columns),
+            ));
+        }
+        for view in schema.views.values() {
+            let Some(range) = view.location.as_ref().map(to_range) else {
+                continue;
+            };
+            let kind = if view.materialized {
+                "materialized view"
+            } else {
+                "view"
+            };
+            symbols.push(symbol(
+                view.name.to_string(),
+                Some(kind.to_string()),
+                SymbolKind::INTERFACE,
+                range,
+                range,
+                None,
+            ));
+        }
+    }
+    for enum_def in catalog.enums.values() {
+        let Some(range) = enum_def.location.as_ref().map(to_range) else {
+            continue;
+        };
+        symbols.push(symbol(
+            enum_def.name.clone(),
+            Some(enum_def.values.join(", ")),
+            SymbolKind::ENUM,
+            range,
+            range,
+            None,
+        ));
+    }
+
+    symbols.sort_by_key(|s| s.selection_range.start);
+    symbols
+}
+
+#[allow(deprecated)] // `DocumentSymbol::deprecated` has to be set
+fn symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Option<Vec<DocumentSymbol>>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children,
+    }
+}
+
+fn to_range(location: &SourceLocation) -> Range {
+    let span = &location.span;
+    let start = Position::new(
+        span.line.saturating_sub(1) as u32,
+        span.column.saturating_sub(1) as u32,
+    );
+    Range::new(
+        start,
+        Position::new(start.line, start.character + span.length as u32),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_symbol_tree() {
+        let text = "CREATE TYPE mood AS ENUM ('ok', 'sad');\n\
+                    CREATE TABLE users (\n    id INTEGER NOT NULL,\n    name TEXT\n);\n\
+                    CREATE VIEW user_names AS SELECT name FROM users;\n\
+                    CREATE TABLE app.orders (id INTEGER, total NUMERIC);";
+        let symbols = document_symbols(text, SqlDialect::default());
+
+        let outline: Vec<(&str, SymbolKind)> =
+            symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect();
+        assert_eq!(
+            outline,
+            vec![
+                ("mood", SymbolKind::ENUM),
+                ("users", SymbolKind::STRUCT),
+                ("user_names", SymbolKind::INTERFACE),
+                ("app.orders", SymbolKind::STRUCT),
+            ]
+        );
+
+        let users = &symbols[1];
+        let columns = users.children.as_ref().unwrap();
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "name"]);
+        assert!(columns.iter().all(|c| c.kind == SymbolKind::FIELD));
+        assert_eq!(columns[0].detail.as_deref(), Some("integer"));
+        assert_eq!(users.selection_range.start, Position::new(1, 13));
+        assert_eq!(columns[1].range.start, Position::new(3, 4));
+        // Columns lie inside their table's range
+        assert_eq!(users.range.end, columns[1].range.end);
+
+        assert!(symbols[2].children.is_none());
+        assert_eq!(symbols[3].children.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_no_symbols_for_queries() {
+        let symbols = document_symbols("SELECT id FROM users;", SqlDialect::default());
+        assert!(symbols.is_empty());
+    }
+}